@@ -8,7 +8,6 @@ import {
   Transaction,
   TransactionInstruction,
   SYSVAR_RENT_PUBKEY,
-  Keypair,
   SendTransactionError,
 } from '@solana/web3.js';
 import {
@@ -17,6 +16,7 @@ import {
   getAssociatedTokenAddressSync,
 } from '@solana/spl-token';
 import { useWallet } from '@solana/wallet-adapter-react';
+import { keccak_256 } from '@noble/hashes/sha3';
 import { useRouter } from 'next/navigation';
 import { useToast } from '@/components/ui/Toast';
 import { getConnection } from '@/lib/actions/connection';
@@ -152,25 +152,47 @@ export default function CreateMarketPage() {
     const now = Math.floor(Date.now() / 1000);
     const cutoffTs = BigInt(now + Math.floor(mins * 60));
 
-    // Program-owned market account
-    const marketKp = Keypair.generate();
-
     // PDAs your program expects
-    const [vaultAuthority] = PublicKey.findProgramAddressSync(
-      [new TextEncoder().encode('vault-auth'), marketKp.publicKey.toBuffer()],
-      PROGRAM_ID
-    );
     const [programStats] = PublicKey.findProgramAddressSync(
       [new TextEncoder().encode('program-stats')],
       PROGRAM_ID
     );
+    // Market PDA is derived from the global market counter (total_markets,
+    // right after the 8-byte discriminator and 32-byte authority).
+    const statsInfo = await connection.getAccountInfo(programStats);
+    if (!statsInfo) {
+      setError('Program is not initialized.');
+      return;
+    }
+    const marketId = statsInfo.data.subarray(40, 48);
+    const [market] = PublicKey.findProgramAddressSync(
+      [new TextEncoder().encode('market'), marketId],
+      PROGRAM_ID
+    );
+    const [vaultAuthority] = PublicKey.findProgramAddressSync(
+      [new TextEncoder().encode('vault-auth'), market.toBuffer()],
+      PROGRAM_ID
+    );
     // Market metadata PDA (derived from market address)
     const [marketMetadata] = PublicKey.findProgramAddressSync(
-      [new TextEncoder().encode('market-metadata'), marketKp.publicKey.toBuffer()],
+      [new TextEncoder().encode('market-metadata'), market.toBuffer()],
       PROGRAM_ID
     );
     // Program expects the ATA (vault) for the vaultAuthority PDA
     const vaultAta = getAssociatedTokenAddressSync(MINT, vaultAuthority, true);
+    const [config] = PublicKey.findProgramAddressSync([te.encode('config')], PROGRAM_ID);
+    // Only needed (and only exists) while the mint allowlist is on.
+    const [approvedMintPda] = PublicKey.findProgramAddressSync(
+      [te.encode('approved-mint'), MINT.toBuffer()],
+      PROGRAM_ID
+    );
+    const approvedMint = (await connection.getAccountInfo(approvedMintPda)) ? approvedMintPda : null;
+    // Duplicate guard: keccak(question || cutoff_ts || mint), as `market_guard_hash`.
+    const guardHash = keccak_256(concatBytes(te.encode(trimmedName), u64le(cutoffTs), MINT.toBytes()));
+    const [marketGuard] = PublicKey.findProgramAddressSync(
+      [te.encode('market-guard'), guardHash],
+      PROGRAM_ID
+    );
 
     try {
       setBusy(true);
@@ -191,24 +213,45 @@ export default function CreateMarketPage() {
       const categoryLen = new Uint8Array(4);
       new DataView(categoryLen.buffer).setUint32(0, categoryBytes.length, true);
       
+      // Trailing args: claim_deadline_ts, event_ts, oracle_threshold and
+      // resolve_authority are all None (one zero byte each), then
+      // allow_duplicate = false.
+      const NONE = new Uint8Array([0]);
       const data = concatBytes(
         disc,
         cutoffBytes,
         questionLen,
         questionBytes,
         categoryLen,
-        categoryBytes
+        categoryBytes,
+        NONE,
+        NONE,
+        NONE,
+        NONE,
+        new Uint8Array([0])
       );
 
+      // Anchor reads an omitted Option account as the program id.
+      const omitted = { pubkey: PROGRAM_ID, isSigner: false, isWritable: false };
+
       // Account order MUST match program definition (IDL)
       const keys = [
         { pubkey: publicKey, isSigner: true, isWritable: true }, // owner (payer)
-        { pubkey: marketKp.publicKey, isSigner: true, isWritable: true }, // market (init)
-        { pubkey: marketMetadata, isSigner: false, isWritable: true }, // market_metadata (PDA)
         { pubkey: programStats, isSigner: false, isWritable: true }, // program_stats (PDA)
+        omitted, // operator
+        { pubkey: market, isSigner: false, isWritable: true }, // market (PDA, init)
+        { pubkey: marketMetadata, isSigner: false, isWritable: true }, // market_metadata (PDA)
+        { pubkey: config, isSigner: false, isWritable: false }, // config (PDA)
         { pubkey: MINT, isSigner: false, isWritable: false }, // bet_mint
+        approvedMint
+          ? { pubkey: approvedMint, isSigner: false, isWritable: false } // approved_mint (PDA)
+          : omitted,
+        { pubkey: marketGuard, isSigner: false, isWritable: true }, // market_guard (PDA)
         { pubkey: vaultAuthority, isSigner: false, isWritable: false }, // vault_authority (PDA)
         { pubkey: vaultAta, isSigner: false, isWritable: true }, // vault (ATA)
+        omitted, // resolver
+        omitted, // oracle_feed
+        omitted, // fee_destination
         { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
         { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
         { pubkey: ASSOCIATED_TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
@@ -222,7 +265,6 @@ export default function CreateMarketPage() {
       tx.recentBlockhash = blockhash;
       tx.add(memoIx(`CreateMarket:${trimmedName.slice(0, 40)}`, publicKey));
       tx.add(ixProgram);
-      tx.partialSign(marketKp);
 
       if (!wallet.signTransaction)
         throw new Error('Wallet does not support transaction signing.');
@@ -245,7 +287,7 @@ export default function CreateMarketPage() {
         'confirmed'
       );
 
-      const marketAddr = marketKp.publicKey.toBase58();
+      const marketAddr = market.toBase58();
       saveMarketName(marketAddr, trimmedName);
       const url = `${window.location.origin}/market/${marketAddr}?title=${encodeURIComponent(
         trimmedName
@@ -191,6 +191,12 @@ export async function buildPlaceBetIx(
     market: PublicKey;
     side: "yes" | "no";
     amountBaseUnits: string;
+    /** Abort if the payout multiple after the bet is below this (bps, 20000 = 2x). */
+    minOddsBps?: number;
+    /** Abort if the effective fee rate is above this (bps). */
+    maxFeeBps?: number;
+    /** Pay a 10% premium to get half the stake back if the bet loses. */
+    insure?: boolean;
     overrides?: Partial<AccountDict>;
     priorityFeeMicroLamports?: number;
   }
@@ -241,7 +247,7 @@ export async function buildPlaceBetIx(
 
   const ix = await buildIx(
     "placeBet",
-    { side: params.side, amount: new BN(params.amountBaseUnits) },
+    { side: params.side, amount: new BN(params.amountBaseUnits), minOddsBps: params.minOddsBps ?? null, maxFeeBps: params.maxFeeBps ?? null, insure: params.insure ?? false },
     accounts
   );
 
@@ -302,6 +308,8 @@ export async function buildClaimWinningsIx(
   params: {
     claimer: PublicKey;
     market: PublicKey;
+    /** Partial claim in base units; omit to claim everything available. */
+    amount?: number | BN;
     overrides?: Partial<AccountDict>;
     priorityFeeMicroLamports?: number;
   }
@@ -345,7 +353,8 @@ export async function buildClaimWinningsIx(
 
   const accounts: Partial<AccountDict> = { ...base, ...(params.overrides || {}) };
 
-  const ix = await buildIx("claimWinnings", {}, accounts);
+  const amount = params.amount != null ? new BN(params.amount) : null;
+  const ix = await buildIx("claimWinnings", { amount }, accounts);
   ixs.push(ix);
   return ixs;
 }
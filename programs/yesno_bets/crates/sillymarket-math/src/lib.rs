@@ -0,0 +1,82 @@
+//! Pure fee, cap, payout and reward math for yes/no markets.
+//!
+//! Everything here is integer-only and free of Anchor types so the on-chain
+//! program and off-chain clients compute identical numbers. Functions return
+//! `None` on arithmetic overflow or division by zero.
+
+#![no_std]
+
+pub const BPS_DENOM: u64 = 10_000;
+/// Fixed-point scale of the liquidity-mining reward index.
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+/// Fee taken from a gross `amount` at `fee_bps`, rounded down.
+pub fn fee(amount: u64, fee_bps: u64) -> Option<u64> {
+    amount.checked_mul(fee_bps)?.checked_div(BPS_DENOM)
+}
+
+/// Splits a gross `amount` into `(net, fee)`; the two always sum to `amount`.
+pub fn split_fee(amount: u64, fee_bps: u64) -> Option<(u64, u64)> {
+    let fee = fee(amount, fee_bps)?;
+    Some((amount.checked_sub(fee)?, fee))
+}
+
+/// Largest gross stake a position may hold: `limit` whole tokens at `decimals`.
+pub fn max_position_total(limit: u64, decimals: u8) -> Option<u128> {
+    (limit as u128).checked_mul(10u128.checked_pow(decimals as u32)?)
+}
+
+/// Whether adding `amount` to a position already holding `current` gross stays
+/// within `limit` whole tokens.
+pub fn within_cap(current: u128, amount: u64, limit: u64, decimals: u8) -> Option<bool> {
+    let new_total = current.checked_add(amount as u128)?;
+    Some(new_total <= max_position_total(limit, decimals)?)
+}
+
+/// Pro-rata share of `total_pool` for `stake` out of `winning_pool`, rounded down.
+pub fn pro_rata_payout(total_pool: u128, winning_pool: u128, stake: u64) -> Option<u64> {
+    let payout = total_pool.checked_mul(stake as u128)?.checked_div(winning_pool)?;
+    u64::try_from(payout).ok()
+}
+
+/// Refund owed on a voided market: exactly the net stake.
+pub fn void_refund(net_stake: u64) -> u64 {
+    net_stake
+}
+
+/// Caps `payout` at `stake * max_multiplier_bps / BPS_DENOM`. A zero multiplier
+/// means uncapped. Returns `(capped_payout, clipped_excess)`.
+pub fn apply_odds_cap(payout: u64, stake: u64, max_multiplier_bps: u32) -> Option<(u64, u64)> {
+    if max_multiplier_bps == 0 {
+        return Some((payout, 0));
+    }
+    let max = (stake as u128).checked_mul(max_multiplier_bps as u128)? / BPS_DENOM as u128;
+    if (payout as u128) <= max {
+        return Some((payout, 0));
+    }
+    let max = max as u64;
+    Some((max, payout - max))
+}
+
+/// Growth of the reward index when `rate` tokens per second are spread over
+/// `stake` for `elapsed` seconds, rounded down. Nothing accrues on zero stake.
+pub fn reward_index_delta(elapsed: u64, rate: u64, stake: u64) -> Option<u128> {
+    if stake == 0 {
+        return Some(0);
+    }
+    (elapsed as u128)
+        .checked_mul(rate as u128)?
+        .checked_mul(REWARD_PRECISION)?
+        .checked_div(stake as u128)
+}
+
+/// Rewards `amount` has earned at `index` since joining; stored as the
+/// position's debt so earlier emissions are not paid again.
+pub fn reward_debt(index: u128, amount: u64) -> Option<u128> {
+    Some((amount as u128).checked_mul(index)? / REWARD_PRECISION)
+}
+
+/// Rewards `amount` has earned at `index` beyond `debt`, rounded down.
+pub fn rewards_earned(index: u128, amount: u64, debt: u128) -> Option<u64> {
+    u64::try_from(reward_debt(index, amount)?.saturating_sub(debt)).ok()
+}
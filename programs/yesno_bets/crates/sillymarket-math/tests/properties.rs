@@ -0,0 +1,93 @@
+use proptest::prelude::*;
+use sillymarket_math::*;
+
+const FEE_BPS: u64 = 250;
+const MIN_GROSS: u64 = 1_000;
+
+proptest! {
+    #[test]
+    fn split_fee_is_exact(amount in 0..=u64::MAX / BPS_DENOM, fee_bps in 0..=BPS_DENOM) {
+        let (net, fee) = split_fee(amount, fee_bps).unwrap();
+        prop_assert_eq!(net as u128 + fee as u128, amount as u128);
+        prop_assert!(fee <= amount);
+    }
+
+    #[test]
+    fn payouts_never_exceed_pool(
+        stakes in prop::collection::vec(1..=1_000_000_000u64, 1..32),
+        losing in 0..=1_000_000_000_000u64,
+    ) {
+        let winning_pool: u128 = stakes.iter().map(|s| *s as u128).sum();
+        let total_pool = winning_pool + losing as u128;
+
+        let mut paid: u128 = 0;
+        for stake in &stakes {
+            let payout = pro_rata_payout(total_pool, winning_pool, *stake).unwrap();
+            prop_assert!(payout as u128 <= total_pool);
+            prop_assert!(payout >= *stake);
+            paid += payout as u128;
+        }
+        prop_assert!(paid <= total_pool);
+    }
+
+    #[test]
+    fn void_refunds_exactly_net(gross in MIN_GROSS..=u64::MAX / BPS_DENOM) {
+        let (net, _) = split_fee(gross, FEE_BPS).unwrap();
+        prop_assert_eq!(void_refund(net), net);
+    }
+
+    #[test]
+    fn cap_check_matches_limit(current in 0..=u64::MAX as u128, amount in any::<u64>(), decimals in 0..=9u8) {
+        let max = max_position_total(100, decimals).unwrap();
+        let ok = within_cap(current, amount, 100, decimals).unwrap();
+        prop_assert_eq!(ok, current + amount as u128 <= max);
+    }
+
+    #[test]
+    fn odds_cap_conserves_payout(payout in any::<u64>(), stake in any::<u64>(), bps in any::<u32>()) {
+        let (capped, clipped) = apply_odds_cap(payout, stake, bps).unwrap();
+        prop_assert_eq!(capped as u128 + clipped as u128, payout as u128);
+        prop_assert!(capped <= payout);
+    }
+
+    #[test]
+    fn rewards_never_exceed_emission(
+        stakes in prop::collection::vec(1..=1_000_000_000_000u64, 1..32),
+        rate in 0..=1_000_000_000u64,
+        elapsed in 0..=31_536_000u64,
+    ) {
+        let total: u64 = stakes.iter().sum();
+        let index = reward_index_delta(elapsed, rate, total).unwrap();
+        let emitted = elapsed as u128 * rate as u128;
+
+        let paid: u128 = stakes.iter().map(|s| rewards_earned(index, *s, 0).unwrap() as u128).sum();
+        prop_assert!(paid <= emitted);
+        // Each position rounds down by under one token, and so does the index.
+        prop_assert!(emitted - paid <= stakes.len() as u128 + total as u128 / REWARD_PRECISION + 1);
+    }
+
+    #[test]
+    fn late_joiner_earns_only_later_emissions(
+        early in 1..=1_000_000_000_000u64,
+        late in 1..=1_000_000_000_000u64,
+        rate in 1..=1_000_000u64,
+        before in 0..=1_000_000u64,
+        after in 0..=1_000_000u64,
+    ) {
+        // Only `early` is staked for `before` seconds, then both for `after`.
+        let first = reward_index_delta(before, rate, early).unwrap();
+        let debt = reward_debt(first, late).unwrap();
+        let index = first + reward_index_delta(after, rate, early + late).unwrap();
+
+        let earned = rewards_earned(index, late, debt).unwrap() as u128;
+        let fair = after as u128 * rate as u128 * late as u128 / (early as u128 + late as u128);
+        // Both the index and the two debt snapshots round down.
+        prop_assert!(earned <= fair + 1);
+        prop_assert!(fair <= earned + 1);
+    }
+
+    #[test]
+    fn zero_stake_accrues_nothing(elapsed in any::<u64>(), rate in any::<u64>()) {
+        prop_assert_eq!(reward_index_delta(elapsed, rate, 0), Some(0));
+    }
+}
@@ -0,0 +1,784 @@
+//! LiteSVM harness for `yesno_bets`.
+//!
+//! Loads the program built by `anchor build` (override the path with
+//! `YESNO_BETS_SO`) into an in-process SVM with signature verification off,
+//! so admin instructions can be signed as the hardcoded owner without its key.
+//! The fixtures cover the program singletons, a 6-decimal bet mint, funded
+//! bettors and markets; scenario tests live in `tests/`.
+//!
+//! Run `anchor build` before `cargo test`: scenarios start from
+//! `TestEnv::try_new`, which skips them with a message when the `.so` is
+//! missing.
+
+use std::str::FromStr;
+
+use anchor_lang::prelude::Clock;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token::TokenAccount;
+use litesvm::types::TransactionResult;
+use litesvm::LiteSVM;
+use litesvm_token::{CreateAssociatedTokenAccount, CreateMint, MintTo};
+use solana_keccak_hasher as keccak;
+use solana_sdk::{
+    ed25519_instruction::new_ed25519_instruction_with_signature,
+    instruction::InstructionError,
+    message::Message,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use yesno_bets::{Market, Operator, Outcome, ProgramStats, ResolutionReason};
+
+pub const DECIMALS: u8 = 6;
+pub const ONE_TOKEN: u64 = 10u64.pow(DECIMALS as u32);
+pub const INSURANCE_SHARE_BPS: u16 = 1_000;
+
+const DEFAULT_SO_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../target/deploy/yesno_bets.so");
+const START_TS: i64 = 1_700_000_000;
+
+pub struct TestEnv {
+    pub svm: LiteSVM,
+    /// Fee payer for every transaction; also the bet mint authority.
+    pub payer: Keypair,
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+}
+
+impl TestEnv {
+    /// `new`, or `None` after printing a skip notice when the program hasn't
+    /// been built.
+    pub fn try_new() -> Option<Self> {
+        let so_path = so_path();
+        if !std::path::Path::new(&so_path).exists() {
+            eprintln!("skipping: {so_path} not found (run `anchor build` first)");
+            return None;
+        }
+        Some(Self::new())
+    }
+
+    /// Fresh SVM with the program loaded, program stats, config and insurance
+    /// fund initialised, and a bet mint ready to fund bettors from.
+    pub fn new() -> Self {
+        let so_path = so_path();
+        let mut svm = LiteSVM::new().with_sigverify(false);
+        svm.add_program_from_file(yesno_bets::ID, &so_path)
+            .unwrap_or_else(|e| panic!("load {so_path} (run `anchor build` first): {e}"));
+
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = START_TS;
+        svm.set_sysvar(&clock);
+
+        let payer = Keypair::new();
+        let owner = Pubkey::from_str(yesno_bets::OWNER).unwrap();
+        svm.airdrop(&payer.pubkey(), 100_000_000_000).unwrap();
+        svm.airdrop(&owner, 100_000_000_000).unwrap();
+
+        let mint = CreateMint::new(&mut svm, &payer)
+            .decimals(DECIMALS)
+            .send()
+            .unwrap();
+
+        let mut env = Self { svm, payer, owner, mint };
+        env.init_program();
+        env
+    }
+
+    fn init_program(&mut self) {
+        let owner = self.owner;
+        let ixs = vec![
+            ix(
+                yesno_bets::accounts::InitializeProgram {
+                    owner,
+                    program_stats: program_stats_pda(),
+                    system_program: system_program(),
+                },
+                yesno_bets::instruction::InitializeProgram {},
+            ),
+            ix(
+                yesno_bets::accounts::InitConfig {
+                    owner,
+                    config: pda(&[yesno_bets::CONFIG_SEED]),
+                    system_program: system_program(),
+                },
+                yesno_bets::instruction::InitConfig {},
+            ),
+            ix(
+                yesno_bets::accounts::InitInsuranceFund {
+                    owner,
+                    insurance_fund: pda(&[yesno_bets::INSURANCE_FUND_SEED]),
+                    system_program: system_program(),
+                },
+                yesno_bets::instruction::InitInsuranceFund {
+                    fee_share_bps: INSURANCE_SHARE_BPS,
+                },
+            ),
+        ];
+        self.send(&ixs, &[]).unwrap();
+    }
+
+    /// Sends `ixs` paid by the harness payer. `signers` are signed for real;
+    /// the owner (and anyone else required) gets a blank signature.
+    #[allow(clippy::result_large_err)]
+    pub fn send(&mut self, ixs: &[Instruction], signers: &[&Keypair]) -> TransactionResult {
+        let mut all: Vec<&Keypair> = vec![&self.payer];
+        all.extend_from_slice(signers);
+
+        let message = Message::new(ixs, Some(&self.payer.pubkey()));
+        let mut tx = Transaction::new_unsigned(message);
+        tx.partial_sign(&all, self.svm.latest_blockhash());
+        let result = self.svm.send_transaction(tx);
+        // Identical retries would otherwise be rejected as already processed.
+        self.svm.expire_blockhash();
+        result
+    }
+
+    pub fn now(&self) -> i64 {
+        self.svm.get_sysvar::<Clock>().unix_timestamp
+    }
+
+    pub fn warp_to(&mut self, ts: i64) {
+        let mut clock = self.svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = ts;
+        self.svm.set_sysvar(&clock);
+    }
+
+    /// A wallet with SOL for rent and `tokens` whole bet tokens in its ATA.
+    pub fn new_bettor(&mut self, tokens: u64) -> Keypair {
+        let bettor = Keypair::new();
+        self.svm.airdrop(&bettor.pubkey(), 10_000_000_000).unwrap();
+        let ata = CreateAssociatedTokenAccount::new(&mut self.svm, &self.payer, &self.mint)
+            .owner(&bettor.pubkey())
+            .send()
+            .unwrap();
+        if tokens > 0 {
+            MintTo::new(&mut self.svm, &self.payer, &self.mint, &ata, tokens * ONE_TOKEN)
+                .send()
+                .unwrap();
+        }
+        bettor
+    }
+
+    /// Creates a market closing at `cutoff_ts` and returns its address.
+    pub fn create_market(&mut self, cutoff_ts: i64) -> Pubkey {
+        let stats: ProgramStats = self.account(&program_stats_pda());
+        let market = pda(&[yesno_bets::MARKET_SEED, &stats.total_markets.to_le_bytes()]);
+        let create = self.create_market_ix(&market, &self.owner, None, cutoff_ts);
+        self.send(&[create], &[]).unwrap();
+        market
+    }
+
+    /// `create_market` at `market` signed by `creator`, optionally in
+    /// `operator`'s namespace.
+    pub fn create_market_ix(&self, market: &Pubkey, creator: &Pubkey, operator: Option<Pubkey>, cutoff_ts: i64) -> Instruction {
+        let market = *market;
+        let stats: ProgramStats = self.account(&program_stats_pda());
+        let question = format!("Scenario market #{}", stats.total_markets);
+        let guard_hash = keccak::hashv(&[question.as_bytes(), &cutoff_ts.to_le_bytes(), self.mint.as_ref()]).0;
+        let vault_authority = vault_authority_pda(&market);
+
+        let create = ix(
+            yesno_bets::accounts::CreateMarket {
+                owner: *creator,
+                program_stats: program_stats_pda(),
+                operator,
+                market,
+                market_metadata: pda(&[b"market-metadata", market.as_ref()]),
+                config: pda(&[yesno_bets::CONFIG_SEED]),
+                bet_mint: self.mint,
+                approved_mint: None,
+                market_guard: Some(pda(&[yesno_bets::MARKET_GUARD_SEED, &guard_hash])),
+                vault_authority,
+                vault: ata(&vault_authority, &self.mint),
+                resolver: None,
+                oracle_feed: None,
+                fee_destination: None,
+                system_program: system_program(),
+                token_program: anchor_spl::token::ID,
+                associated_token_program: anchor_spl::associated_token::ID,
+            },
+            yesno_bets::instruction::CreateMarket {
+                cutoff_ts,
+                question,
+                category: "test".to_string(),
+                claim_deadline_ts: None,
+                event_ts: None,
+                oracle_threshold: None,
+                resolve_authority: None,
+                allow_duplicate: false,
+            },
+        );
+        create
+    }
+
+    /// Registers operator `operator_id` run by `admin`, with `platform_cut_bps`
+    /// of its fees going to the treasury, and returns its address.
+    pub fn register_operator(&mut self, operator_id: u64, admin: &Pubkey, platform_cut_bps: u16) -> Pubkey {
+        let operator = pda(&[yesno_bets::OPERATOR_SEED, &operator_id.to_le_bytes()]);
+        let register = ix(
+            yesno_bets::accounts::RegisterOperator {
+                owner: self.owner,
+                operator,
+                system_program: system_program(),
+            },
+            yesno_bets::instruction::RegisterOperator {
+                operator_id,
+                admin: *admin,
+                fee_receiver: *admin,
+                platform_cut_bps,
+            },
+        );
+        self.send(&[register], &[]).unwrap();
+        operator
+    }
+
+    /// Address of the next market in `operator`'s namespace.
+    pub fn next_operator_market(&self, operator: &Pubkey) -> Pubkey {
+        let op: Operator = self.account(operator);
+        let seed = [op.operator_id.to_le_bytes(), op.market_count.to_le_bytes()].concat();
+        pda(&[yesno_bets::MARKET_SEED, &seed])
+    }
+
+    pub fn place_bet_ix(&self, market: &Pubkey, bettor: &Pubkey, outcome: Outcome, amount: u64) -> Instruction {
+        self.place_bet_ix_with_vault(market, bettor, outcome, amount, &self.vault(market))
+    }
+
+    pub fn place_bet_ix_with_vault(
+        &self,
+        market: &Pubkey,
+        bettor: &Pubkey,
+        outcome: Outcome,
+        amount: u64,
+        vault: &Pubkey,
+    ) -> Instruction {
+        ix(
+            self.place_bet_accounts(market, bettor, vault),
+            yesno_bets::instruction::PlaceBet {
+                outcome,
+                amount,
+                position_index: 0,
+                tag: None,
+                min_odds_bps: None,
+                max_fee_bps: None,
+                insure: false,
+            },
+        )
+    }
+
+    /// `place_bet` accounts for `bettor`'s index-0 position with every
+    /// optional feature left out.
+    pub fn place_bet_accounts(&self, market: &Pubkey, bettor: &Pubkey, vault: &Pubkey) -> yesno_bets::accounts::PlaceBet {
+        yesno_bets::accounts::PlaceBet {
+            bettor: *bettor,
+            payer: *bettor,
+            market: *market,
+            program_stats: program_stats_pda(),
+            bet_mint: self.mint,
+            bettor_ata: ata(bettor, &self.mint),
+            vault: *vault,
+            position: position_pda(market, bettor, 0),
+            user_stats: user_stats_pda(bettor),
+            price_feed: None,
+            house: None,
+            house_vault: None,
+            attestation: None,
+            portfolio: None,
+            insurance_fund: None,
+            insurance_vault: None,
+            system_program: system_program(),
+            token_program: anchor_spl::token::ID,
+            event_authority: event_authority_pda(),
+            program: yesno_bets::ID,
+        }
+    }
+
+    pub fn place_bet_ix_with_min_odds(
+        &self,
+        market: &Pubkey,
+        bettor: &Pubkey,
+        outcome: Outcome,
+        amount: u64,
+        min_odds_bps: u32,
+    ) -> Instruction {
+        let mut bet = self.place_bet_ix(market, bettor, outcome, amount);
+        bet.data = yesno_bets::instruction::PlaceBet {
+            outcome,
+            amount,
+            position_index: 0,
+            tag: None,
+            min_odds_bps: Some(min_odds_bps),
+            max_fee_bps: None,
+            insure: false,
+        }
+        .data();
+        bet
+    }
+
+    /// `place_bet` paying the insurance premium on top of `amount`.
+    pub fn place_insured_bet_ix(&self, market: &Pubkey, bettor: &Pubkey, outcome: Outcome, amount: u64) -> Instruction {
+        let insurance_fund = pda(&[yesno_bets::INSURANCE_FUND_SEED]);
+        let mut accounts = self.place_bet_accounts(market, bettor, &self.vault(market));
+        accounts.insurance_fund = Some(insurance_fund);
+        accounts.insurance_vault = Some(ata(&insurance_fund, &self.mint));
+        ix(
+            accounts,
+            yesno_bets::instruction::PlaceBet {
+                outcome,
+                amount,
+                position_index: 0,
+                tag: None,
+                min_odds_bps: None,
+                max_fee_bps: None,
+                insure: true,
+            },
+        )
+    }
+
+    pub fn claim_insurance_ix(&self, market: &Pubkey, bettor: &Pubkey) -> Instruction {
+        let insurance_fund = pda(&[yesno_bets::INSURANCE_FUND_SEED]);
+        ix(
+            yesno_bets::accounts::ClaimInsurance {
+                owner: *bettor,
+                market: *market,
+                position: position_pda(market, bettor, 0),
+                portfolio: None,
+                bet_mint: self.mint,
+                insurance_fund,
+                insurance_vault: ata(&insurance_fund, &self.mint),
+                owner_ata: ata(bettor, &self.mint),
+                token_program: anchor_spl::token::ID,
+            },
+            yesno_bets::instruction::ClaimInsurance {},
+        )
+    }
+
+    /// Creates the insurance fund's vault and mints `tokens` whole tokens
+    /// into it, standing in for previously swept fee shares.
+    pub fn fund_insurance(&mut self, tokens: u64) {
+        let insurance_fund = pda(&[yesno_bets::INSURANCE_FUND_SEED]);
+        let vault = CreateAssociatedTokenAccount::new(&mut self.svm, &self.payer, &self.mint)
+            .owner(&insurance_fund)
+            .send()
+            .unwrap();
+        MintTo::new(&mut self.svm, &self.payer, &self.mint, &vault, tokens * ONE_TOKEN)
+            .send()
+            .unwrap();
+    }
+
+    /// `resolve_market` signed by `signer` (the owner unless testing auth).
+    pub fn resolve_ix(&self, market: &Pubkey, signer: &Pubkey, outcome: Outcome) -> Instruction {
+        ix(
+            yesno_bets::accounts::ResolveMarket {
+                owner: *signer,
+                market: *market,
+                program_stats: program_stats_pda(),
+                parent_market: None,
+                instructions: anchor_lang::solana_program::sysvar::instructions::ID,
+                operator: None,
+                event_authority: event_authority_pda(),
+                program: yesno_bets::ID,
+            },
+            yesno_bets::instruction::ResolveMarket {
+                winning_outcome: outcome,
+                resolution_reason: ResolutionReason::Manual,
+                evidence_hash: [0; 32],
+                evidence_uri: String::new(),
+            },
+        )
+    }
+
+    pub fn set_report_signer_ix(&self, market: &Pubkey, report_signer: &Pubkey) -> Instruction {
+        ix(
+            yesno_bets::accounts::SetReportSigner {
+                owner: self.owner,
+                market: *market,
+                config: pda(&[yesno_bets::CONFIG_SEED]),
+            },
+            yesno_bets::instruction::SetReportSigner {
+                report_signer: *report_signer,
+            },
+        )
+    }
+
+    pub fn set_three_way_ix(&self, market: &Pubkey) -> Instruction {
+        ix(
+            yesno_bets::accounts::SetThreeWay {
+                owner: self.owner,
+                market: *market,
+            },
+            yesno_bets::instruction::SetThreeWay { enabled: true },
+        )
+    }
+
+    pub fn set_governance_ix(&self, governance_mint: &Pubkey, vote_quorum: u64) -> Instruction {
+        ix(
+            yesno_bets::accounts::SetConfig {
+                owner: self.owner,
+                config: pda(&[yesno_bets::CONFIG_SEED]),
+            },
+            yesno_bets::instruction::SetGovernance {
+                governance_mint: *governance_mint,
+                vote_quorum,
+            },
+        )
+    }
+
+    pub fn open_vote_ix(&self, market: &Pubkey) -> Instruction {
+        ix(
+            yesno_bets::accounts::OpenVote {
+                owner: self.owner,
+                market: *market,
+                config: pda(&[yesno_bets::CONFIG_SEED]),
+            },
+            yesno_bets::instruction::OpenVote {},
+        )
+    }
+
+    pub fn finalize_vote_ix(&self, market: &Pubkey) -> Instruction {
+        ix(
+            yesno_bets::accounts::FinalizeVote {
+                caller: self.payer.pubkey(),
+                market: *market,
+                config: pda(&[yesno_bets::CONFIG_SEED]),
+            },
+            yesno_bets::instruction::FinalizeVote {},
+        )
+    }
+
+    pub fn appeal_ix(&self, market: &Pubkey, appellant: &Pubkey) -> Instruction {
+        ix(
+            yesno_bets::accounts::Appeal {
+                appellant: *appellant,
+                market: *market,
+                system_program: system_program(),
+            },
+            yesno_bets::instruction::Appeal {},
+        )
+    }
+
+    /// Owner ruling on an appeal of a plain vote (no resolver dispute).
+    pub fn resolve_appeal_ix(&self, market: &Pubkey, appellant: &Pubkey, outcome: Outcome) -> Instruction {
+        ix(
+            yesno_bets::accounts::ResolveAppeal {
+                owner: self.owner,
+                market: *market,
+                program_stats: program_stats_pda(),
+                appellant: *appellant,
+                treasury: Pubkey::from_str(yesno_bets::FEE_WALLET).unwrap(),
+                resolver_entry: None,
+                disputer: None,
+                instructions: anchor_lang::solana_program::sysvar::instructions::ID,
+            },
+            yesno_bets::instruction::ResolveAppeal { outcome },
+        )
+    }
+
+    /// `place_limit_bet` for `bettor`'s index-0 position.
+    pub fn place_limit_bet_ix(
+        &self,
+        market: &Pubkey,
+        bettor: &Pubkey,
+        outcome: Outcome,
+        amount: u64,
+        min_odds_bps: u32,
+        keeper_tip: u64,
+    ) -> Instruction {
+        let limit_bet = limit_bet_pda(market, bettor, 0);
+        ix(
+            yesno_bets::accounts::PlaceLimitBet {
+                owner: *bettor,
+                market: *market,
+                bet_mint: self.mint,
+                owner_ata: ata(bettor, &self.mint),
+                limit_bet,
+                escrow: ata(&limit_bet, &self.mint),
+                system_program: system_program(),
+                token_program: anchor_spl::token::ID,
+                associated_token_program: anchor_spl::associated_token::ID,
+            },
+            yesno_bets::instruction::PlaceLimitBet {
+                outcome,
+                amount,
+                min_odds_bps,
+                keeper_tip,
+                position_index: 0,
+            },
+        )
+    }
+
+    /// `execute_limit_bet` on `bettor`'s index-0 limit bet, tip to `keeper`.
+    pub fn execute_limit_bet_ix(&self, market: &Pubkey, bettor: &Pubkey, keeper: &Pubkey) -> Instruction {
+        let limit_bet = limit_bet_pda(market, bettor, 0);
+        ix(
+            yesno_bets::accounts::ExecuteLimitBet {
+                keeper: *keeper,
+                owner: *bettor,
+                limit_bet,
+                market: *market,
+                program_stats: program_stats_pda(),
+                bet_mint: self.mint,
+                escrow: ata(&limit_bet, &self.mint),
+                vault: self.vault(market),
+                keeper_ata: ata(keeper, &self.mint),
+                position: position_pda(market, bettor, 0),
+                user_stats: user_stats_pda(bettor),
+                portfolio: None,
+                price_feed: None,
+                house: None,
+                house_vault: None,
+                system_program: system_program(),
+                token_program: anchor_spl::token::ID,
+                event_authority: event_authority_pda(),
+                program: yesno_bets::ID,
+            },
+            yesno_bets::instruction::ExecuteLimitBet {},
+        )
+    }
+
+    /// The Ed25519 check of `reporter`'s signed report followed by
+    /// `resolve_from_report`, as the program expects them.
+    pub fn resolve_from_report_ixs(
+        &self,
+        market: &Pubkey,
+        reporter: &Keypair,
+        outcome: Outcome,
+        report_ts: i64,
+    ) -> [Instruction; 2] {
+        let mut message = market.to_bytes().to_vec();
+        message.push(outcome as u8);
+        message.extend_from_slice(&report_ts.to_le_bytes());
+        let signature = reporter.sign_message(&message);
+        let verify = new_ed25519_instruction_with_signature(
+            &message,
+            &<[u8; 64]>::from(signature),
+            &reporter.pubkey().to_bytes(),
+        );
+        let resolve = ix(
+            yesno_bets::accounts::ResolveFromReport {
+                caller: self.payer.pubkey(),
+                market: *market,
+                program_stats: program_stats_pda(),
+                parent_market: None,
+                instructions: anchor_lang::solana_program::sysvar::instructions::ID,
+            },
+            yesno_bets::instruction::ResolveFromReport { outcome, report_ts },
+        );
+        [verify, resolve]
+    }
+
+    pub fn void_ix(&self, market: &Pubkey) -> Instruction {
+        ix(
+            yesno_bets::accounts::VoidMarket {
+                owner: self.owner,
+                market: *market,
+                program_stats: program_stats_pda(),
+            },
+            yesno_bets::instruction::VoidMarket {},
+        )
+    }
+
+    pub fn claim_ix(&self, market: &Pubkey, bettor: &Pubkey) -> Instruction {
+        ix(
+            yesno_bets::accounts::ClaimWinnings {
+                bettor: *bettor,
+                market: *market,
+                bet_mint: self.mint,
+                bettor_ata: ata(bettor, &self.mint),
+                vault_authority: vault_authority_pda(market),
+                vault: self.vault(market),
+                position: position_pda(market, bettor, 0),
+                user_stats: user_stats_pda(bettor),
+                portfolio: None,
+                system_program: system_program(),
+                token_program: anchor_spl::token::ID,
+                associated_token_program: anchor_spl::associated_token::ID,
+                event_authority: event_authority_pda(),
+                program: yesno_bets::ID,
+            },
+            yesno_bets::instruction::ClaimWinnings { amount: None },
+        )
+    }
+
+    /// `distribute_payouts` from the owner to each bettor's index-0 position.
+    pub fn distribute_ix(&self, market: &Pubkey, bettors: &[Pubkey]) -> Instruction {
+        let mut distribute = ix(
+            yesno_bets::accounts::DistributePayouts {
+                caller: self.owner,
+                market: *market,
+                bet_mint: self.mint,
+                vault_authority: vault_authority_pda(market),
+                vault: self.vault(market),
+                token_program: anchor_spl::token::ID,
+            },
+            yesno_bets::instruction::DistributePayouts {},
+        );
+        for bettor in bettors {
+            distribute.accounts.extend([
+                AccountMeta::new(position_pda(market, bettor, 0), false),
+                AccountMeta::new(*bettor, false),
+                AccountMeta::new(ata(bettor, &self.mint), false),
+                AccountMeta::new(user_stats_pda(bettor), false),
+            ]);
+        }
+        distribute
+    }
+
+    pub fn sweep_ix(&self, market: &Pubkey) -> Instruction {
+        let fee_receiver = Pubkey::from_str(yesno_bets::FEE_WALLET).unwrap();
+        let insurance_fund = pda(&[yesno_bets::INSURANCE_FUND_SEED]);
+        ix(
+            yesno_bets::accounts::SweepFees {
+                owner: self.owner,
+                market: *market,
+                program_stats: program_stats_pda(),
+                bet_mint: self.mint,
+                vault_authority: vault_authority_pda(market),
+                vault: self.vault(market),
+                fee_receiver,
+                fee_receiver_ata: ata(&fee_receiver, &self.mint),
+                fee_destination: None,
+                insurance_fund,
+                insurance_vault: ata(&insurance_fund, &self.mint),
+                jackpot: pda(&[yesno_bets::JACKPOT_SEED, self.mint.as_ref()]),
+                jackpot_vault: None,
+                operator: None,
+                platform_ata: None,
+                system_program: system_program(),
+                token_program: anchor_spl::token::ID,
+                associated_token_program: anchor_spl::associated_token::ID,
+            },
+            yesno_bets::instruction::SweepFees {},
+        )
+    }
+
+    pub fn skim_ix(&self, market: &Pubkey) -> Instruction {
+        let fee_receiver = Pubkey::from_str(yesno_bets::FEE_WALLET).unwrap();
+        ix(
+            yesno_bets::accounts::SkimSurplus {
+                owner: self.owner,
+                market: *market,
+                bet_mint: self.mint,
+                vault_authority: vault_authority_pda(market),
+                vault: self.vault(market),
+                fee_receiver,
+                fee_receiver_ata: ata(&fee_receiver, &self.mint),
+                system_program: system_program(),
+                token_program: anchor_spl::token::ID,
+                associated_token_program: anchor_spl::associated_token::ID,
+            },
+            yesno_bets::instruction::SkimSurplus {},
+        )
+    }
+
+    /// Plain SPL transfer from `from`'s ATA, bypassing the program.
+    pub fn transfer_ix(&self, from: &Pubkey, to_token_account: &Pubkey, amount: u64) -> Instruction {
+        anchor_spl::token::spl_token::instruction::transfer(
+            &anchor_spl::token::ID,
+            &ata(from, &self.mint),
+            to_token_account,
+            from,
+            &[],
+            amount,
+        )
+        .unwrap()
+    }
+
+    pub fn vault(&self, market: &Pubkey) -> Pubkey {
+        ata(&vault_authority_pda(market), &self.mint)
+    }
+
+    pub fn token_balance(&self, owner: &Pubkey) -> u64 {
+        self.svm
+            .get_account(&ata(owner, &self.mint))
+            .map(|a| TokenAccount::try_deserialize(&mut a.data.as_slice()).unwrap().amount)
+            .unwrap_or(0)
+    }
+
+    pub fn vault_balance(&self, market: &Pubkey) -> u64 {
+        self.token_balance(&vault_authority_pda(market))
+    }
+
+    pub fn market(&self, market: &Pubkey) -> Market {
+        self.account(market)
+    }
+
+    pub fn exists(&self, address: &Pubkey) -> bool {
+        self.svm.get_account(address).is_some_and(|a| a.lamports > 0)
+    }
+
+    pub fn account<T: AccountDeserialize>(&self, address: &Pubkey) -> T {
+        let account = self.svm.get_account(address).expect("account missing");
+        T::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+}
+
+impl Default for TestEnv {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Fails unless `result` is the program error `code`.
+fn so_path() -> String {
+    std::env::var("YESNO_BETS_SO").unwrap_or_else(|_| DEFAULT_SO_PATH.to_string())
+}
+
+pub fn assert_program_error(result: TransactionResult, code: yesno_bets::ErrorCode) {
+    let expected = anchor_lang::error::ERROR_CODE_OFFSET + code as u32;
+    match result {
+        Err(failed) => match failed.err {
+            TransactionError::InstructionError(_, InstructionError::Custom(actual)) => {
+                assert_eq!(actual, expected, "logs: {:#?}", failed.meta.logs)
+            }
+            other => panic!("expected custom error {expected}, got {other:?}"),
+        },
+        Ok(_) => panic!("expected custom error {expected}, transaction succeeded"),
+    }
+}
+
+pub fn ix(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: yesno_bets::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+pub fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &yesno_bets::ID).0
+}
+
+pub fn ata(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, &anchor_spl::token::ID)
+}
+
+pub fn program_stats_pda() -> Pubkey {
+    pda(&[b"program-stats"])
+}
+
+pub fn vault_authority_pda(market: &Pubkey) -> Pubkey {
+    pda(&[yesno_bets::VAULT_AUTH_SEED, market.as_ref()])
+}
+
+pub fn position_pda(market: &Pubkey, bettor: &Pubkey, index: u16) -> Pubkey {
+    pda(&[yesno_bets::POSITION_SEED, market.as_ref(), bettor.as_ref(), &index.to_le_bytes()])
+}
+
+pub fn limit_bet_pda(market: &Pubkey, bettor: &Pubkey, index: u16) -> Pubkey {
+    pda(&[yesno_bets::LIMIT_BET_SEED, market.as_ref(), bettor.as_ref(), &index.to_le_bytes()])
+}
+
+pub fn user_stats_pda(bettor: &Pubkey) -> Pubkey {
+    pda(&[yesno_bets::USER_STATS_SEED, bettor.as_ref()])
+}
+
+pub fn event_authority_pda() -> Pubkey {
+    pda(&[b"__event_authority"])
+}
+
+fn system_program() -> Pubkey {
+    anchor_lang::system_program::ID
+}
@@ -0,0 +1,452 @@
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use yesno_bets::{
+    ErrorCode, Operator, Outcome, ResolutionReason, BPS_DENOM, FEE_BPS, INSURANCE_COVER_BPS, INSURANCE_PREMIUM_BPS,
+};
+use yesno_bets_tests::*;
+
+const HOUR: i64 = 3600;
+const STAKE: u64 = 10 * ONE_TOKEN;
+
+/// Market with Alice on Yes and Bob on No, `STAKE` each, cutoff an hour out.
+fn funded_market(env: &mut TestEnv) -> (Pubkey, Keypair, Keypair) {
+    let market = env.create_market(env.now() + HOUR);
+    let alice = env.new_bettor(50);
+    let bob = env.new_bettor(50);
+    let bet = env.place_bet_ix(&market, &alice.pubkey(), Outcome::Yes, STAKE);
+    env.send(&[bet], &[&alice]).unwrap();
+    let bet = env.place_bet_ix(&market, &bob.pubkey(), Outcome::No, STAKE);
+    env.send(&[bet], &[&bob]).unwrap();
+    (market, alice, bob)
+}
+
+fn net(gross: u64) -> u64 {
+    sillymarket_math::split_fee(gross, FEE_BPS).unwrap().0
+}
+
+#[test]
+fn place_bet_books_net_stake_and_fee() {
+    let Some(mut env) = TestEnv::try_new() else { return };
+    let (market, alice, _bob) = funded_market(&mut env);
+
+    let m = env.market(&market);
+    assert_eq!(m.total_yes, net(STAKE));
+    assert_eq!(m.total_no, net(STAKE));
+    assert_eq!(m.fees_accrued, 2 * (STAKE - net(STAKE)));
+    assert_eq!(m.gross_volume, 2 * STAKE);
+    assert_eq!(env.vault_balance(&market), 2 * STAKE);
+    assert_eq!(env.token_balance(&alice.pubkey()), 50 * ONE_TOKEN - STAKE);
+}
+
+#[test]
+fn winner_claims_whole_pool_and_position_closes() {
+    let Some(mut env) = TestEnv::try_new() else { return };
+    let (market, alice, _bob) = funded_market(&mut env);
+
+    env.warp_to(env.now() + 2 * HOUR);
+    let resolve = env.resolve_ix(&market, &env.owner, Outcome::Yes);
+    env.send(&[resolve], &[]).unwrap();
+
+    let before = env.token_balance(&alice.pubkey());
+    let claim = env.claim_ix(&market, &alice.pubkey());
+    env.send(&[claim], &[&alice]).unwrap();
+
+    let m = env.market(&market);
+    let payout = env.token_balance(&alice.pubkey()) - before;
+    assert_eq!(payout, 2 * net(STAKE) + m.winner_bonus);
+    assert_eq!(m.total_claimed, payout);
+    assert!(!env.exists(&position_pda(&market, &alice.pubkey(), 0)));
+}
+
+#[test]
+fn double_claim_is_rejected() {
+    let Some(mut env) = TestEnv::try_new() else { return };
+    let (market, alice, _bob) = funded_market(&mut env);
+
+    env.warp_to(env.now() + 2 * HOUR);
+    let resolve = env.resolve_ix(&market, &env.owner, Outcome::Yes);
+    env.send(&[resolve], &[]).unwrap();
+
+    let claim = env.claim_ix(&market, &alice.pubkey());
+    env.send(std::slice::from_ref(&claim), &[&alice]).unwrap();
+    let balance = env.token_balance(&alice.pubkey());
+
+    assert!(env.send(&[claim], &[&alice]).is_err());
+    assert_eq!(env.token_balance(&alice.pubkey()), balance);
+}
+
+#[test]
+fn loser_cannot_claim() {
+    let Some(mut env) = TestEnv::try_new() else { return };
+    let (market, _alice, bob) = funded_market(&mut env);
+
+    env.warp_to(env.now() + 2 * HOUR);
+    let resolve = env.resolve_ix(&market, &env.owner, Outcome::Yes);
+    env.send(&[resolve], &[]).unwrap();
+
+    let claim = env.claim_ix(&market, &bob.pubkey());
+    assert_program_error(env.send(&[claim], &[&bob]), ErrorCode::NoPayout);
+}
+
+#[test]
+fn void_refunds_both_sides_net_of_fees() {
+    let Some(mut env) = TestEnv::try_new() else { return };
+    let (market, alice, bob) = funded_market(&mut env);
+
+    let void = env.void_ix(&market);
+    env.send(&[void], &[]).unwrap();
+
+    for bettor in [&alice, &bob] {
+        let before = env.token_balance(&bettor.pubkey());
+        let claim = env.claim_ix(&market, &bettor.pubkey());
+        env.send(&[claim], &[bettor]).unwrap();
+        assert_eq!(env.token_balance(&bettor.pubkey()) - before, net(STAKE));
+    }
+    assert_eq!(env.vault_balance(&market), env.market(&market).fees_accrued);
+}
+
+#[test]
+fn sweep_splits_fees_between_treasury_and_insurance() {
+    let Some(mut env) = TestEnv::try_new() else { return };
+    let (market, _alice, _bob) = funded_market(&mut env);
+
+    env.warp_to(env.now() + 2 * HOUR);
+    let resolve = env.resolve_ix(&market, &env.owner, Outcome::Yes);
+    env.send(&[resolve], &[]).unwrap();
+
+    let fees = env.market(&market).fees_accrued;
+    let sweep = env.sweep_ix(&market);
+    env.send(&[sweep], &[]).unwrap();
+
+    let cut = fees * INSURANCE_SHARE_BPS as u64 / BPS_DENOM;
+    let fee_wallet = yesno_bets::FEE_WALLET.parse().unwrap();
+    let insurance = pda(&[yesno_bets::INSURANCE_FUND_SEED]);
+    assert_eq!(env.token_balance(&fee_wallet), fees - cut);
+    assert_eq!(env.token_balance(&insurance), cut);
+    assert_eq!(env.market(&market).fees_accrued, 0);
+
+    let sweep = env.sweep_ix(&market);
+    assert_program_error(env.send(&[sweep], &[]), ErrorCode::NoFees);
+}
+
+#[test]
+fn repeat_bet_stays_within_compute_budget() {
+    // Regression guard for the place_bet hot path; the first bet also pays
+    // for creating the position and user stats.
+    const PLACE_BET_CU_BUDGET: u64 = 40_000;
+
+    let Some(mut env) = TestEnv::try_new() else { return };
+    let market = env.create_market(env.now() + HOUR);
+    let alice = env.new_bettor(50);
+    let bet = env.place_bet_ix(&market, &alice.pubkey(), Outcome::Yes, STAKE);
+    env.send(std::slice::from_ref(&bet), &[&alice]).unwrap();
+
+    let meta = env.send(&[bet], &[&alice]).unwrap();
+    assert!(
+        meta.compute_units_consumed <= PLACE_BET_CU_BUDGET,
+        "place_bet used {} CU",
+        meta.compute_units_consumed
+    );
+}
+
+#[test]
+fn bet_after_cutoff_is_rejected() {
+    let Some(mut env) = TestEnv::try_new() else { return };
+    let market = env.create_market(env.now() + HOUR);
+    let alice = env.new_bettor(50);
+
+    env.warp_to(env.now() + HOUR);
+    let bet = env.place_bet_ix(&market, &alice.pubkey(), Outcome::Yes, STAKE);
+    assert_program_error(env.send(&[bet], &[&alice]), ErrorCode::BettingClosed);
+}
+
+#[test]
+fn bet_into_another_markets_vault_is_rejected() {
+    let Some(mut env) = TestEnv::try_new() else { return };
+    let market = env.create_market(env.now() + HOUR);
+    let other = env.create_market(env.now() + 2 * HOUR);
+    let alice = env.new_bettor(50);
+
+    let wrong_vault = env.vault(&other);
+    let bet = env.place_bet_ix_with_vault(&market, &alice.pubkey(), Outcome::Yes, STAKE, &wrong_vault);
+    assert!(env.send(&[bet], &[&alice]).is_err());
+    assert_eq!(env.vault_balance(&other), 0);
+    assert_eq!(env.token_balance(&alice.pubkey()), 50 * ONE_TOKEN);
+}
+
+#[test]
+fn resolve_before_cutoff_is_rejected() {
+    let Some(mut env) = TestEnv::try_new() else { return };
+    let (market, _alice, _bob) = funded_market(&mut env);
+
+    let resolve = env.resolve_ix(&market, &env.owner, Outcome::Yes);
+    assert_program_error(env.send(&[resolve], &[]), ErrorCode::TooEarly);
+}
+
+#[test]
+fn only_owner_can_resolve() {
+    let Some(mut env) = TestEnv::try_new() else { return };
+    let (market, _alice, _bob) = funded_market(&mut env);
+    let mallory = Keypair::new();
+
+    env.warp_to(env.now() + 2 * HOUR);
+    let resolve = env.resolve_ix(&market, &mallory.pubkey(), Outcome::No);
+    assert_program_error(env.send(&[resolve], &[&mallory]), ErrorCode::Unauthorized);
+    assert!(!env.market(&market).resolved);
+}
+
+#[test]
+fn one_sided_market_auto_voids() {
+    let Some(mut env) = TestEnv::try_new() else { return };
+    let market = env.create_market(env.now() + HOUR);
+    let alice = env.new_bettor(50);
+    let bet = env.place_bet_ix(&market, &alice.pubkey(), Outcome::Yes, STAKE);
+    env.send(&[bet], &[&alice]).unwrap();
+
+    env.warp_to(env.now() + 2 * HOUR);
+    let resolve = env.resolve_ix(&market, &env.owner, Outcome::Yes);
+    env.send(&[resolve], &[]).unwrap();
+
+    let m = env.market(&market);
+    assert_eq!(m.winning_outcome, Outcome::Void as u8);
+    let before = env.token_balance(&alice.pubkey());
+    let claim = env.claim_ix(&market, &alice.pubkey());
+    env.send(&[claim], &[&alice]).unwrap();
+    assert_eq!(env.token_balance(&alice.pubkey()) - before, net(STAKE));
+}
+
+#[test]
+fn resolve_and_claim_in_one_transaction_is_rejected() {
+    let Some(mut env) = TestEnv::try_new() else { return };
+    let (market, alice, _bob) = funded_market(&mut env);
+
+    env.warp_to(env.now() + 2 * HOUR);
+    let resolve = env.resolve_ix(&market, &env.owner, Outcome::Yes);
+    let claim = env.claim_ix(&market, &alice.pubkey());
+    assert_program_error(env.send(&[resolve, claim], &[&alice]), ErrorCode::ResolutionSandwich);
+    assert!(!env.market(&market).resolved);
+}
+
+#[test]
+fn bet_aborts_when_odds_fall_below_min() {
+    let Some(mut env) = TestEnv::try_new() else { return };
+    let (market, _alice, _bob) = funded_market(&mut env);
+    let carol = env.new_bettor(50);
+
+    // Equal pools: another STAKE on Yes leaves Yes paying 1.5x.
+    let bet = env.place_bet_ix_with_min_odds(&market, &carol.pubkey(), Outcome::Yes, STAKE, 20_000);
+    assert_program_error(env.send(&[bet], &[&carol]), ErrorCode::OddsSlippage);
+    assert_eq!(env.token_balance(&carol.pubkey()), 50 * ONE_TOKEN);
+
+    let bet = env.place_bet_ix_with_min_odds(&market, &carol.pubkey(), Outcome::Yes, STAKE, 15_000);
+    env.send(&[bet], &[&carol]).unwrap();
+    assert_eq!(env.market(&market).total_yes, 2 * net(STAKE));
+}
+
+#[test]
+fn distribute_pushes_payouts_and_closes_positions() {
+    let Some(mut env) = TestEnv::try_new() else { return };
+    let (market, alice, bob) = funded_market(&mut env);
+
+    let void = env.void_ix(&market);
+    env.send(&[void], &[]).unwrap();
+
+    let before = env.token_balance(&alice.pubkey());
+    let distribute = env.distribute_ix(&market, &[alice.pubkey(), bob.pubkey()]);
+    env.send(&[distribute], &[]).unwrap();
+
+    assert_eq!(env.token_balance(&alice.pubkey()) - before, net(STAKE));
+    for bettor in [&alice, &bob] {
+        assert!(!env.exists(&position_pda(&market, &bettor.pubkey(), 0)));
+    }
+    let claim = env.claim_ix(&market, &alice.pubkey());
+    assert!(env.send(&[claim], &[&alice]).is_err());
+}
+
+#[test]
+fn signed_report_resolves_market() {
+    let Some(mut env) = TestEnv::try_new() else { return };
+    let (market, _alice, _bob) = funded_market(&mut env);
+    let reporter = Keypair::new();
+    let set = env.set_report_signer_ix(&market, &reporter.pubkey());
+    env.send(&[set], &[]).unwrap();
+
+    env.warp_to(env.now() + 2 * HOUR);
+    let report_ts = env.now();
+    let forged = env.resolve_from_report_ixs(&market, &Keypair::new(), Outcome::No, report_ts);
+    assert_program_error(env.send(&forged, &[]), ErrorCode::InvalidReport);
+
+    let resolve = env.resolve_from_report_ixs(&market, &reporter, Outcome::No, report_ts);
+    env.send(&resolve, &[]).unwrap();
+    let m = env.market(&market);
+    assert_eq!(m.winning_outcome, Outcome::No as u8);
+    assert_eq!(m.resolution_reason, ResolutionReason::Report as u8);
+}
+
+#[test]
+fn skim_moves_direct_transfers_to_treasury() {
+    let Some(mut env) = TestEnv::try_new() else { return };
+    let (market, alice, _bob) = funded_market(&mut env);
+
+    let stray = env.transfer_ix(&alice.pubkey(), &env.vault(&market), STAKE);
+    env.send(&[stray], &[&alice]).unwrap();
+
+    let skim = env.skim_ix(&market);
+    env.send(&[skim], &[]).unwrap();
+    let fee_wallet = yesno_bets::FEE_WALLET.parse().unwrap();
+    assert_eq!(env.token_balance(&fee_wallet), STAKE);
+    assert_eq!(env.vault_balance(&market), 2 * STAKE);
+
+    let skim = env.skim_ix(&market);
+    assert_program_error(env.send(&[skim], &[]), ErrorCode::NoSurplus);
+}
+
+#[test]
+fn insured_loser_recovers_cover_and_position_closes() {
+    let Some(mut env) = TestEnv::try_new() else { return };
+    env.fund_insurance(50);
+    let market = env.create_market(env.now() + HOUR);
+    let alice = env.new_bettor(50);
+    let bob = env.new_bettor(50);
+
+    let bet = env.place_insured_bet_ix(&market, &alice.pubkey(), Outcome::Yes, STAKE);
+    env.send(&[bet], &[&alice]).unwrap();
+    let bet = env.place_bet_ix(&market, &bob.pubkey(), Outcome::No, STAKE);
+    env.send(&[bet], &[&bob]).unwrap();
+
+    let premium = net(STAKE) * INSURANCE_PREMIUM_BPS / BPS_DENOM;
+    assert_eq!(env.token_balance(&alice.pubkey()), 50 * ONE_TOKEN - STAKE - premium);
+    assert_eq!(env.market(&market).premium_pool, premium);
+
+    env.warp_to(env.now() + 2 * HOUR);
+    let resolve = env.resolve_ix(&market, &env.owner, Outcome::No);
+    env.send(&[resolve], &[]).unwrap();
+
+    let before = env.token_balance(&alice.pubkey());
+    let claim = env.claim_insurance_ix(&market, &alice.pubkey());
+    env.send(std::slice::from_ref(&claim), &[&alice]).unwrap();
+    let cover = net(STAKE) * INSURANCE_COVER_BPS / BPS_DENOM;
+    assert_eq!(env.token_balance(&alice.pubkey()) - before, cover);
+    assert_eq!(env.market(&market).insured_claimed, cover);
+    assert!(!env.exists(&position_pda(&market, &alice.pubkey(), 0)));
+
+    assert!(env.send(&[claim], &[&alice]).is_err());
+}
+
+#[test]
+fn insured_bet_without_insurance_accounts_is_rejected() {
+    let Some(mut env) = TestEnv::try_new() else { return };
+    let market = env.create_market(env.now() + HOUR);
+    let alice = env.new_bettor(50);
+
+    let mut bet = env.place_insured_bet_ix(&market, &alice.pubkey(), Outcome::Yes, STAKE);
+    bet.accounts = env.place_bet_ix(&market, &alice.pubkey(), Outcome::Yes, STAKE).accounts;
+    assert_program_error(env.send(&[bet], &[&alice]), ErrorCode::InsuranceAccountsMissing);
+    assert_eq!(env.token_balance(&alice.pubkey()), 50 * ONE_TOKEN);
+}
+
+#[test]
+fn operator_markets_live_in_operator_namespace() {
+    let Some(mut env) = TestEnv::try_new() else { return };
+    let admin = env.new_bettor(0);
+    let operator = env.register_operator(7, &admin.pubkey(), 1_000);
+    let market = env.next_operator_market(&operator);
+
+    let create = env.create_market_ix(&market, &env.owner, Some(operator), env.now() + HOUR);
+    assert_program_error(env.send(&[create], &[]), ErrorCode::Unauthorized);
+
+    let create = env.create_market_ix(&market, &admin.pubkey(), Some(operator), env.now() + HOUR);
+    env.send(&[create], &[&admin]).unwrap();
+    let m = env.market(&market);
+    assert_eq!(m.operator, operator);
+    assert_eq!(m.fee_receiver, admin.pubkey());
+    let op: Operator = env.account(&operator);
+    assert_eq!(op.market_count, 1);
+
+    // Protocol markets keep counting in the global namespace.
+    let protocol = env.create_market(env.now() + HOUR);
+    assert_eq!(env.market(&protocol).market_id, m.market_id + 1);
+    assert_ne!(env.next_operator_market(&operator), market);
+}
+
+#[test]
+fn keeper_fills_resting_draw_bet_once_odds_reach_target() {
+    let Some(mut env) = TestEnv::try_new() else { return };
+    let market = env.create_market(env.now() + HOUR);
+    let three_way = env.set_three_way_ix(&market);
+    env.send(&[three_way], &[]).unwrap();
+    let alice = env.new_bettor(50);
+    let bob = env.new_bettor(50);
+    let bet = env.place_bet_ix(&market, &alice.pubkey(), Outcome::Yes, STAKE);
+    env.send(&[bet], &[&alice]).unwrap();
+    let bet = env.place_bet_ix(&market, &bob.pubkey(), Outcome::No, STAKE);
+    env.send(&[bet], &[&bob]).unwrap();
+    let carol = env.new_bettor(50);
+    let dave = env.new_bettor(50);
+    let keeper = env.new_bettor(0);
+
+    // Draw is the only empty pool, so STAKE on it pays 3x.
+    let place = env.place_limit_bet_ix(&market, &carol.pubkey(), Outcome::Draw, STAKE, 30_000, ONE_TOKEN);
+    env.send(&[place], &[&carol]).unwrap();
+    assert_eq!(env.token_balance(&carol.pubkey()), 50 * ONE_TOKEN - STAKE - ONE_TOKEN);
+
+    let execute = env.execute_limit_bet_ix(&market, &carol.pubkey(), &keeper.pubkey());
+    env.send(&[execute], &[&keeper]).unwrap();
+    assert_eq!(env.market(&market).total_draw, net(STAKE));
+    assert_eq!(env.token_balance(&keeper.pubkey()), ONE_TOKEN);
+    assert!(!env.exists(&limit_bet_pda(&market, &carol.pubkey(), 0)));
+    let position: yesno_bets::Position = env.account(&position_pda(&market, &carol.pubkey(), 0));
+    assert_eq!(position.outcome, Outcome::Draw as u8);
+
+    // Another STAKE on Yes only pays 2x, short of the 2.5x target.
+    let place = env.place_limit_bet_ix(&market, &dave.pubkey(), Outcome::Yes, STAKE, 25_000, 0);
+    env.send(&[place], &[&dave]).unwrap();
+    let execute = env.execute_limit_bet_ix(&market, &dave.pubkey(), &keeper.pubkey());
+    assert_program_error(env.send(&[execute], &[&keeper]), ErrorCode::OddsSlippage);
+    assert!(env.exists(&limit_bet_pda(&market, &dave.pubkey(), 0)));
+}
+
+#[test]
+fn appeal_settles_three_way_market_as_draw() {
+    let Some(mut env) = TestEnv::try_new() else { return };
+    let market = env.create_market(env.now() + HOUR);
+    let three_way = env.set_three_way_ix(&market);
+    env.send(&[three_way], &[]).unwrap();
+    let mut bettors = Vec::new();
+    for outcome in [Outcome::Yes, Outcome::No, Outcome::Draw] {
+        let bettor = env.new_bettor(50);
+        let bet = env.place_bet_ix(&market, &bettor.pubkey(), outcome, STAKE);
+        env.send(&[bet], &[&bettor]).unwrap();
+        bettors.push(bettor);
+    }
+    let carol = &bettors[2];
+    let appellant = env.new_bettor(0);
+
+    // Nobody votes, so the tally misses quorum and comes out Void.
+    env.warp_to(env.now() + 2 * HOUR);
+    let governance = env.set_governance_ix(&env.mint, 1);
+    let open = env.open_vote_ix(&market);
+    env.send(&[governance, open], &[]).unwrap();
+    env.warp_to(env.now() + yesno_bets::VOTE_WINDOW);
+    let finalize = env.finalize_vote_ix(&market);
+    env.send(&[finalize], &[]).unwrap();
+    assert_eq!(env.market(&market).vote_outcome, Outcome::Void as u8);
+
+    let appeal = env.appeal_ix(&market, &appellant.pubkey());
+    env.send(&[appeal], &[&appellant]).unwrap();
+    let bonded = env.svm.get_balance(&appellant.pubkey()).unwrap();
+    env.warp_to(env.now() + yesno_bets::APPEAL_DELAY);
+    let rule = env.resolve_appeal_ix(&market, &appellant.pubkey(), Outcome::Draw);
+    env.send(&[rule], &[]).unwrap();
+
+    let m = env.market(&market);
+    assert_eq!(m.winning_outcome, Outcome::Draw as u8);
+    assert_eq!(m.resolution_reason, ResolutionReason::Appeal as u8);
+    // Overturning the vote refunds the appeal bond.
+    assert_eq!(env.svm.get_balance(&appellant.pubkey()).unwrap(), bonded + yesno_bets::APPEAL_BOND);
+
+    let before = env.token_balance(&carol.pubkey());
+    let claim = env.claim_ix(&market, &carol.pubkey());
+    env.send(&[claim], &[carol]).unwrap();
+    assert_eq!(env.token_balance(&carol.pubkey()) - before, 3 * net(STAKE) + m.winner_bonus);
+}
@@ -0,0 +1,636 @@
+use crate::*;
+
+pub const LENDING_DEPOSIT_LIQUIDITY: u8 = 4; // Token-lending DepositReserveLiquidity
+pub const LENDING_REDEEM_COLLATERAL: u8 = 5; // Token-lending RedeemReserveCollateral
+pub const WSOL_MINT: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
+pub const STAKE_PROGRAM_ID: Pubkey = pubkey!("Stake11111111111111111111111111111111111111");
+pub const STAKE_POOL_DEPOSIT_SOL: u8 = 14; // SPL stake-pool DepositSol
+pub const STAKE_POOL_WITHDRAW_SOL: u8 = 16; // SPL stake-pool WithdrawSol
+pub const LST_STAGING_SEED: &[u8] = b"lst-staging";
+// Solend (SPL token-lending layout), the only lending program vault funds may go to.
+pub const LENDING_PROGRAM_ID: Pubkey = pubkey!("So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo");
+// SPL stake pool, the only stake-pool program vault SOL may be staked with.
+pub const STAKE_POOL_PROGRAM_ID: Pubkey = pubkey!("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy");
+
+pub(crate) fn enable_yield(ctx: Context<EnableYield>, to_pool: bool) -> Result<()> {
+    require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+    let m = &mut ctx.accounts.market;
+    validate_market_active(m)?;
+    require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+    require!(m.yield_deposited == 0, ErrorCode::YieldDeployed);
+    require!(m.lst_pool == Pubkey::default(), ErrorCode::YieldDeployed);
+
+    m.yield_program = ctx.accounts.lending_program.key();
+    m.yield_reserve = ctx.accounts.reserve.key();
+    m.yield_to_pool = to_pool;
+
+    emit!(YieldEnabled {
+        market: m.key(),
+        lending_program: m.yield_program,
+        reserve: m.yield_reserve,
+        to_pool,
+    });
+
+    Ok(())
+}
+
+pub(crate) fn deposit_idle(ctx: Context<MoveIdleFunds>, amount: u64) -> Result<()> {
+    require_keys_eq!(ctx.accounts.caller.key(), owner_pubkey(), ErrorCode::Unauthorized);
+    let m = &ctx.accounts.market;
+    validate_market_active(m)?;
+    require!(m.yield_reserve != Pubkey::default(), ErrorCode::YieldNotEnabled);
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    require!(amount <= deployable_idle(m, ctx.accounts.vault.amount), ErrorCode::Insolvent);
+
+    let market_key = m.key();
+    let bump: u8 = ctx.bumps.vault_authority;
+    let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, market_key.as_ref(), &[bump]];
+    let a = &ctx.accounts;
+
+    let ix = tagged_instruction(
+        a.lending_program.key(),
+        LENDING_DEPOSIT_LIQUIDITY,
+        amount,
+        vec![
+            AccountMeta::new(a.vault.key(), false),
+            AccountMeta::new(a.collateral.key(), false),
+            AccountMeta::new(a.reserve.key(), false),
+            AccountMeta::new(a.reserve_liquidity_supply.key(), false),
+            AccountMeta::new(a.collateral_mint.key(), false),
+            AccountMeta::new_readonly(a.lending_market.key(), false),
+            AccountMeta::new_readonly(a.lending_market_authority.key(), false),
+            AccountMeta::new_readonly(a.vault_authority.key(), true),
+            AccountMeta::new_readonly(a.token_program.key(), false),
+        ],
+    );
+    invoke_signed(
+        &ix,
+        &[
+            a.vault.to_account_info(),
+            a.collateral.to_account_info(),
+            a.reserve.to_account_info(),
+            a.reserve_liquidity_supply.to_account_info(),
+            a.collateral_mint.to_account_info(),
+            a.lending_market.to_account_info(),
+            a.lending_market_authority.to_account_info(),
+            a.vault_authority.to_account_info(),
+            a.token_program.to_account_info(),
+        ],
+        &[seeds],
+    )?;
+
+    let m = &mut ctx.accounts.market;
+    m.yield_deposited = m.yield_deposited.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+
+    emit!(YieldDeposited {
+        market: market_key,
+        amount,
+        deployed: m.yield_deposited,
+    });
+
+    Ok(())
+}
+
+pub(crate) fn withdraw_idle(ctx: Context<MoveIdleFunds>) -> Result<()> {
+    let m = &ctx.accounts.market;
+    require!(
+        m.resolved || ctx.accounts.caller.key() == owner_pubkey(),
+        ErrorCode::Unauthorized
+    );
+    require!(m.yield_deposited > 0, ErrorCode::YieldNotEnabled);
+
+    let market_key = m.key();
+    let bump: u8 = ctx.bumps.vault_authority;
+    let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, market_key.as_ref(), &[bump]];
+    let a = &ctx.accounts;
+    let before = a.vault.amount;
+
+    let ix = tagged_instruction(
+        a.lending_program.key(),
+        LENDING_REDEEM_COLLATERAL,
+        a.collateral.amount,
+        vec![
+            AccountMeta::new(a.collateral.key(), false),
+            AccountMeta::new(a.vault.key(), false),
+            AccountMeta::new(a.reserve.key(), false),
+            AccountMeta::new(a.collateral_mint.key(), false),
+            AccountMeta::new(a.reserve_liquidity_supply.key(), false),
+            AccountMeta::new_readonly(a.lending_market.key(), false),
+            AccountMeta::new_readonly(a.lending_market_authority.key(), false),
+            AccountMeta::new_readonly(a.vault_authority.key(), true),
+            AccountMeta::new_readonly(a.token_program.key(), false),
+        ],
+    );
+    invoke_signed(
+        &ix,
+        &[
+            a.collateral.to_account_info(),
+            a.vault.to_account_info(),
+            a.reserve.to_account_info(),
+            a.collateral_mint.to_account_info(),
+            a.reserve_liquidity_supply.to_account_info(),
+            a.lending_market.to_account_info(),
+            a.lending_market_authority.to_account_info(),
+            a.vault_authority.to_account_info(),
+            a.token_program.to_account_info(),
+        ],
+        &[seeds],
+    )?;
+
+    ctx.accounts.vault.reload()?;
+    let received = ctx.accounts.vault.amount.checked_sub(before).ok_or(ErrorCode::Overflow)?;
+
+    let m = &mut ctx.accounts.market;
+    let principal = m.yield_deposited;
+    let (earned, shortfall, to_pool) = book_yield(m, received)?;
+
+    emit!(YieldWithdrawn {
+        market: market_key,
+        principal,
+        received,
+        earned,
+        shortfall,
+        yield_loss: m.yield_loss,
+        to_pool,
+    });
+
+    Ok(())
+}
+
+pub(crate) fn enable_lst_staking(ctx: Context<EnableLstStaking>, to_pool: bool) -> Result<()> {
+    require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+    let m = &mut ctx.accounts.market;
+    validate_market_active(m)?;
+    require_keys_eq!(m.bet_mint, WSOL_MINT, ErrorCode::WrongMint);
+    require!(m.yield_deposited == 0, ErrorCode::YieldDeployed);
+    require!(m.yield_reserve == Pubkey::default(), ErrorCode::YieldDeployed);
+
+    m.lst_program = ctx.accounts.stake_pool_program.key();
+    m.lst_pool = ctx.accounts.stake_pool.key();
+    m.yield_to_pool = to_pool;
+
+    emit!(LstStakingEnabled {
+        market: m.key(),
+        stake_pool_program: m.lst_program,
+        stake_pool: m.lst_pool,
+        to_pool,
+    });
+
+    Ok(())
+}
+
+pub(crate) fn stake_idle(ctx: Context<StakeIdle>, amount: u64) -> Result<()> {
+    require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+    let m = &ctx.accounts.market;
+    validate_market_active(m)?;
+    require!(m.lst_pool != Pubkey::default(), ErrorCode::YieldNotEnabled);
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    require!(amount <= deployable_idle(m, ctx.accounts.vault.amount), ErrorCode::Insolvent);
+
+    let market_key = m.key();
+    let bump: u8 = ctx.bumps.vault_authority;
+    let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, market_key.as_ref(), &[bump]];
+    let signer: &[&[&[u8]]] = &[seeds];
+    let a = &ctx.accounts;
+    let staging_rent = a.staging.to_account_info().lamports();
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            a.token_program.to_account_info(),
+            TransferChecked {
+                from: a.vault.to_account_info(),
+                mint: a.bet_mint.to_account_info(),
+                to: a.staging.to_account_info(),
+                authority: a.vault_authority.to_account_info(),
+            },
+            signer,
+        ),
+        amount,
+        a.bet_mint.decimals,
+    )?;
+
+    // Closing a WSOL account releases its balance as lamports.
+    token_interface::close_account(CpiContext::new_with_signer(
+        a.token_program.to_account_info(),
+        CloseAccount {
+            account: a.staging.to_account_info(),
+            destination: a.vault_authority.to_account_info(),
+            authority: a.vault_authority.to_account_info(),
+        },
+        signer,
+    ))?;
+
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            a.system_program.to_account_info(),
+            system_program::Transfer {
+                from: a.vault_authority.to_account_info(),
+                to: a.owner.to_account_info(),
+            },
+            signer,
+        ),
+        staging_rent,
+    )?;
+
+    let ix = tagged_instruction(
+        a.stake_pool_program.key(),
+        STAKE_POOL_DEPOSIT_SOL,
+        amount,
+        vec![
+            AccountMeta::new(a.stake_pool.key(), false),
+            AccountMeta::new_readonly(a.pool_withdraw_authority.key(), false),
+            AccountMeta::new(a.reserve_stake.key(), false),
+            AccountMeta::new(a.vault_authority.key(), true),
+            AccountMeta::new(a.lst_account.key(), false),
+            AccountMeta::new(a.manager_fee_account.key(), false),
+            AccountMeta::new(a.lst_account.key(), false),
+            AccountMeta::new(a.pool_mint.key(), false),
+            AccountMeta::new_readonly(a.system_program.key(), false),
+            AccountMeta::new_readonly(a.token_program.key(), false),
+        ],
+    );
+    invoke_signed(
+        &ix,
+        &[
+            a.stake_pool.to_account_info(),
+            a.pool_withdraw_authority.to_account_info(),
+            a.reserve_stake.to_account_info(),
+            a.vault_authority.to_account_info(),
+            a.lst_account.to_account_info(),
+            a.manager_fee_account.to_account_info(),
+            a.pool_mint.to_account_info(),
+            a.system_program.to_account_info(),
+            a.token_program.to_account_info(),
+        ],
+        signer,
+    )?;
+
+    let m = &mut ctx.accounts.market;
+    m.yield_deposited = m.yield_deposited.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+
+    emit!(YieldDeposited {
+        market: market_key,
+        amount,
+        deployed: m.yield_deposited,
+    });
+
+    Ok(())
+}
+
+pub(crate) fn unstake_idle(ctx: Context<UnstakeIdle>) -> Result<()> {
+    let m = &ctx.accounts.market;
+    require!(
+        m.resolved || ctx.accounts.caller.key() == owner_pubkey(),
+        ErrorCode::Unauthorized
+    );
+    require!(m.yield_deposited > 0, ErrorCode::YieldNotEnabled);
+
+    let market_key = m.key();
+    let bump: u8 = ctx.bumps.vault_authority;
+    let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, market_key.as_ref(), &[bump]];
+    let signer: &[&[&[u8]]] = &[seeds];
+    let a = &ctx.accounts;
+    let before = a.vault_authority.lamports();
+
+    let ix = tagged_instruction(
+        a.stake_pool_program.key(),
+        STAKE_POOL_WITHDRAW_SOL,
+        a.lst_account.amount,
+        vec![
+            AccountMeta::new(a.stake_pool.key(), false),
+            AccountMeta::new_readonly(a.pool_withdraw_authority.key(), false),
+            AccountMeta::new_readonly(a.vault_authority.key(), true),
+            AccountMeta::new(a.lst_account.key(), false),
+            AccountMeta::new(a.reserve_stake.key(), false),
+            AccountMeta::new(a.vault_authority.key(), true),
+            AccountMeta::new(a.manager_fee_account.key(), false),
+            AccountMeta::new(a.pool_mint.key(), false),
+            AccountMeta::new_readonly(a.clock.key(), false),
+            AccountMeta::new_readonly(a.stake_history.key(), false),
+            AccountMeta::new_readonly(a.stake_program.key(), false),
+            AccountMeta::new_readonly(a.token_program.key(), false),
+        ],
+    );
+    invoke_signed(
+        &ix,
+        &[
+            a.stake_pool.to_account_info(),
+            a.pool_withdraw_authority.to_account_info(),
+            a.vault_authority.to_account_info(),
+            a.lst_account.to_account_info(),
+            a.reserve_stake.to_account_info(),
+            a.manager_fee_account.to_account_info(),
+            a.pool_mint.to_account_info(),
+            a.clock.to_account_info(),
+            a.stake_history.to_account_info(),
+            a.stake_program.to_account_info(),
+            a.token_program.to_account_info(),
+        ],
+        signer,
+    )?;
+
+    let received = a.vault_authority.lamports().checked_sub(before).ok_or(ErrorCode::Overflow)?;
+
+    // Re-wrap: move the lamports into the WSOL vault and resync its balance.
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            a.system_program.to_account_info(),
+            system_program::Transfer {
+                from: a.vault_authority.to_account_info(),
+                to: a.vault.to_account_info(),
+            },
+            signer,
+        ),
+        received,
+    )?;
+    token_interface::sync_native(CpiContext::new(
+        a.token_program.to_account_info(),
+        token_interface::SyncNative {
+            account: a.vault.to_account_info(),
+        },
+    ))?;
+
+    let m = &mut ctx.accounts.market;
+    let principal = m.yield_deposited;
+    let (earned, shortfall, to_pool) = book_yield(m, received)?;
+
+    emit!(YieldWithdrawn {
+        market: market_key,
+        principal,
+        received,
+        earned,
+        shortfall,
+        yield_loss: m.yield_loss,
+        to_pool,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct YieldEnabled {
+    pub market: Pubkey,
+    pub lending_program: Pubkey,
+    pub reserve: Pubkey,
+    pub to_pool: bool,
+}
+
+#[event]
+pub struct YieldDeposited {
+    pub market: Pubkey,
+    pub amount: u64,
+    pub deployed: u64,
+}
+
+#[event]
+pub struct YieldWithdrawn {
+    pub market: Pubkey,
+    pub principal: u64,
+    pub received: u64,
+    pub earned: u64,
+    /// Principal not returned; covered by fees, then by `yield_loss`.
+    pub shortfall: u64,
+    pub yield_loss: u64,
+    pub to_pool: bool,
+}
+
+#[event]
+pub struct LstStakingEnabled {
+    pub market: Pubkey,
+    pub stake_pool_program: Pubkey,
+    pub stake_pool: Pubkey,
+    pub to_pool: bool,
+}
+
+#[derive(Accounts)]
+pub struct EnableYield<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    /// CHECK: the lending program; only its id is stored.
+    #[account(executable, address = LENDING_PROGRAM_ID @ ErrorCode::UnsupportedYieldProgram)]
+    pub lending_program: UncheckedAccount<'info>,
+    /// CHECK: must be owned by the lending program; validated by it on use.
+    #[account(owner = lending_program.key())]
+    pub reserve: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MoveIdleFunds<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(mut, has_one = bet_mint, has_one = vault)]
+    pub market: Account<'info, Market>,
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA signer for the vault, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Reserve collateral held on behalf of the vault.
+    #[account(
+        mut,
+        token::mint = collateral_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub collateral: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: must be the reserve bound by `enable_yield`.
+    #[account(mut, address = market.yield_reserve @ ErrorCode::YieldNotEnabled)]
+    pub reserve: UncheckedAccount<'info>,
+    /// CHECK: validated by the lending program.
+    #[account(mut)]
+    pub reserve_liquidity_supply: UncheckedAccount<'info>,
+    /// CHECK: validated by the lending program.
+    pub lending_market: UncheckedAccount<'info>,
+    /// CHECK: validated by the lending program.
+    pub lending_market_authority: UncheckedAccount<'info>,
+    /// CHECK: must be `LENDING_PROGRAM_ID` and the program bound by `enable_yield`.
+    #[account(
+        executable,
+        address = LENDING_PROGRAM_ID @ ErrorCode::UnsupportedYieldProgram,
+        constraint = market.yield_program == LENDING_PROGRAM_ID @ ErrorCode::YieldNotEnabled
+    )]
+    pub lending_program: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct EnableLstStaking<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    /// CHECK: the stake-pool program; only its id is stored.
+    #[account(executable, address = STAKE_POOL_PROGRAM_ID @ ErrorCode::UnsupportedYieldProgram)]
+    pub stake_pool_program: UncheckedAccount<'info>,
+    /// CHECK: must be owned by the stake-pool program; validated by it on use.
+    #[account(owner = stake_pool_program.key())]
+    pub stake_pool: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct StakeIdle<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut, has_one = bet_mint, has_one = vault)]
+    pub market: Account<'info, Market>,
+    #[account(address = WSOL_MINT @ ErrorCode::WrongMint)]
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA signer for the vault, validated by seeds.
+    #[account(mut, seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Opened and closed within the instruction to unwrap WSOL.
+    #[account(
+        init,
+        payer = owner,
+        seeds = [LST_STAGING_SEED, market.key().as_ref()],
+        bump,
+        token::mint = bet_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub staging: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = pool_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub lst_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub pool_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: must be the pool bound by `enable_lst_staking`.
+    #[account(mut, address = market.lst_pool @ ErrorCode::YieldNotEnabled)]
+    pub stake_pool: UncheckedAccount<'info>,
+    /// CHECK: validated by the stake-pool program.
+    pub pool_withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK: validated by the stake-pool program.
+    #[account(mut)]
+    pub reserve_stake: UncheckedAccount<'info>,
+    /// CHECK: validated by the stake-pool program.
+    #[account(mut)]
+    pub manager_fee_account: UncheckedAccount<'info>,
+    /// CHECK: must be the program bound by `enable_lst_staking`, which is always `STAKE_POOL_PROGRAM_ID`.
+    #[account(
+        executable,
+        address = STAKE_POOL_PROGRAM_ID @ ErrorCode::UnsupportedYieldProgram,
+        constraint = market.lst_program == STAKE_POOL_PROGRAM_ID @ ErrorCode::YieldNotEnabled
+    )]
+    pub stake_pool_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct UnstakeIdle<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(mut, has_one = bet_mint, has_one = vault)]
+    pub market: Account<'info, Market>,
+    #[account(address = WSOL_MINT @ ErrorCode::WrongMint)]
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA signer for the vault, validated by seeds.
+    #[account(mut, seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = pool_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub lst_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub pool_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: must be the pool bound by `enable_lst_staking`.
+    #[account(mut, address = market.lst_pool @ ErrorCode::YieldNotEnabled)]
+    pub stake_pool: UncheckedAccount<'info>,
+    /// CHECK: validated by the stake-pool program.
+    pub pool_withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK: validated by the stake-pool program.
+    #[account(mut)]
+    pub reserve_stake: UncheckedAccount<'info>,
+    /// CHECK: validated by the stake-pool program.
+    #[account(mut)]
+    pub manager_fee_account: UncheckedAccount<'info>,
+    /// CHECK: must be the program bound by `enable_lst_staking`, which is always `STAKE_POOL_PROGRAM_ID`.
+    #[account(
+        executable,
+        address = STAKE_POOL_PROGRAM_ID @ ErrorCode::UnsupportedYieldProgram,
+        constraint = market.lst_program == STAKE_POOL_PROGRAM_ID @ ErrorCode::YieldNotEnabled
+    )]
+    pub stake_pool_program: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    /// CHECK: the stake history sysvar.
+    #[account(address = anchor_lang::solana_program::sysvar::stake_history::ID)]
+    pub stake_history: UncheckedAccount<'info>,
+    /// CHECK: the native stake program.
+    #[account(address = STAKE_PROGRAM_ID)]
+    pub stake_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Token-lending or stake-pool instruction: a one-byte tag then a `u64` amount.
+pub(crate) fn tagged_instruction(program_id: Pubkey, tag: u8, amount: u64, accounts: Vec<AccountMeta>) -> Instruction {
+    let mut data = Vec::with_capacity(9);
+    data.push(tag);
+    data.extend_from_slice(&amount.to_le_bytes());
+    Instruction {
+        program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Vault balance not owed to fees, tips, bridge refunds, set collateral or subsidies.
+pub(crate) fn deployable_idle(m: &Market, vault_amount: u64) -> u64 {
+    vault_amount
+        .saturating_sub(m.fees_accrued)
+        .saturating_sub(m.keeper_tip)
+        .saturating_sub(m.bridge_refunds_due)
+        .saturating_sub(m.set_collateral)
+        .saturating_sub(m.subsidy_yes)
+        .saturating_sub(m.subsidy_no)
+}
+
+/// Books `received` against the deployed principal and clears it.
+pub(crate) fn book_yield(m: &mut Market, received: u64) -> Result<(u64, u64, bool)> {
+    let principal = m.yield_deposited;
+    let earned = received.saturating_sub(principal);
+    let shortfall = principal.saturating_sub(received);
+    let from_fees = shortfall.min(m.fees_accrued);
+    m.fees_accrued -= from_fees;
+    m.yield_loss = m.yield_loss.checked_add(shortfall - from_fees).ok_or(ErrorCode::Overflow)?;
+    m.yield_deposited = 0;
+    m.yield_earned = m.yield_earned.checked_add(earned).ok_or(ErrorCode::Overflow)?;
+
+    // Claims cannot start while funds are deployed, so bumping the winner
+    // bonus here cannot shortchange anyone who already claimed.
+    let to_pool = m.yield_to_pool && m.resolved && m.winning_outcome != Outcome::Void as u8;
+    if to_pool {
+        m.winner_bonus = m.winner_bonus.checked_add(earned).ok_or(ErrorCode::Overflow)?;
+    } else {
+        m.fees_accrued = m.fees_accrued.checked_add(earned).ok_or(ErrorCode::Overflow)?;
+    }
+    Ok((earned, shortfall, to_pool))
+}
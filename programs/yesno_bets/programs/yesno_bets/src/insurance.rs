@@ -0,0 +1,352 @@
+use crate::*;
+
+pub const INSURANCE_FUND_SEED: &[u8] = b"insurance-fund";
+pub const INSURANCE_PREMIUM_BPS: u64 = 1_000; // Insured bets pay 10% of net stake on top
+pub const INSURANCE_COVER_BPS: u64 = 5_000; // Insured losers get 50% of net stake back
+
+pub(crate) fn init_insurance_fund(ctx: Context<InitInsuranceFund>, fee_share_bps: u16) -> Result<()> {
+    require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+    require!(fee_share_bps as u64 <= BPS_DENOM, ErrorCode::InvalidBps);
+
+    let f = &mut ctx.accounts.insurance_fund;
+    f.fee_share_bps = fee_share_bps;
+    f.total_contributed = 0;
+    f.total_paid_out = 0;
+    f.bump = ctx.bumps.insurance_fund;
+    f.max_jackpot_share_bps = 0;
+
+    emit!(InsuranceShareUpdated { fee_share_bps });
+
+    Ok(())
+}
+
+pub(crate) fn set_insurance_share(ctx: Context<SetInsuranceShare>, fee_share_bps: u16) -> Result<()> {
+    require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+    require!(ctx.accounts.config.timelock_secs == 0, ErrorCode::TimelockRequired);
+
+    apply_insurance_share(&mut ctx.accounts.insurance_fund, fee_share_bps)
+}
+
+pub(crate) fn pay_from_insurance(ctx: Context<PayFromInsurance>, market: Pubkey, amount: u64) -> Result<()> {
+    require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+    require!(amount > 0, ErrorCode::InvalidAmount);
+
+    let bump = ctx.accounts.insurance_fund.bump;
+    let seeds: &[&[u8]] = &[INSURANCE_FUND_SEED, &[bump]];
+    let signer: &[&[&[u8]]] = &[seeds];
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.insurance_vault.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.recipient_ata.to_account_info(),
+                authority: ctx.accounts.insurance_fund.to_account_info(),
+            },
+            signer,
+        ),
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    let f = &mut ctx.accounts.insurance_fund;
+    f.total_paid_out = f.total_paid_out.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+
+    emit!(InsurancePaidOut {
+        market,
+        mint: ctx.accounts.mint.key(),
+        recipient: ctx.accounts.recipient_ata.key(),
+        amount,
+    });
+
+    Ok(())
+}
+
+pub(crate) fn claim_insurance(ctx: Context<ClaimInsurance>) -> Result<()> {
+    let m = &mut ctx.accounts.market;
+    let p = &mut ctx.accounts.position;
+    require!(m.resolved, ErrorCode::NotResolved);
+    require!(
+        m.winning_outcome != Outcome::Void as u8 && m.winning_outcome != Outcome::Split as u8,
+        ErrorCode::NoPayout
+    );
+    require!(p.outcome != m.winning_outcome && p.insured > 0, ErrorCode::NoPayout);
+    require!(!p.frozen, ErrorCode::PositionFrozen);
+    let now = Clock::get()?.unix_timestamp;
+    require!(now <= m.claim_deadline_ts, ErrorCode::ClaimExpired);
+
+    let refund = p.insured
+        .checked_mul(INSURANCE_COVER_BPS)
+        .ok_or(ErrorCode::Overflow)?
+        / BPS_DENOM;
+    p.insured = 0;
+
+    let bump = ctx.accounts.insurance_fund.bump;
+    let seeds: &[&[u8]] = &[INSURANCE_FUND_SEED, &[bump]];
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.insurance_vault.to_account_info(),
+                mint: ctx.accounts.bet_mint.to_account_info(),
+                to: ctx.accounts.owner_ata.to_account_info(),
+                authority: ctx.accounts.insurance_fund.to_account_info(),
+            },
+            &[seeds],
+        ),
+        refund,
+        ctx.accounts.bet_mint.decimals,
+    )?;
+
+    let f = &mut ctx.accounts.insurance_fund;
+    f.total_paid_out = f.total_paid_out.checked_add(refund).ok_or(ErrorCode::Overflow)?;
+    m.insured_claimed = m.insured_claimed.checked_add(refund).ok_or(ErrorCode::Overflow)?;
+
+    emit!(InsuranceClaimed {
+        market: m.key(),
+        owner: p.owner,
+        index: p.index,
+        amount: refund,
+    });
+
+    // A settled loser has nothing left to collect once its cover is paid.
+    accrue_rewards(m, now)?;
+    (p.reward_debt, p.rewards_owed) = settle_rewards(m.reward_per_stake, p.amount, p.reward_debt, p.rewards_owed)?;
+    if position_closable(m, p, now)? {
+        if let Some(pf) = ctx.accounts.portfolio.as_mut() {
+            pf.untrack(m.key());
+        }
+        p.close(ctx.accounts.owner.to_account_info())?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn migrate_insurance_fund<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MigrateInsuranceFund<'info>>,
+) -> Result<()> {
+    require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+    let info = ctx.accounts.insurance_fund.to_account_info();
+    require!(info.owner == ctx.program_id, ErrorCode::Unauthorized);
+    let old_len = info.data_len();
+    if old_len >= InsuranceFund::LEN {
+        return Ok(());
+    }
+
+    let mut max_jackpot_share_bps = 0;
+    for jackpot in ctx.remaining_accounts {
+        let j = Account::<Jackpot>::try_from(jackpot)?;
+        max_jackpot_share_bps = max_jackpot_share_bps.max(j.fee_share_bps);
+    }
+
+    let rent = Rent::get()?.minimum_balance(InsuranceFund::LEN);
+    let top_up = rent.saturating_sub(info.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: info.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    info.resize(InsuranceFund::LEN)?;
+    let mut data = info.try_borrow_mut_data()?;
+    let mut f = InsuranceFund::try_deserialize(&mut &data[..])?;
+    f.max_jackpot_share_bps = max_jackpot_share_bps;
+    f.try_serialize(&mut &mut data[..])?;
+
+    emit!(InsuranceFundMigrated {
+        old_len: old_len as u32,
+        new_len: InsuranceFund::LEN as u32,
+        max_jackpot_share_bps,
+    });
+
+    Ok(())
+}
+
+/// Protocol-wide insurance pool. Holds one vault (ATA) per mint.
+#[account]
+pub struct InsuranceFund {
+    pub fee_share_bps: u16,
+    pub total_contributed: u64,
+    pub total_paid_out: u64,
+    pub bump: u8,
+    /// Highest fee share any jackpot has been given; kept so the two shares never add up past `BPS_DENOM`.
+    pub max_jackpot_share_bps: u16,
+}
+
+impl InsuranceFund {
+    pub const LEN: usize = 8 + 2 + 8 + 8 + 1 + 2;
+}
+
+#[event]
+pub struct InsuranceShareUpdated {
+    pub fee_share_bps: u16,
+}
+
+#[event]
+pub struct InsuranceContributed {
+    pub market: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct InsurancePaidOut {
+    pub market: Pubkey,
+    pub mint: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct BetInsured {
+    pub market: Pubkey,
+    pub bettor: Pubkey,
+    pub insured: u64,
+    pub premium: u64,
+}
+
+#[event]
+pub struct InsuranceClaimed {
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub index: u16,
+    pub amount: u64,
+}
+
+#[event]
+pub struct InsuranceFundMigrated {
+    pub old_len: u32,
+    pub new_len: u32,
+    pub max_jackpot_share_bps: u16,
+}
+
+#[derive(Accounts)]
+pub struct InitInsuranceFund<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = InsuranceFund::LEN,
+        seeds = [INSURANCE_FUND_SEED],
+        bump
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetInsuranceShare<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [INSURANCE_FUND_SEED], bump = insurance_fund.bump)]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct PayFromInsurance<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(mut, seeds = [INSURANCE_FUND_SEED], bump = insurance_fund.bump)]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = insurance_fund,
+        associated_token::token_program = token_program
+    )]
+    pub insurance_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub recipient_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimInsurance<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut, has_one = bet_mint)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = market,
+        seeds = [POSITION_SEED, market.key().as_ref(), owner.key().as_ref(), &position.index.to_le_bytes()],
+        bump
+    )]
+    pub position: Account<'info, Position>,
+
+    /// Optional portfolio index; kept in step with the positions opened and closed here when passed.
+    #[account(mut, seeds = [PORTFOLIO_SEED, owner.key().as_ref()], bump = portfolio.bump)]
+    pub portfolio: Option<Account<'info, Portfolio>>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, seeds = [INSURANCE_FUND_SEED], bump = insurance_fund.bump)]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    #[account(
+        mut,
+        associated_token::mint = bet_mint,
+        associated_token::authority = insurance_fund,
+        associated_token::token_program = token_program
+    )]
+    pub insurance_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = bet_mint,
+        token::authority = owner,
+        token::token_program = token_program
+    )]
+    pub owner_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateInsuranceFund<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// CHECK: may be shorter than `InsuranceFund::LEN`, so it is not deserialized; seeds and owner are checked.
+    #[account(mut, seeds = [INSURANCE_FUND_SEED], bump)]
+    pub insurance_fund: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn calculate_insurance_cut(fees: u64, fee_share_bps: u16) -> Result<u64> {
+    fees.checked_mul(fee_share_bps as u64)
+        .ok_or(ErrorCode::Overflow)?
+        .checked_div(BPS_DENOM)
+        .ok_or(error!(ErrorCode::Overflow))
+}
+
+pub(crate) fn apply_insurance_share(f: &mut InsuranceFund, fee_share_bps: u16) -> Result<()> {
+    require!(
+        fee_share_bps as u64 + f.max_jackpot_share_bps as u64 <= BPS_DENOM,
+        ErrorCode::InvalidBps
+    );
+    f.fee_share_bps = fee_share_bps;
+
+    emit!(InsuranceShareUpdated { fee_share_bps });
+
+    Ok(())
+}
@@ -0,0 +1,476 @@
+use crate::*;
+
+pub const JACKPOT_SEED: &[u8] = b"jackpot";
+pub const JACKPOT_DRAW_TIMEOUT: i64 = 24 * 3600; // Anyone may cancel a stuck draw after this
+
+pub(crate) fn init_jackpot(ctx: Context<InitJackpot>, fee_share_bps: u16, trigger_amount: u64) -> Result<()> {
+    require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+    let j = &mut ctx.accounts.jackpot;
+    j.bet_mint = ctx.accounts.bet_mint.key();
+    j.vault = ctx.accounts.jackpot_vault.key();
+    j.bump = ctx.bumps.jackpot;
+
+    apply_jackpot(j, &mut ctx.accounts.insurance_fund, fee_share_bps, trigger_amount)
+}
+
+pub(crate) fn set_jackpot(ctx: Context<SetJackpot>, fee_share_bps: u16, trigger_amount: u64) -> Result<()> {
+    require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+    require!(ctx.accounts.config.timelock_secs == 0, ErrorCode::TimelockRequired);
+
+    apply_jackpot(&mut ctx.accounts.jackpot, &mut ctx.accounts.insurance_fund, fee_share_bps, trigger_amount)
+}
+
+pub(crate) fn migrate_jackpot(ctx: Context<MigrateJackpot>) -> Result<()> {
+    require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+    let info = ctx.accounts.jackpot.to_account_info();
+    require!(info.owner == ctx.program_id, ErrorCode::Unauthorized);
+    let old_len = info.data_len();
+    if old_len >= Jackpot::LEN {
+        return Ok(());
+    }
+
+    let rent = Rent::get()?.minimum_balance(Jackpot::LEN);
+    let top_up = rent.saturating_sub(info.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: info.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    info.resize(Jackpot::LEN)?;
+
+    emit!(JackpotMigrated {
+        bet_mint: ctx.accounts.bet_mint.key(),
+        old_len: old_len as u32,
+        new_len: Jackpot::LEN as u32,
+    });
+
+    Ok(())
+}
+
+pub(crate) fn set_jackpot_market(ctx: Context<SetJackpotMarket>) -> Result<()> {
+    require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+    let j = &mut ctx.accounts.jackpot;
+    require!(j.market == Pubkey::default(), ErrorCode::JackpotDrawPending);
+
+    let m = &mut ctx.accounts.market;
+    validate_market_active(m)?;
+    require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+    m.jackpot_pending = true;
+    j.market = m.key();
+
+    emit!(JackpotMarketSet {
+        bet_mint: j.bet_mint,
+        market: j.market,
+    });
+
+    Ok(())
+}
+
+pub(crate) fn request_jackpot_draw(ctx: Context<RequestJackpotDraw>) -> Result<()> {
+    let caller = ctx.accounts.caller.key();
+    let m = &mut ctx.accounts.market;
+    require!(
+        caller == owner_pubkey() || (m.keeper != Pubkey::default() && caller == m.keeper),
+        ErrorCode::Unauthorized
+    );
+    let j = &mut ctx.accounts.jackpot;
+    require!(j.randomness == Pubkey::default(), ErrorCode::JackpotDrawPending);
+    let pot = ctx.accounts.jackpot_vault.amount;
+    require!(pot > 0 && pot >= j.trigger_amount, ErrorCode::JackpotNotTriggered);
+
+    require!(m.resolved, ErrorCode::NotResolved);
+    require!(
+        m.winning_outcome != Outcome::Void as u8 && m.winning_outcome != Outcome::Split as u8,
+        ErrorCode::NoPayout
+    );
+    require!(jackpot_pool(m) > 0, ErrorCode::NoPayout);
+
+    let clock = Clock::get()?;
+    let data = ctx.accounts.randomness.try_borrow_data()?;
+    let randomness = parse_randomness(&data)?;
+    require!(randomness.seed_slot == clock.slot.saturating_sub(1), ErrorCode::InvalidRandomness);
+    require!(randomness.reveal_slot == 0, ErrorCode::InvalidRandomness);
+    drop(data);
+
+    j.randomness = ctx.accounts.randomness.key();
+    j.commit_slot = clock.slot;
+    j.requested_ts = clock.unix_timestamp;
+
+    emit!(JackpotDrawRequested {
+        bet_mint: j.bet_mint,
+        market: j.market,
+        randomness: j.randomness,
+        pot,
+    });
+
+    Ok(())
+}
+
+pub(crate) fn settle_jackpot_draw<'info>(ctx: Context<'_, '_, 'info, 'info, SettleJackpotDraw<'info>>) -> Result<()> {
+    let j = &ctx.accounts.jackpot;
+    let m = &ctx.accounts.market;
+    require_keys_eq!(j.market, m.key(), ErrorCode::WrongMarket);
+    require_keys_eq!(ctx.accounts.randomness.key(), j.randomness, ErrorCode::InvalidRandomness);
+
+    let data = ctx.accounts.randomness.try_borrow_data()?;
+    let randomness = parse_randomness(&data)?;
+    require!(randomness.reveal_slot > j.commit_slot, ErrorCode::RandomnessNotRevealed);
+    let value = randomness.value;
+    drop(data);
+
+    let winning_pool = jackpot_pool(m);
+    let ticket = u64::from_le_bytes(value[..8].try_into().map_err(|_| ErrorCode::InvalidRandomness)?) % winning_pool;
+
+    require!(!ctx.remaining_accounts.is_empty(), ErrorCode::InvalidBatch);
+    let mut covered = j.covered;
+    let mut last_key = j.cursor;
+    let (mut winner, mut stake) = (j.winner, j.winner_stake);
+    for info in ctx.remaining_accounts.iter() {
+        require!(info.key() > last_key, ErrorCode::InvalidPosition);
+        last_key = info.key();
+        let p = Account::<Position>::try_from(info)?;
+        require_keys_eq!(p.market, m.key(), ErrorCode::WrongMarket);
+        require!(p.outcome == m.winning_outcome, ErrorCode::InvalidPosition);
+        let end = covered.checked_add(p.amount).ok_or(ErrorCode::Overflow)?;
+        if winner == Pubkey::default() && ticket < end {
+            (winner, stake) = (p.owner, p.amount);
+        }
+        covered = end;
+    }
+    require!(covered <= winning_pool, ErrorCode::InvalidPosition);
+
+    if covered < winning_pool {
+        let j = &mut ctx.accounts.jackpot;
+        j.covered = covered;
+        j.cursor = last_key;
+        j.winner = winner;
+        j.winner_stake = stake;
+
+        emit!(JackpotDrawProgress {
+            bet_mint: j.bet_mint,
+            market: j.market,
+            covered,
+            winning_pool,
+        });
+        return Ok(());
+    }
+
+    let winner_ata = ctx.accounts.winner_ata.as_ref().ok_or(ErrorCode::JackpotAccountsMissing)?;
+    require_keys_eq!(winner_ata.owner, winner, ErrorCode::Unauthorized);
+
+    let pot = ctx.accounts.jackpot_vault.amount;
+    let mint_key = ctx.accounts.bet_mint.key();
+    let seeds: &[&[u8]] = &[JACKPOT_SEED, mint_key.as_ref(), &[j.bump]];
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.jackpot_vault.to_account_info(),
+                mint: ctx.accounts.bet_mint.to_account_info(),
+                to: winner_ata.to_account_info(),
+                authority: ctx.accounts.jackpot.to_account_info(),
+            },
+            &[seeds],
+        ),
+        pot,
+        ctx.accounts.bet_mint.decimals,
+    )?;
+
+    let market_key = ctx.accounts.market.key();
+    ctx.accounts.market.jackpot_pending = false;
+    let j = &mut ctx.accounts.jackpot;
+    clear_jackpot_draw(j);
+    j.total_paid = j.total_paid.checked_add(pot).ok_or(ErrorCode::Overflow)?;
+
+    emit!(JackpotWon {
+        bet_mint: j.bet_mint,
+        market: market_key,
+        winner,
+        stake,
+        amount: pot,
+    });
+
+    Ok(())
+}
+
+pub(crate) fn cancel_jackpot_draw(ctx: Context<CancelJackpotDraw>) -> Result<()> {
+    let j = &mut ctx.accounts.jackpot;
+    require_keys_eq!(j.market, ctx.accounts.market.key(), ErrorCode::WrongMarket);
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        ctx.accounts.caller.key() == owner_pubkey()
+            || (j.randomness != Pubkey::default()
+                && now > j.requested_ts.saturating_add(JACKPOT_DRAW_TIMEOUT)),
+        ErrorCode::Unauthorized
+    );
+
+    clear_jackpot_draw(j);
+    ctx.accounts.market.jackpot_pending = false;
+
+    emit!(JackpotDrawCancelled {
+        bet_mint: j.bet_mint,
+        market: ctx.accounts.market.key(),
+    });
+
+    Ok(())
+}
+
+/// Progressive jackpot for one bet mint, fed by a share of swept fees.
+#[account]
+pub struct Jackpot {
+    pub bet_mint: Pubkey,
+    pub vault: Pubkey,
+    pub fee_share_bps: u16,
+    pub trigger_amount: u64,
+    pub market: Pubkey,
+    pub randomness: Pubkey,
+    pub commit_slot: u64,
+    pub requested_ts: i64,
+    pub total_paid: u64,
+    pub bump: u8,
+    pub covered: u64,
+    pub cursor: Pubkey,
+    pub winner: Pubkey,
+    pub winner_stake: u64,
+}
+
+impl Jackpot {
+    pub const LEN: usize = 8 + 32 + 32 + 2 + 8 + 32 + 32 + 8 + 8 + 8 + 1 + 8 + 32 + 32 + 8;
+}
+
+#[event]
+pub struct JackpotUpdated {
+    pub bet_mint: Pubkey,
+    pub fee_share_bps: u16,
+    pub trigger_amount: u64,
+}
+
+#[event]
+pub struct JackpotAccrued {
+    pub market: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct JackpotDrawRequested {
+    pub bet_mint: Pubkey,
+    pub market: Pubkey,
+    pub randomness: Pubkey,
+    pub pot: u64,
+}
+
+#[event]
+pub struct JackpotWon {
+    pub bet_mint: Pubkey,
+    pub market: Pubkey,
+    pub winner: Pubkey,
+    pub stake: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct JackpotMarketSet {
+    pub bet_mint: Pubkey,
+    pub market: Pubkey,
+}
+
+#[event]
+pub struct JackpotDrawProgress {
+    pub bet_mint: Pubkey,
+    pub market: Pubkey,
+    pub covered: u64,
+    pub winning_pool: u64,
+}
+
+#[event]
+pub struct JackpotMigrated {
+    pub bet_mint: Pubkey,
+    pub old_len: u32,
+    pub new_len: u32,
+}
+
+#[event]
+pub struct JackpotDrawCancelled {
+    pub bet_mint: Pubkey,
+    pub market: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct InitJackpot<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Jackpot::LEN,
+        seeds = [JACKPOT_SEED, bet_mint.key().as_ref()],
+        bump
+    )]
+    pub jackpot: Account<'info, Jackpot>,
+
+    #[account(mut, seeds = [INSURANCE_FUND_SEED], bump = insurance_fund.bump)]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = bet_mint,
+        associated_token::authority = jackpot,
+        associated_token::token_program = token_program
+    )]
+    pub jackpot_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct SetJackpot<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [JACKPOT_SEED, jackpot.bet_mint.as_ref()], bump = jackpot.bump)]
+    pub jackpot: Account<'info, Jackpot>,
+    #[account(mut, seeds = [INSURANCE_FUND_SEED], bump = insurance_fund.bump)]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateJackpot<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: may be shorter than `Jackpot::LEN`, so it is not deserialized; seeds and owner are checked.
+    #[account(mut, seeds = [JACKPOT_SEED, bet_mint.key().as_ref()], bump)]
+    pub jackpot: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetJackpotMarket<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(mut, seeds = [JACKPOT_SEED, market.bet_mint.as_ref()], bump = jackpot.bump)]
+    pub jackpot: Account<'info, Jackpot>,
+
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct RequestJackpotDraw<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(mut, seeds = [JACKPOT_SEED, market.bet_mint.as_ref()], bump = jackpot.bump)]
+    pub jackpot: Account<'info, Jackpot>,
+
+    #[account(address = jackpot.vault)]
+    pub jackpot_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, address = jackpot.market @ ErrorCode::WrongMarket)]
+    pub market: Account<'info, Market>,
+
+    /// CHECK: Switchboard randomness account, owner-checked and parsed in `parse_randomness`.
+    #[account(owner = SWITCHBOARD_ON_DEMAND_ID @ ErrorCode::InvalidRandomness)]
+    pub randomness: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SettleJackpotDraw<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(mut, has_one = bet_mint, seeds = [JACKPOT_SEED, bet_mint.key().as_ref()], bump = jackpot.bump)]
+    pub jackpot: Account<'info, Jackpot>,
+
+    #[account(mut, address = jackpot.vault)]
+    pub jackpot_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    /// CHECK: must be the randomness account committed by `request_jackpot_draw`.
+    #[account(owner = SWITCHBOARD_ON_DEMAND_ID @ ErrorCode::InvalidRandomness)]
+    pub randomness: UncheckedAccount<'info>,
+
+    /// Needed on the final page only.
+    #[account(mut, token::mint = bet_mint, token::token_program = token_program)]
+    pub winner_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CancelJackpotDraw<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(mut, seeds = [JACKPOT_SEED, market.bet_mint.as_ref()], bump = jackpot.bump)]
+    pub jackpot: Account<'info, Jackpot>,
+
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+}
+
+/// The jackpot stored at `info`, or `None` while its PDA is still empty.
+pub(crate) fn existing_jackpot(info: &AccountInfo) -> Result<Option<Jackpot>> {
+    if info.data_is_empty() {
+        return Ok(None);
+    }
+    require_keys_eq!(*info.owner, crate::ID, ErrorCode::JackpotAccountsMissing);
+    let data = info.try_borrow_data()?;
+    Ok(Some(Jackpot::try_deserialize(&mut &data[..])?))
+}
+
+pub(crate) fn apply_jackpot(j: &mut Jackpot, f: &mut InsuranceFund, fee_share_bps: u16, trigger_amount: u64) -> Result<()> {
+    require!(
+        fee_share_bps as u64 + f.fee_share_bps as u64 <= BPS_DENOM,
+        ErrorCode::InvalidBps
+    );
+    f.max_jackpot_share_bps = f.max_jackpot_share_bps.max(fee_share_bps);
+    j.fee_share_bps = fee_share_bps;
+    j.trigger_amount = trigger_amount;
+
+    emit!(JackpotUpdated {
+        bet_mint: j.bet_mint,
+        fee_share_bps,
+        trigger_amount,
+    });
+
+    Ok(())
+}
+
+pub(crate) fn clear_jackpot_draw(j: &mut Jackpot) {
+    j.market = Pubkey::default();
+    j.randomness = Pubkey::default();
+    j.covered = 0;
+    j.cursor = Pubkey::default();
+    j.winner = Pubkey::default();
+    j.winner_stake = 0;
+}
+
+/// Winning stake held in positions, i.e. excluding the house's counter-bets.
+pub(crate) fn jackpot_pool(m: &Market) -> u64 {
+    let house = if m.winning_outcome == Outcome::Yes as u8 {
+        m.house_yes
+    } else if m.winning_outcome == Outcome::No as u8 {
+        m.house_no
+    } else {
+        0
+    };
+    outcome_pool(m, m.winning_outcome).saturating_sub(house)
+}
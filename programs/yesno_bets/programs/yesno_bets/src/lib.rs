@@ -1,15 +1,50 @@
 #![allow(unexpected_cfgs)]
 #![allow(deprecated)]
+// `is_multiple_of` is newer than the SBF toolchain's rustc.
+#![allow(clippy::manual_is_multiple_of)]
 
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{get_stack_height, AccountMeta, Instruction, TRANSACTION_LEVEL_STACK_HEIGHT},
+    program::invoke_signed,
+    sysvar::instructions::{self as ix_sysvar, load_current_index_checked, load_instruction_at_checked},
+};
+use anchor_lang::system_program::{self, CreateAccount};
 use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::{self, Mint, Token, TokenAccount, Transfer},
+    associated_token::{self, AssociatedToken},
+    token_interface::{
+        self, Approve, Burn, CloseAccount, Mint, MintTo, Revoke, TokenAccount, TokenInterface, TransferChecked,
+    },
+};
+use mpl_bubblegum::{
+    instructions::MintV1CpiBuilder,
+    types::{MetadataArgs, TokenProgramVersion, TokenStandard},
 };
+use mpl_token_metadata::accounts::Metadata;
+use solana_keccak_hasher as keccak;
+use solana_sdk_ids::ed25519_program;
 use std::str::FromStr;
 
 declare_id!("D1M35W2QNQ6fDMUkYe1ZBp79oxHmQS7YXiHmctqKfefq");
 
+mod idle_yield;
+mod insurance;
+mod jackpot;
+mod merkle_refund;
+mod operator;
+mod rounds;
+mod subsidy;
+mod template;
+
+pub use idle_yield::*;
+pub use insurance::*;
+pub use jackpot::*;
+pub use merkle_refund::*;
+pub use operator::*;
+pub use rounds::*;
+pub use subsidy::*;
+pub use template::*;
+
 // --------- Owner & constants ---------
 pub const OWNER: &str = "9sjC1DmEhMXHwmSNaq3jQrfAFzfSrPBooDjDDjukuyoR";
 // HARDCODE YOUR FEE WALLET HERE:
@@ -19,8 +54,86 @@ pub const POSITION_SEED: &[u8] = b"position";
 pub const FEE_BPS: u64 = 250;       // 2.5%
 pub const BPS_DENOM: u64 = 10_000;  // 100%
 pub const MAX_BET_LIMIT: u64 = 100; // Maximum tokens per position (INCLUDING FEES)
+pub const MAX_POSITIONS_PER_MARKET: u16 = 4; // Position indexes per wallet and market, which bounds per-wallet exposure
 pub const MIN_BET_AMOUNT: u64 = 1_000; // Minimum bet amount
 pub const MAX_MARKET_DURATION: i64 = 30 * 24 * 3600; // 30 days max
+pub const MARKET_MINT_SEED: &[u8] = b"market-mint";
+pub const MAX_MARKET_MINTS: u8 = 8; // Mints per multi-mint market (claim bitmask is a u8)
+pub const ACCOUNTING_DECIMALS: u32 = 6; // Multi-mint pools are kept in USD with 6 decimals
+pub const MAX_ORACLE_STALENESS: i64 = 120; // Seconds before a feed value is considered stale
+pub const USER_STATS_SEED: &[u8] = b"user-stats";
+pub const REWARD_CONFIG_SEED: &[u8] = b"reward-config";
+pub const REWARD_MINT_AUTH_SEED: &[u8] = b"reward-mint-auth";
+pub const REBATE_SEED: &[u8] = b"rebate";
+pub const EMERGENCY_WITHDRAW_DELAY: i64 = 2 * 365 * 24 * 3600; // 2 years after cutoff
+pub const EMERGENCY_WITHDRAW_TIMELOCK: i64 = 7 * 24 * 3600; // 7 days between queue and execute
+pub const DEFAULT_CLAIM_WINDOW: i64 = 180 * 24 * 3600; // Claims stay open 180 days after cutoff by default
+pub const RENT_CRANK_BPS: u64 = 2_000; // 20% of reclaimed position rent goes to the cranker
+pub const MAX_EVIDENCE_URI_LEN: usize = 128;
+pub const MARKET_SEED: &[u8] = b"market";
+pub const MAX_BATCH_MARKETS: usize = 8; // Markets per create_markets call (account limit bound)
+pub const PROGRAM_VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const SNAPSHOT_SEED: &[u8] = b"snapshots";
+pub const SNAPSHOT_CAPACITY: usize = 64; // Ring buffer length per market
+pub const SNAPSHOT_INTERVAL: i64 = 5 * 60; // Minimum seconds between snapshots
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const DEFAULT_MIN_DURATION: i64 = 10 * 60; // Markets must run at least 10 minutes
+pub const MAX_SCHEDULE_HORIZON: i64 = 5 * 365 * 24 * 3600; // Sanity bound on event and claim timestamps
+pub const PENDING_CHANGE_SEED: &[u8] = b"pending-change";
+pub const CHANGE_GRACE_PERIOD: i64 = 7 * 24 * 3600; // Queued changes lapse a week after becoming executable
+pub const MAX_TIMELOCK: i64 = 30 * 24 * 3600;
+pub const VOTE_SEED: &[u8] = b"vote";
+pub const VOTE_AUTH_SEED: &[u8] = b"vote-auth";
+pub const VOTE_WINDOW: i64 = 3 * 24 * 3600; // Voting stays open for 3 days
+pub const RESOLVER_SEED: &[u8] = b"resolver";
+pub const MAX_RESOLVER_NAME_LEN: usize = 32;
+pub const DISPUTE_WINDOW: i64 = 24 * 3600; // Time to dispute a resolver's proposal
+pub const DISPUTE_BOND: u64 = 100_000_000; // 0.1 SOL posted by the disputer
+pub const APPEAL_WINDOW: i64 = 24 * 3600; // Time to appeal a tallied vote
+pub const APPEAL_DELAY: i64 = 7 * 24 * 3600; // Admin cannot rule on an appeal before this
+pub const APPEAL_BOND: u64 = 1_000_000_000; // 1 SOL, 10x the dispute bond
+pub const MAX_BET_TAG_LEN: usize = 32; // Attribution tag echoed in BetPlaced
+pub const RECEIPT_CONFIG_SEED: &[u8] = b"receipt-config";
+pub const RECEIPT_AUTH_SEED: &[u8] = b"receipt-auth";
+pub const MAX_RECEIPT_URI_BASE_LEN: usize = 100;
+pub const SPL_NOOP_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+pub const SPL_ACCOUNT_COMPRESSION_ID: Pubkey = pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+pub const KEEPER_TIP_BPS: u64 = 500; // Share of a market's fees paid to whoever cranks its settlement
+pub const MARKET_GUARD_SEED: &[u8] = b"market-guard";
+pub const ARCHIVE_SEED: &[u8] = b"archive";
+pub const POOL_SHARD_SEED: &[u8] = b"pool-shard";
+pub const MAX_POOL_SHARDS: usize = 8; // Merge tracking is a u8 bitmask
+pub const FEE_DISCOUNT_SEED: &[u8] = b"fee-discount";
+// Wormhole Core Bridge (mainnet; devnet is 3u8hJUVTA4jH1wYAyUur7FFZVQ8H635K3tSHHF4ssjQ5).
+pub const WORMHOLE_CORE_BRIDGE_ID: Pubkey = pubkey!("worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth");
+// Wormhole Token Bridge (mainnet; devnet is DZnkkTmCiFWfYTfT41X3Rd1kDgozqzxWaHqsw6W4x2oe)
+// and the instruction tags this program sends it.
+pub const TOKEN_BRIDGE_ID: Pubkey = pubkey!("wormDTUJ6AWPNvk59vGQbDvGJmqbDTdgWgAqcLBCgUb");
+pub const TB_TRANSFER_WRAPPED: u8 = 4;
+pub const TB_TRANSFER_NATIVE: u8 = 5;
+pub const TB_COMPLETE_NATIVE_WITH_PAYLOAD: u8 = 9;
+pub const TB_COMPLETE_WRAPPED_WITH_PAYLOAD: u8 = 10;
+pub const BRIDGE_ACCOUNT_SEED: &[u8] = b"bridge-account";
+pub const BRIDGE_REFUND_SEED: &[u8] = b"bridge-refund";
+pub const SESSION_SEED: &[u8] = b"session";
+pub const MAX_SESSION_SECS: i64 = 7 * 24 * 60 * 60;
+pub const HOUSE_SEED: &[u8] = b"house";
+pub const APPROVED_MINT_SEED: &[u8] = b"approved-mint";
+pub const LIMIT_BET_SEED: &[u8] = b"limit-bet";
+pub const ORDER_BOOK_SEED: &[u8] = b"order-book";
+pub const ORDER_BOOK_DEPTH: usize = 16; // Resting orders per side
+pub const OUTCOME_MINT_SEED: &[u8] = b"outcome-mint";
+pub const PORTFOLIO_SEED: &[u8] = b"portfolio";
+pub const MAX_PORTFOLIO_MARKETS: usize = 64;
+pub const ATTESTATION_SEED: &[u8] = b"attestation";
+pub const SWITCHBOARD_ON_DEMAND_ID: Pubkey = pubkey!("SBondMDrcV3K4kxZR1HNVT7osZxAHVHgYXL5Ze1oMUv");
+pub const REWARD_PRECISION: u128 = sillymarket_math::REWARD_PRECISION; // Scale of `Market::reward_per_stake`
+pub const BRACKET_SEED: &[u8] = b"bracket";
+pub const MAX_BRACKET_ROUNDS: u8 = 5; // 32 entrants, 31 matches
+pub const BRACKET_TBD: u8 = u8::MAX; // Entrant not yet known
+pub const SEASON_PASS_CONFIG_SEED: &[u8] = b"season-pass-config";
+pub const SEASON_PASS_SEED: &[u8] = b"season-pass";
+pub const SECONDS_PER_DAY: i64 = 86_400;
 
 #[program]
 pub mod yesno_bets {
@@ -36,6 +149,8 @@ pub mod yesno_bets {
         stats.total_fees = 0;
         stats.total_bettors = 0;
         stats.is_initialized = true;
+        stats.markets_resolved = 0;
+        stats.fees_swept = 0;
         
         emit!(ProgramInitialized {
             authority: stats.authority,
@@ -45,38 +160,83 @@ pub mod yesno_bets {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn create_market(
         ctx: Context<CreateMarket>, 
         cutoff_ts: i64,
         question: String,
         category: String,
+        claim_deadline_ts: Option<i64>,
+        event_ts: Option<i64>,
+        oracle_threshold: Option<i64>,
+        resolve_authority: Option<Pubkey>,
+        allow_duplicate: bool,
     ) -> Result<()> {
-        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require_market_creator(ctx.accounts.owner.key(), ctx.accounts.operator.as_ref())?;
+        require!(
+            ctx.accounts.oracle_feed.is_some() == oracle_threshold.is_some(),
+            ErrorCode::WrongOracle
+        );
+        require_mint_allowed(&ctx.accounts.config, ctx.accounts.approved_mint.as_ref())?;
         
         let now = Clock::get()?.unix_timestamp;
-        require!(cutoff_ts > now, ErrorCode::InvalidCutoff);
-        require!(cutoff_ts <= now + MAX_MARKET_DURATION, ErrorCode::MarketDurationTooLong);
+        let (event_ts, claim_deadline_ts) = validate_schedule(
+            now,
+            cutoff_ts,
+            event_ts,
+            claim_deadline_ts,
+            ctx.accounts.config.min_duration,
+        )?;
         require!(question.len() <= 280, ErrorCode::MetadataTooLong);
         require!(category.len() <= 50, ErrorCode::MetadataTooLong);
 
+        // The guard PDA for (question, cutoff, mint) can only be created once,
+        // so an accidental second copy fails unless explicitly allowed.
+        match ctx.accounts.market_guard.as_mut() {
+            Some(guard) => guard.market = ctx.accounts.market.key(),
+            None => require!(allow_duplicate, ErrorCode::DuplicateMarket),
+        }
+
         let stats = &mut ctx.accounts.program_stats;
+        let market_id = stats.total_markets;
         stats.total_markets = stats.total_markets.checked_add(1).ok_or(ErrorCode::Overflow)?;
 
         let m = &mut ctx.accounts.market;
-        m.creator = ctx.accounts.owner.key();
-        m.bet_mint = ctx.accounts.bet_mint.key();
-        m.vault = ctx.accounts.vault.key();
-        m.vault_authority = ctx.accounts.vault_authority.key();
-        m.cutoff_ts = cutoff_ts;
-        m.resolved = false;
-        m.winning_outcome = Outcome::Unset as u8;
-        m.total_yes = 0;
-        m.total_no = 0;
-        m.fees_accrued = 0;
-        m.emergency_paused = false;
-        m.fee_receiver = fee_wallet_pubkey(); // Uses hardcoded fee wallet
-        m.created_at = now;
-        m.resolution_reason = ResolutionReason::Unset as u8;
+        init_market(
+            m,
+            ctx.accounts.owner.key(),
+            ctx.accounts.bet_mint.key(),
+            ctx.accounts.vault.key(),
+            ctx.accounts.vault_authority.key(),
+            cutoff_ts,
+            event_ts,
+            claim_deadline_ts,
+            now,
+        );
+        m.market_id = market_id;
+        m.bump = ctx.bumps.market;
+        if let Some(op) = ctx.accounts.operator.as_mut() {
+            assign_operator(m, op)?;
+        }
+        if let Some(resolver) = ctx.accounts.resolver.as_ref() {
+            require!(resolver.approved, ErrorCode::WrongResolver);
+            m.resolver = resolver.authority;
+        }
+        if let Some(dest) = ctx.accounts.fee_destination.as_ref() {
+            m.fee_destination = dest.key();
+        }
+        if let Some(authority) = resolve_authority {
+            m.resolve_authority = authority;
+        }
+        if let (Some(feed), Some(threshold)) = (ctx.accounts.oracle_feed.as_ref(), oracle_threshold) {
+            m.oracle_feed = feed.key();
+            m.oracle_threshold = threshold;
+            emit!(ResolutionOracleBound {
+                market: m.key(),
+                oracle_feed: m.oracle_feed,
+                threshold,
+            });
+        }
         
         let metadata = &mut ctx.accounts.market_metadata;
         metadata.market = m.key();
@@ -91,105 +251,335 @@ pub mod yesno_bets {
             bet_mint: m.bet_mint,
             question: metadata.question.clone(),
             category: metadata.category.clone(),
+            claim_deadline_ts: m.claim_deadline_ts,
+            event_ts: m.event_ts,
         });
         
         Ok(())
     }
 
-    pub fn update_cutoff(ctx: Context<UpdateCutoff>, new_cutoff_ts: i64) -> Result<()> {
+    /// Grows a stats account from before `markets_resolved`/`fees_swept`; they start at zero.
+    pub fn migrate_program_stats(ctx: Context<MigrateProgramStats>) -> Result<()> {
         require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
-        let m = &mut ctx.accounts.market;
-        
-        validate_market_active(m)?;
 
-        let now = Clock::get()?.unix_timestamp;
-        require!(new_cutoff_ts > now, ErrorCode::InvalidCutoff);
-        require!(new_cutoff_ts <= now + MAX_MARKET_DURATION, ErrorCode::MarketDurationTooLong);
+        let info = ctx.accounts.program_stats.to_account_info();
+        require!(info.owner == ctx.program_id, ErrorCode::Unauthorized);
+        let old_len = info.data_len();
+        if old_len >= ProgramStats::LEN {
+            return Ok(());
+        }
 
-        m.cutoff_ts = new_cutoff_ts;
-        
-        emit!(CutoffUpdated {
-            market: m.key(),
-            new_cutoff_ts,
+        let rent = Rent::get()?.minimum_balance(ProgramStats::LEN);
+        let top_up = rent.saturating_sub(info.lamports());
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.owner.to_account_info(),
+                        to: info.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        info.resize(ProgramStats::LEN)?;
+
+        emit!(ProgramStatsMigrated {
+            old_len: old_len as u32,
+            new_len: ProgramStats::LEN as u32,
         });
-        
+
         Ok(())
     }
 
-    pub fn place_bet(ctx: Context<PlaceBet>, outcome: Outcome, amount: u64) -> Result<()> {
+    /// Moves a position from its unindexed address to `index` under the indexed seeds.
+    pub fn migrate_legacy_position(ctx: Context<MigrateLegacyPosition>, index: u16) -> Result<()> {
+        const LEGACY_LEN: usize = 8 + 32 + 32 + 1 + 1 + 8;
+        require!(index < MAX_POSITIONS_PER_MARKET, ErrorCode::PositionIndexTooHigh);
+
+        let legacy = ctx.accounts.legacy_position.to_account_info();
+        require!(legacy.owner == ctx.program_id, ErrorCode::Unauthorized);
+        let (outcome, claimed, amount) = {
+            let data = legacy.try_borrow_data()?;
+            require!(
+                data.len() == LEGACY_LEN && data.starts_with(Position::DISCRIMINATOR),
+                ErrorCode::InvalidPosition
+            );
+            require!(data[8..40] == ctx.accounts.owner.key().to_bytes(), ErrorCode::Unauthorized);
+            require!(data[40..72] == ctx.accounts.market.key().to_bytes(), ErrorCode::WrongMarket);
+            (data[72], data[73] != 0, u64::from_le_bytes(data[74..82].try_into().map_err(|_| ErrorCode::InvalidPosition)?))
+        };
+        // Legacy claims closed the position, so a live one is never paid out.
+        require!(!claimed, ErrorCode::AlreadyClaimed);
+
+        let p = &mut ctx.accounts.position;
+        p.owner = ctx.accounts.owner.key();
+        p.market = ctx.accounts.market.key();
+        p.outcome = outcome;
+        p.amount = amount;
+        p.index = index;
+
+        let rent = legacy.lamports();
+        legacy.sub_lamports(rent)?;
+        ctx.accounts.owner.add_lamports(rent)?;
+        legacy.assign(&system_program::ID);
+        legacy.resize(0)?;
+
+        emit!(LegacyPositionMigrated {
+            market: p.market,
+            owner: p.owner,
+            legacy_position: legacy.key(),
+            position: p.key(),
+            index,
+        });
+
+        Ok(())
+    }
+
+    /// Grows a position from before its insurance and reward fields; they start at zero.
+    pub fn migrate_position(ctx: Context<MigratePosition>, _index: u16) -> Result<()> {
+        let info = ctx.accounts.position.to_account_info();
+        require!(info.owner == ctx.program_id, ErrorCode::Unauthorized);
+        require!(info.try_borrow_data()?.starts_with(Position::DISCRIMINATOR), ErrorCode::InvalidPosition);
+        let old_len = info.data_len();
+        if old_len >= Position::LEN {
+            return Ok(());
+        }
+
+        let rent = Rent::get()?.minimum_balance(Position::LEN);
+        let top_up = rent.saturating_sub(info.lamports());
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: info.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        info.resize(Position::LEN)?;
+
+        emit!(PositionMigrated {
+            position: info.key(),
+            old_len: old_len as u32,
+            new_len: Position::LEN as u32,
+        });
+
+        Ok(())
+    }
+
+    pub fn update_cutoff(ctx: Context<UpdateCutoff>, new_cutoff_ts: i64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(ctx.accounts.config.timelock_secs == 0, ErrorCode::TimelockRequired);
+
+        let now = Clock::get()?.unix_timestamp;
+        apply_cutoff(&mut ctx.accounts.market, new_cutoff_ts, ctx.accounts.config.min_duration, now)
+    }
+
+    /// `min_odds_bps` aborts the bet if its post-bet payout multiple is lower (20_000 = 2x).
+    #[allow(clippy::too_many_arguments)]
+    pub fn place_bet<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PlaceBet<'info>>,
+        outcome: Outcome,
+        amount: u64,
+        position_index: u16,
+        tag: Option<String>,
+        min_odds_bps: Option<u32>,
+        max_fee_bps: Option<u16>,
+        insure: bool,
+    ) -> Result<()> {
         require!(amount >= MIN_BET_AMOUNT, ErrorCode::BetTooSmall);
+        let tag = tag.unwrap_or_default();
+        require!(tag.len() <= MAX_BET_TAG_LEN, ErrorCode::MetadataTooLong);
 
         let now = Clock::get()?.unix_timestamp;
         let m = &mut ctx.accounts.market;
 
         validate_market_active(m)?;
+        require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+        validate_bet_caller(m)?;
         require!(!m.emergency_paused, ErrorCode::EmergencyPaused);
+        require!(!m.bracket_locked, ErrorCode::BracketMatchLocked);
+        require!(now >= m.open_ts, ErrorCode::BettingNotOpen);
         require!(now < m.cutoff_ts, ErrorCode::BettingClosed);
-        require_keys_eq!(m.bet_mint, ctx.accounts.bet_mint.key(), ErrorCode::WrongMint);
-
-        let fee = calculate_fee(amount)?;
-        let net = amount.checked_sub(fee).ok_or(ErrorCode::Overflow)?;
 
-        validate_bet_limit(&ctx.accounts.bet_mint, &ctx.accounts.position, amount)?;
+        require_attestation(m, ctx.accounts.attestation.as_ref(), ctx.accounts.bettor.key(), now)?;
+        validate_position_cap(
+            m,
+            &ctx.accounts.bet_mint,
+            &ctx.accounts.position,
+            amount,
+            ctx.accounts.price_feed.as_ref(),
+            now,
+        )?;
 
-        if fee > 0 {
-            token::transfer(
-                CpiContext::new(
-                    ctx.accounts.token_program.to_account_info(),
-                    Transfer {
-                        from: ctx.accounts.bettor_ata.to_account_info(),
-                        to: ctx.accounts.fee_receiver_ata.to_account_info(),
-                        authority: ctx.accounts.bettor.to_account_info(),
-                    },
-                ),
-                fee,
-            )?;
+        let extras = bet_extras(ctx.remaining_accounts)?;
+        let fee_discount_bps = match extras.holder_discount {
+            Some([discount, nft_token, nft_metadata]) => {
+                let discount: Account<FeeDiscount> = Account::try_from(discount)?;
+                let nft_token: InterfaceAccount<TokenAccount> = InterfaceAccount::try_from(nft_token)?;
+                holder_discount_bps(&discount, &nft_token, nft_metadata, ctx.accounts.bettor.key())?
+            }
+            _ => 0,
+        };
+        // The larger of the holder and season-pass discounts applies.
+        let fee_discount_bps = match extras.season_pass {
+            Some([config, pass]) => {
+                let config: Account<SeasonPassConfig> = Account::try_from(config)?;
+                let pass: Account<SeasonPass> = Account::try_from(pass)?;
+                fee_discount_bps.max(season_pass_discount_bps(&config, &pass, ctx.accounts.bettor.key(), now)?)
+            }
+            _ => fee_discount_bps,
+        };
+        if let Some(max_fee_bps) = max_fee_bps {
+            require!(effective_fee_bps(m, fee_discount_bps) <= max_fee_bps as u64, ErrorCode::FeeTooHigh);
         }
 
-        token::transfer(
+        // The whole stake goes to the vault; the fee is carved out of whatever
+        // actually arrived so transfer-fee / deflationary mints stay solvent.
+        let vault_before = ctx.accounts.vault.amount;
+        token_interface::transfer_checked(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
-                Transfer {
+                TransferChecked {
                     from: ctx.accounts.bettor_ata.to_account_info(),
+                    mint: ctx.accounts.bet_mint.to_account_info(),
                     to: ctx.accounts.vault.to_account_info(),
                     authority: ctx.accounts.bettor.to_account_info(),
                 },
             ),
-            net,
+            amount,
+            ctx.accounts.bet_mint.decimals,
         )?;
+        ctx.accounts.vault.reload()?;
+        let received = ctx.accounts.vault.amount
+            .checked_sub(vault_before)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(received > 0, ErrorCode::InvalidAmount);
 
-        let stats = &mut ctx.accounts.program_stats;
-        stats.total_volume = stats.total_volume.checked_add(amount).ok_or(ErrorCode::Overflow)?;
-        stats.total_fees = stats.total_fees.checked_add(fee).ok_or(ErrorCode::Overflow)?;
-        if ctx.accounts.position.amount == 0 {
-            stats.total_bettors = stats.total_bettors.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        let (fee, net) = record_bet(
+            m,
+            &mut ctx.accounts.position,
+            &mut ctx.accounts.program_stats,
+            &mut ctx.accounts.user_stats,
+            ctx.accounts.portfolio.as_deref_mut(),
+            ctx.accounts.bettor.key(),
+            ctx.bumps.user_stats,
+            outcome,
+            received,
+            fee_discount_bps,
+            position_index,
+            now,
+            house_leg(
+                ctx.accounts.house.as_mut(),
+                ctx.accounts.house_vault.as_ref(),
+                &ctx.accounts.vault,
+                &ctx.accounts.bet_mint,
+                &ctx.accounts.token_program,
+            ),
+        )?;
+        if insure {
+            // The premium is pooled in the insurance fund, which pays the cover.
+            let (insurance, insurance_vault) = ctx.accounts.insurance_fund.as_mut()
+                .zip(ctx.accounts.insurance_vault.as_mut())
+                .ok_or(ErrorCode::InsuranceAccountsMissing)?;
+            let premium = net
+                .checked_mul(INSURANCE_PREMIUM_BPS)
+                .ok_or(ErrorCode::Overflow)?
+                / BPS_DENOM;
+            require!(premium > 0, ErrorCode::InvalidAmount);
+            let insurance_before = insurance_vault.amount;
+            token_interface::transfer_checked(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.bettor_ata.to_account_info(),
+                        mint: ctx.accounts.bet_mint.to_account_info(),
+                        to: insurance_vault.to_account_info(),
+                        authority: ctx.accounts.bettor.to_account_info(),
+                    },
+                ),
+                premium,
+                ctx.accounts.bet_mint.decimals,
+            )?;
+            insurance_vault.reload()?;
+            // Transfer-fee mints deliver less; only what arrived is pooled.
+            let premium = insurance_vault.amount
+                .checked_sub(insurance_before)
+                .ok_or(ErrorCode::Overflow)?;
+            require!(premium > 0, ErrorCode::InvalidAmount);
+            insurance.total_contributed = insurance.total_contributed.checked_add(premium).ok_or(ErrorCode::Overflow)?;
+            m.premium_pool = m.premium_pool.checked_add(premium).ok_or(ErrorCode::Overflow)?;
+            let p = &mut ctx.accounts.position;
+            p.insured = p.insured.checked_add(net).ok_or(ErrorCode::Overflow)?;
+
+            emit!(BetInsured {
+                market: m.key(),
+                bettor: ctx.accounts.bettor.key(),
+                insured: net,
+                premium,
+            });
+        }
+        if let Some(min_odds_bps) = min_odds_bps {
+            require!(implied_odds_bps(m, outcome)? >= min_odds_bps as u64, ErrorCode::OddsSlippage);
         }
 
-        let p = &mut ctx.accounts.position;
-        if p.amount == 0 {
-            p.owner = ctx.accounts.bettor.key();
-            p.market = m.key();
-            p.outcome = outcome as u8;
-            p.claimed = false;
-            p.amount = net;
-        } else {
-            require!(p.outcome == outcome as u8, ErrorCode::CannotSwitchSide);
-            p.amount = p.amount.checked_add(net).ok_or(ErrorCode::Overflow)?;
+        if let Some([rc, reward_mint, reward_auth, reward_ata, reward_token_program]) = extras.rewards {
+            let rc: Account<RewardConfig> = Account::try_from(rc)?;
+            // The reward mint's token program need not match the bet mint's.
+            let reward_token_program: Interface<TokenInterface> = Interface::try_from(reward_token_program)?;
+            require_keys_eq!(reward_mint.key(), rc.reward_mint, ErrorCode::WrongMint);
+            require_keys_eq!(*reward_mint.owner, reward_token_program.key(), ErrorCode::WrongMint);
+            let bettor_reward_ata: InterfaceAccount<TokenAccount> = InterfaceAccount::try_from(reward_ata)?;
+            require_keys_eq!(bettor_reward_ata.mint, rc.reward_mint, ErrorCode::WrongMint);
+            require_keys_eq!(bettor_reward_ata.owner, ctx.accounts.bettor.key(), ErrorCode::Unauthorized);
+            let mint_authority = Pubkey::create_program_address(&[REWARD_MINT_AUTH_SEED, &[rc.mint_authority_bump]], &crate::ID)
+                .map_err(|_| error!(ErrorCode::InvalidBetExtras))?;
+            require_keys_eq!(reward_auth.key(), mint_authority, ErrorCode::InvalidBetExtras);
+            let reward = calculate_reward(received, rc.emission_per_thousand)?;
+            if rc.enabled && reward > 0 {
+                let seeds: &[&[u8]] = &[REWARD_MINT_AUTH_SEED, &[rc.mint_authority_bump]];
+                let signer: &[&[&[u8]]] = &[seeds];
+                token_interface::mint_to(
+                    CpiContext::new_with_signer(
+                        reward_token_program.to_account_info(),
+                        MintTo {
+                            mint: reward_mint.clone(),
+                            to: reward_ata.clone(),
+                            authority: reward_auth.clone(),
+                        },
+                        signer,
+                    ),
+                    reward,
+                )?;
+
+                emit!(RewardsEmitted {
+                    market: m.key(),
+                    bettor: ctx.accounts.bettor.key(),
+                    wagered: received,
+                    reward,
+                });
+            }
         }
 
-        match outcome {
-            Outcome::Yes => m.total_yes = m.total_yes.checked_add(net).ok_or(ErrorCode::Overflow)?,
-            Outcome::No => m.total_no = m.total_no.checked_add(net).ok_or(ErrorCode::Overflow)?,
-            _ => return err!(ErrorCode::InvalidOutcomeArg),
+        let market_key = m.key();
+        if let Some(receipt) = extras.receipt {
+            mint_bet_receipt(ctx.accounts, receipt, market_key, outcome, net)?;
         }
 
-        emit!(BetPlaced {
-            market: m.key(),
+        emit_cpi!(BetPlaced {
+            market: market_key,
             bettor: ctx.accounts.bettor.key(),
             outcome,
             amount,
+            received,
             fee,
             net_amount: net,
+            tag,
         });
         
         Ok(())
@@ -198,78 +588,126 @@ pub mod yesno_bets {
     pub fn resolve_market(
         ctx: Context<ResolveMarket>, 
         winning_outcome: Outcome,
-        resolution_reason: ResolutionReason
+        resolution_reason: ResolutionReason,
+        evidence_hash: [u8; 32],
+        evidence_uri: String,
     ) -> Result<()> {
-        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(evidence_uri.len() <= MAX_EVIDENCE_URI_LEN, ErrorCode::MetadataTooLong);
 
         let m = &mut ctx.accounts.market;
+        // The admin, or the market's delegated resolver (which has no other powers).
+        let signer = ctx.accounts.owner.key();
+        require!(
+            signer == owner_pubkey()
+                || (m.resolve_authority != Pubkey::default() && signer == m.resolve_authority)
+                || is_operator_admin(m, ctx.accounts.operator.as_ref(), signer),
+            ErrorCode::Unauthorized
+        );
         require!(!m.resolved, ErrorCode::AlreadyResolved);
+        require!(m.vote_end_ts == 0, ErrorCode::VoteInProgress);
+        require!(m.proposed_outcome == Outcome::Unset as u8, ErrorCode::ProposalPending);
+        require!(m.round_count == 0, ErrorCode::StagedMarket);
 
         let now = Clock::get()?.unix_timestamp;
-        require!(now >= m.cutoff_ts, ErrorCode::TooEarly);
+        require!(now >= m.cutoff_ts && now >= m.event_ts, ErrorCode::TooEarly);
 
-        let auto_void = m.total_yes == 0 || m.total_no == 0;
+        if parent_condition_failed(m, ctx.accounts.parent_market.as_ref())? {
+            return void_market_state(m, &mut ctx.accounts.program_stats, ResolutionReason::ConditionFailed, now);
+        }
 
-        m.resolved = true;
-        m.winning_outcome = if auto_void {
-            Outcome::Void as u8
-        } else {
-            match winning_outcome {
-                Outcome::Yes | Outcome::No => winning_outcome as u8,
-                _ => return err!(ErrorCode::InvalidOutcomeArg),
-            }
-        };
-        m.resolution_reason = if auto_void {
-            ResolutionReason::AutoVoid as u8
-        } else {
-            resolution_reason as u8
-        };
+        m.evidence_hash = evidence_hash;
+        m.evidence_uri = evidence_uri;
+
+        let resolved = settle_market(m, &mut ctx.accounts.program_stats, winning_outcome, resolution_reason, &ctx.accounts.instructions)?;
+        emit_cpi!(resolved);
 
-        emit!(MarketResolved {
-            market: m.key(),
-            winning_outcome: m.winning_outcome,
-            auto_void,
-            total_yes: m.total_yes,
-            total_no: m.total_no,
-            fees_accrued: 0,
-            resolution_reason: m.resolution_reason,
-        });
-        
         Ok(())
     }
 
-    pub fn claim_winnings(ctx: Context<ClaimWinnings>) -> Result<()> {
+    /// Resolves a market partially: Yes holders share `yes_bps` of the pool, No holders the rest.
+    pub fn resolve_split(
+        ctx: Context<ResolveMarket>,
+        yes_bps: u16,
+        evidence_hash: [u8; 32],
+        evidence_uri: String,
+    ) -> Result<()> {
+        require!(evidence_uri.len() <= MAX_EVIDENCE_URI_LEN, ErrorCode::MetadataTooLong);
+        require!(yes_bps > 0 && (yes_bps as u64) < BPS_DENOM, ErrorCode::InvalidBps);
+
         let m = &mut ctx.accounts.market;
-        let p = &mut ctx.accounts.position;
+        let signer = ctx.accounts.owner.key();
+        require!(
+            signer == owner_pubkey()
+                || (m.resolve_authority != Pubkey::default() && signer == m.resolve_authority)
+                || is_operator_admin(m, ctx.accounts.operator.as_ref(), signer),
+            ErrorCode::Unauthorized
+        );
+        require!(!m.resolved, ErrorCode::AlreadyResolved);
+        require!(!m.three_way && !m.multi_mint, ErrorCode::InvalidOutcomeArg);
+        require!(m.vote_end_ts == 0, ErrorCode::VoteInProgress);
+        require!(m.proposed_outcome == Outcome::Unset as u8, ErrorCode::ProposalPending);
+        require!(m.round_count == 0, ErrorCode::StagedMarket);
 
-        require!(m.resolved, ErrorCode::NotResolved);
-        require!(!p.claimed, ErrorCode::AlreadyClaimed);
-        require_keys_eq!(p.market, m.key(), ErrorCode::WrongMarket);
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= m.cutoff_ts && now >= m.event_ts, ErrorCode::TooEarly);
+
+        if parent_condition_failed(m, ctx.accounts.parent_market.as_ref())? {
+            return void_market_state(m, &mut ctx.accounts.program_stats, ResolutionReason::ConditionFailed, now);
+        }
+
+        m.evidence_hash = evidence_hash;
+        m.evidence_uri = evidence_uri;
+        m.split_yes_bps = yes_bps;
+
+        let resolved = settle_market(m, &mut ctx.accounts.program_stats, Outcome::Split, ResolutionReason::Split, &ctx.accounts.instructions)?;
+        emit_cpi!(resolved);
+
+        Ok(())
+    }
+
+    /// Pays out up to `amount` of what is currently claimable, or all of it when `None`.
+    pub fn claim_winnings(ctx: Context<ClaimWinnings>, amount: Option<u64>) -> Result<()> {
+        let m = &mut ctx.accounts.market;
+        let p = &mut ctx.accounts.position;
         require_keys_eq!(ctx.accounts.bet_mint.key(), m.bet_mint, ErrorCode::WrongMint);
 
-        let payout = calculate_payout(m, p)?;
+        let now = Clock::get()?.unix_timestamp;
+        let (payout, voided, first_claim) = take_claimable(m, p, ctx.accounts.vault.amount, amount, now)?;
+        accrue_rewards(m, now)?;
+        (p.reward_debt, p.rewards_owed) = settle_rewards(m.reward_per_stake, p.amount, p.reward_debt, p.rewards_owed)?;
 
         let market_key = m.key();
         let bump: u8 = ctx.bumps.vault_authority;
         let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, market_key.as_ref(), &[bump]];
         let signer: &[&[&[u8]]] = &[seeds];
 
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
-                Transfer {
+                TransferChecked {
                     from: ctx.accounts.vault.to_account_info(),
+                    mint: ctx.accounts.bet_mint.to_account_info(),
                     to: ctx.accounts.bettor_ata.to_account_info(),
                     authority: ctx.accounts.vault_authority.to_account_info(),
                 },
                 signer,
             ),
             payout,
+            ctx.accounts.bet_mint.decimals,
         )?;
 
-        p.claimed = true;
+        // Created on first use as in `place_bet`, so a missing stats account
+        // never blocks a payout.
+        let us = &mut ctx.accounts.user_stats;
+        if us.owner == Pubkey::default() {
+            us.owner = ctx.accounts.bettor.key();
+            us.bump = ctx.bumps.user_stats;
+        }
+        if !voided {
+            record_win(us, payout, first_claim)?;
+        }
 
-        emit!(WinningsClaimed {
+        emit_cpi!(WinningsClaimed {
             market: m.key(),
             bettor: ctx.accounts.bettor.key(),
             payout,
@@ -277,10 +715,179 @@ pub mod yesno_bets {
             outcome: p.outcome,
             winning_outcome: m.winning_outcome,
         });
+        emit_cpi!(pnl_realized(m.key(), ctx.accounts.bettor.key(), p, payout)?);
+
+        // Unclaimed liquidity-mining rewards keep the position open.
+        if ctx.accounts.position.claimed && position_closable(&ctx.accounts.market, &ctx.accounts.position, now)? {
+            if let Some(pf) = ctx.accounts.portfolio.as_mut() {
+                pf.untrack(market_key);
+            }
+            ctx.accounts.position.close(ctx.accounts.bettor.to_account_info())?;
+        }
         
         Ok(())
     }
 
+    /// Operator admins may sweep their own markets; the platform cut comes out first.
+    pub fn sweep_fees(ctx: Context<SweepFees>) -> Result<()> {
+        let caller = ctx.accounts.owner.key();
+        require!(
+            caller == owner_pubkey()
+                || is_operator_admin(&ctx.accounts.market, ctx.accounts.operator.as_ref(), caller),
+            ErrorCode::Unauthorized
+        );
+
+        let m = &mut ctx.accounts.market;
+        require!(m.resolved, ErrorCode::NotResolved);
+        let total = m.fees_accrued;
+        require!(total > 0, ErrorCode::NoFees);
+
+        let insurance = &mut ctx.accounts.insurance_fund;
+        let cut = calculate_insurance_cut(total, insurance.fee_share_bps)?;
+        let jackpot = existing_jackpot(&ctx.accounts.jackpot)?;
+        let jackpot_cut = match jackpot.as_ref() {
+            Some(j) => calculate_insurance_cut(total, j.fee_share_bps)?,
+            None => 0,
+        };
+        let amount = total
+            .checked_sub(cut)
+            .and_then(|v| v.checked_sub(jackpot_cut))
+            .ok_or(ErrorCode::Overflow)?;
+
+        let market_key = m.key();
+        let bump: u8 = ctx.bumps.vault_authority;
+        let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, market_key.as_ref(), &[bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+
+        if jackpot_cut > 0 {
+            let jackpot_vault = ctx.accounts.jackpot_vault.as_ref().ok_or(ErrorCode::JackpotAccountsMissing)?;
+            let expected_vault = jackpot.as_ref().map(|j| j.vault).ok_or(ErrorCode::JackpotAccountsMissing)?;
+            require_keys_eq!(jackpot_vault.key(), expected_vault, ErrorCode::JackpotAccountsMissing);
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.vault.to_account_info(),
+                        mint: ctx.accounts.bet_mint.to_account_info(),
+                        to: jackpot_vault.to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    signer,
+                ),
+                jackpot_cut,
+                ctx.accounts.bet_mint.decimals,
+            )?;
+
+            emit!(JackpotAccrued {
+                market: market_key,
+                mint: m.bet_mint,
+                amount: jackpot_cut,
+            });
+        }
+
+        if cut > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.vault.to_account_info(),
+                        mint: ctx.accounts.bet_mint.to_account_info(),
+                        to: ctx.accounts.insurance_vault.to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    signer,
+                ),
+                cut,
+                ctx.accounts.bet_mint.decimals,
+            )?;
+
+            insurance.total_contributed = insurance.total_contributed.checked_add(cut).ok_or(ErrorCode::Overflow)?;
+            m.insurance_contributed = m.insurance_contributed.checked_add(cut).ok_or(ErrorCode::Overflow)?;
+
+            emit!(InsuranceContributed {
+                market: market_key,
+                mint: m.bet_mint,
+                amount: cut,
+            });
+        }
+
+        let platform_cut = match ctx.accounts.operator.as_ref().filter(|_| m.operator != Pubkey::default()) {
+            Some(op) => {
+                require_keys_eq!(op.key(), m.operator, ErrorCode::WrongOperator);
+                calculate_insurance_cut(amount, op.platform_cut_bps)?
+            }
+            None => {
+                require!(m.operator == Pubkey::default(), ErrorCode::WrongOperator);
+                0
+            }
+        };
+        if platform_cut > 0 {
+            let platform_ata = ctx.accounts.platform_ata.as_ref().ok_or(ErrorCode::WrongOperator)?;
+            require_keys_eq!(platform_ata.owner, fee_wallet_pubkey(), ErrorCode::InvalidFeeReceiver);
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.vault.to_account_info(),
+                        mint: ctx.accounts.bet_mint.to_account_info(),
+                        to: platform_ata.to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    signer,
+                ),
+                platform_cut,
+                ctx.accounts.bet_mint.decimals,
+            )?;
+
+            emit!(PlatformFeeTaken {
+                market: market_key,
+                operator: m.operator,
+                amount: platform_cut,
+            });
+        }
+        let amount = amount.checked_sub(platform_cut).ok_or(ErrorCode::Overflow)?;
+
+        // Partner markets sweep to their stored token account instead of the
+        // fee receiver's ATA.
+        let (destination, recipient) = if m.fee_destination != Pubkey::default() {
+            let dest = ctx.accounts.fee_destination.as_ref().ok_or(ErrorCode::InvalidFeeReceiver)?;
+            require_keys_eq!(dest.key(), m.fee_destination, ErrorCode::InvalidFeeReceiver);
+            (dest.to_account_info(), m.fee_destination)
+        } else {
+            (ctx.accounts.fee_receiver_ata.to_account_info(), m.fee_receiver)
+        };
+
+        if amount > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.vault.to_account_info(),
+                        mint: ctx.accounts.bet_mint.to_account_info(),
+                        to: destination,
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    signer,
+                ),
+                amount,
+                ctx.accounts.bet_mint.decimals,
+            )?;
+        }
+
+        m.fees_accrued = 0;
+
+        let stats = &mut ctx.accounts.program_stats;
+        stats.fees_swept = stats.fees_swept.checked_add(total).ok_or(ErrorCode::Overflow)?;
+
+        emit!(FeesCollected {
+            market: market_key,
+            amount,
+            recipient,
+        });
+
+        Ok(())
+    }
+
     pub fn emergency_pause(ctx: Context<EmergencyPause>, pause: bool) -> Result<()> {
         require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
         let m = &mut ctx.accounts.market;
@@ -296,17 +903,9 @@ pub mod yesno_bets {
 
     pub fn update_fee_receiver(ctx: Context<UpdateFeeReceiver>, new_receiver: Pubkey) -> Result<()> {
         require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
-        let m = &mut ctx.accounts.market;
-        
-        require!(new_receiver != Pubkey::default(), ErrorCode::InvalidFeeReceiver);
-        m.fee_receiver = new_receiver;
+        require!(ctx.accounts.config.timelock_secs == 0, ErrorCode::TimelockRequired);
 
-        emit!(FeeReceiverUpdated {
-            market: m.key(),
-            new_receiver,
-        });
-        
-        Ok(())
+        apply_fee_receiver(&mut ctx.accounts.market, new_receiver)
     }
 
     pub fn get_potential_payout(ctx: Context<GetPotentialPayout>, outcome: Outcome) -> Result<u64> {
@@ -320,434 +919,11221 @@ pub mod yesno_bets {
             return Ok(0);
         }
 
-        let total_yes = market.total_yes as u128;
-        let total_no = market.total_no as u128;
-        let total_pool = total_yes.checked_add(total_no).ok_or(ErrorCode::Overflow)?;
+        let bonus = calculate_winner_bonus(market.fees_accrued, market.winner_rake_bps)? as u128;
+        let subsidy = losing_subsidy(market, outcome as u8) as u128;
+        let total_pool = (total_staked(market)? as u128)
+            .checked_add(bonus)
+            .and_then(|v| v.checked_add(subsidy))
+            .ok_or(ErrorCode::Overflow)?;
         
-        let outcome_pool = match outcome {
-            Outcome::Yes => total_yes,
-            Outcome::No => total_no,
-            _ => return Ok(0),
-        };
+        let outcome_pool = outcome_pool(market, outcome as u8) as u128;
 
         if outcome_pool == 0 {
             return Ok(0);
         }
 
-        let user_amt = position.amount as u128;
-        let payout_u128 = total_pool
-            .checked_mul(user_amt)
-            .ok_or(ErrorCode::Overflow)?
-            .checked_div(outcome_pool)
-            .ok_or(ErrorCode::Overflow)?;
-            
-        u64::try_from(payout_u128).map_err(|_| ErrorCode::Overflow)
+        let payout = sillymarket_math::pro_rata_payout(total_pool, outcome_pool, position.amount)
+            .ok_or(error!(ErrorCode::Overflow))?;
+
+        Ok(apply_odds_cap(payout, position.amount, market.max_multiplier_bps)?.0)
     }
-}
 
-#[account]
-pub struct Market {
-    pub creator: Pubkey,
-    pub bet_mint: Pubkey,
-    pub vault: Pubkey,
-    pub vault_authority: Pubkey,
-    pub cutoff_ts: i64,
-    pub resolved: bool,
-    pub winning_outcome: u8,
-    pub total_yes: u64,
-    pub total_no: u64,
-    pub fees_accrued: u64,
-    pub emergency_paused: bool,
-    pub fee_receiver: Pubkey,
-    pub created_at: i64,
-    pub resolution_reason: u8,
-}
-impl Market {
-    pub const LEN: usize = 8
-        + 32 + 32 + 32 + 32
-        + 8 + 1 + 1 + 8 + 8 + 8
-        + 1 + 32 + 8 + 1;
-}
+    pub fn create_oracle_feed(ctx: Context<CreateOracleFeed>, reporter: Pubkey, expo: i32) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(reporter != Pubkey::default(), ErrorCode::InvalidOracleReporter);
 
-#[account]
-pub struct Position {
-    pub owner: Pubkey,
-    pub market: Pubkey,
-    pub outcome: u8,
-    pub claimed: bool,
-    pub amount: u64,
-}
-impl Position {
-    pub const LEN: usize = 8 + 32 + 32 + 1 + 1 + 8;
-}
+        let feed = &mut ctx.accounts.feed;
+        feed.reporter = reporter;
+        feed.value = 0;
+        feed.expo = expo;
+        feed.updated_ts = 0;
 
-#[account]
-pub struct MarketMetadata {
-    pub market: Pubkey,
-    pub question: String,
-    pub category: String,
-    pub created_at: i64,
-}
-impl MarketMetadata {
-    pub const LEN: usize = 8 + 32 + (4 + 280) + (4 + 50) + 8;
+        emit!(OracleFeedCreated {
+            feed: feed.key(),
+            reporter,
+            expo,
+        });
+
+        Ok(())
+    }
+
+    pub fn update_oracle_feed(ctx: Context<UpdateOracleFeed>, value: i64) -> Result<()> {
+        let feed = &mut ctx.accounts.feed;
+        require_keys_eq!(ctx.accounts.reporter.key(), feed.reporter, ErrorCode::Unauthorized);
+
+        let now = Clock::get()?.unix_timestamp;
+        feed.value = value;
+        feed.updated_ts = now;
+
+        emit!(OracleFeedUpdated {
+            feed: feed.key(),
+            value,
+            expo: feed.expo,
+            updated_ts: now,
+        });
+
+        Ok(())
+    }
+
+    pub fn add_market_mint(ctx: Context<AddMarketMint>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(m.total_yes == 0 && m.total_no == 0, ErrorCode::MarketHasBets);
+        require!(m.mint_count < MAX_MARKET_MINTS, ErrorCode::TooManyMints);
+
+        let mm = &mut ctx.accounts.market_mint;
+        mm.market = m.key();
+        mm.mint = ctx.accounts.mint.key();
+        mm.vault = ctx.accounts.vault.key();
+        mm.price_feed = ctx.accounts.price_feed.key();
+        mm.decimals = ctx.accounts.mint.decimals;
+        mm.index = m.mint_count;
+        mm.total_yes = 0;
+        mm.total_no = 0;
+        mm.fees_accrued = 0;
+        mm.bump = ctx.bumps.market_mint;
+        mm.units_yes = 0;
+        mm.units_no = 0;
+
+        m.multi_mint = true;
+        m.mint_count = m.mint_count.checked_add(1).ok_or(ErrorCode::Overflow)?;
+
+        emit!(MarketMintAdded {
+            market: m.key(),
+            mint: mm.mint,
+            vault: mm.vault,
+            price_feed: mm.price_feed,
+            index: mm.index,
+        });
+
+        Ok(())
+    }
+
+    pub fn place_bet_multi(ctx: Context<PlaceBetMulti>, outcome: Outcome, amount: u64) -> Result<()> {
+        require!(amount >= MIN_BET_AMOUNT, ErrorCode::BetTooSmall);
+
+        let now = Clock::get()?.unix_timestamp;
+        let m = &mut ctx.accounts.market;
+
+        validate_market_active(m)?;
+        require!(m.multi_mint, ErrorCode::NotMultiMintMarket);
+        validate_bet_caller(m)?;
+        require!(m.attestation_issuer == Pubkey::default(), ErrorCode::AttestationRequired);
+        require!(!m.emergency_paused, ErrorCode::EmergencyPaused);
+        require!(!m.bracket_locked, ErrorCode::BracketMatchLocked);
+        require!(now >= m.open_ts, ErrorCode::BettingNotOpen);
+        require!(now < m.cutoff_ts, ErrorCode::BettingClosed);
+        require!(matches!(outcome, Outcome::Yes | Outcome::No), ErrorCode::InvalidOutcomeArg);
+
+        validate_mint_bet_limit(&ctx.accounts.mint, ctx.accounts.position.deposited, amount)?;
+        let (price, expo) = read_oracle_price(&ctx.accounts.price_feed, now)?;
+
+        let vault_before = ctx.accounts.vault.amount;
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.bettor_ata.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.bettor.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+        ctx.accounts.vault.reload()?;
+        let received = ctx.accounts.vault.amount
+            .checked_sub(vault_before)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(received > 0, ErrorCode::InvalidAmount);
+
+        // Market pools are in accounting units, so the cap is too.
+        let received_units = to_accounting_units(received, ctx.accounts.mint.decimals, price, expo)?;
+        validate_pool_share(m, outcome, received_units)?;
+
+        let fee = calculate_fee(m, received)?;
+        let net = received.checked_sub(fee).ok_or(ErrorCode::Overflow)?;
+        let units = to_accounting_units(net, ctx.accounts.mint.decimals, price, expo)?;
+        require!(units > 0, ErrorCode::BetTooSmall);
+
+        let mm = &mut ctx.accounts.market_mint;
+        mm.fees_accrued = mm.fees_accrued.checked_add(fee).ok_or(ErrorCode::Overflow)?;
+
+        let stats = &mut ctx.accounts.program_stats;
+        if ctx.accounts.position.deposited == 0 {
+            stats.total_bettors = stats.total_bettors.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        }
+
+        let p = &mut ctx.accounts.position;
+        if p.deposited == 0 {
+            if let Some(pf) = ctx.accounts.portfolio.as_mut() {
+                pf.track(m.key())?;
+            }
+            p.owner = ctx.accounts.bettor.key();
+            p.market = m.key();
+            p.mint = mm.mint;
+            p.outcome = outcome as u8;
+            p.claimed_mask = 0;
+            p.deposited = net;
+            p.units = units;
+        } else {
+            require!(p.outcome == outcome as u8, ErrorCode::CannotSwitchSide);
+            p.deposited = p.deposited.checked_add(net).ok_or(ErrorCode::Overflow)?;
+            p.units = p.units.checked_add(units).ok_or(ErrorCode::Overflow)?;
+        }
+
+        accumulate_twap(m, now)?;
+        match outcome {
+            Outcome::Yes => {
+                m.total_yes = m.total_yes.checked_add(units).ok_or(ErrorCode::Overflow)?;
+                mm.total_yes = mm.total_yes.checked_add(net).ok_or(ErrorCode::Overflow)?;
+                mm.units_yes = mm.units_yes.checked_add(units).ok_or(ErrorCode::Overflow)?;
+            }
+            _ => {
+                m.total_no = m.total_no.checked_add(units).ok_or(ErrorCode::Overflow)?;
+                mm.total_no = mm.total_no.checked_add(net).ok_or(ErrorCode::Overflow)?;
+                mm.units_no = mm.units_no.checked_add(units).ok_or(ErrorCode::Overflow)?;
+            }
+        }
+        m.twap_last_bps = implied_yes_bps(m)?;
+
+        emit!(MultiMintBetPlaced {
+            market: m.key(),
+            bettor: ctx.accounts.bettor.key(),
+            mint: mm.mint,
+            outcome,
+            amount,
+            received,
+            fee,
+            net_amount: net,
+            units,
+            price,
+            expo,
+        });
+
+        Ok(())
+    }
+
+    /// Pays a position in the mint it deposited; voided positions get their deposit back.
+    pub fn claim_multi_mint<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimMultiMint<'info>>) -> Result<()> {
+        let m = &ctx.accounts.market;
+        let mm = &ctx.accounts.payout_market_mint;
+        let p = &mut ctx.accounts.position;
+
+        require!(m.resolved, ErrorCode::NotResolved);
+        require!(m.multi_mint, ErrorCode::NotMultiMintMarket);
+        require_keys_eq!(p.market, m.key(), ErrorCode::WrongMarket);
+        require!(Clock::get()?.unix_timestamp <= m.claim_deadline_ts, ErrorCode::ClaimExpired);
+
+        let bit = 1u8 << mm.index;
+        require!(p.claimed_mask & bit == 0, ErrorCode::AlreadyClaimed);
+
+        let payout = if m.winning_outcome == Outcome::Void as u8 {
+            require_keys_eq!(mm.mint, p.mint, ErrorCode::WrongMint);
+            p.claimed_mask = full_mint_mask(m.mint_count);
+            p.deposited
+        } else {
+            require!(p.outcome == m.winning_outcome, ErrorCode::NoPayout);
+            let winning_units = if m.winning_outcome == Outcome::Yes as u8 {
+                m.total_yes
+            } else {
+                m.total_no
+            };
+            require!(winning_units > 0, ErrorCode::NoPayout);
+            let total_units = m.total_yes.checked_add(m.total_no).ok_or(ErrorCode::Overflow)?;
+            let owed = sillymarket_math::pro_rata_payout(total_units as u128, winning_units as u128, p.units)
+                .ok_or(error!(ErrorCode::Overflow))?;
+
+            let units = if mm.mint == p.mint {
+                let (own, rest) = split_mint_claim(m, mm, owed)?;
+                p.claimed_mask = if rest == 0 { full_mint_mask(m.mint_count) } else { p.claimed_mask | bit };
+                own
+            } else {
+                let mints = load_market_mints(m, ctx.remaining_accounts)?;
+                let deposit_mm = mints.iter().find(|x| x.mint == p.mint).ok_or(ErrorCode::WrongMint)?;
+                let (_, rest) = split_mint_claim(m, deposit_mm, owed)?;
+                let mut surplus_total = 0u64;
+                for x in mints.iter() {
+                    surplus_total = surplus_total.checked_add(mint_surplus(m, x)?).ok_or(ErrorCode::Overflow)?;
+                }
+                p.claimed_mask |= bit;
+                if rest == 0 || surplus_total == 0 {
+                    0
+                } else {
+                    sillymarket_math::pro_rata_payout(mint_surplus(m, mm)? as u128, surplus_total as u128, rest)
+                        .ok_or(error!(ErrorCode::Overflow))?
+                }
+            };
+            units_to_mint_amount(mm, units)?
+        };
+
+        if payout > 0 {
+            let market_key = m.key();
+            let bump: u8 = ctx.bumps.vault_authority;
+            let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, market_key.as_ref(), &[bump]];
+            let signer: &[&[&[u8]]] = &[seeds];
+
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.vault.to_account_info(),
+                        mint: ctx.accounts.payout_mint.to_account_info(),
+                        to: ctx.accounts.bettor_ata.to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    signer,
+                ),
+                payout,
+                ctx.accounts.payout_mint.decimals,
+            )?;
+        }
+
+        emit!(MultiMintClaimed {
+            market: m.key(),
+            bettor: ctx.accounts.bettor.key(),
+            deposit_mint: p.mint,
+            payout_mint: mm.mint,
+            payout,
+            units: p.units,
+        });
+
+        if p.claimed_mask == full_mint_mask(m.mint_count) {
+            if let Some(pf) = ctx.accounts.portfolio.as_mut() {
+                pf.untrack(m.key());
+            }
+            ctx.accounts.position.close(ctx.accounts.bettor.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    /// Per-mint `sweep_fees`; the platform cut comes out first.
+    pub fn sweep_mint_fees(ctx: Context<SweepMintFees>) -> Result<()> {
+        let caller = ctx.accounts.owner.key();
+        require!(
+            caller == owner_pubkey()
+                || is_operator_admin(&ctx.accounts.market, ctx.accounts.operator.as_ref(), caller),
+            ErrorCode::Unauthorized
+        );
+
+        let mm = &mut ctx.accounts.market_mint;
+        let total = mm.fees_accrued;
+        require!(total > 0, ErrorCode::NoFees);
+
+        let insurance = &mut ctx.accounts.insurance_fund;
+        let cut = calculate_insurance_cut(total, insurance.fee_share_bps)?;
+        let amount = total.checked_sub(cut).ok_or(ErrorCode::Overflow)?;
+
+        let market_key = ctx.accounts.market.key();
+        let bump: u8 = ctx.bumps.vault_authority;
+        let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, market_key.as_ref(), &[bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+
+        if cut > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.vault.to_account_info(),
+                        mint: ctx.accounts.mint.to_account_info(),
+                        to: ctx.accounts.insurance_vault.to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    signer,
+                ),
+                cut,
+                ctx.accounts.mint.decimals,
+            )?;
+
+            let m = &mut ctx.accounts.market;
+            insurance.total_contributed = insurance.total_contributed.checked_add(cut).ok_or(ErrorCode::Overflow)?;
+            m.insurance_contributed = m.insurance_contributed.checked_add(cut).ok_or(ErrorCode::Overflow)?;
+
+            emit!(InsuranceContributed {
+                market: market_key,
+                mint: mm.mint,
+                amount: cut,
+            });
+        }
+
+        let m = &ctx.accounts.market;
+        let platform_cut = match ctx.accounts.operator.as_ref().filter(|_| m.operator != Pubkey::default()) {
+            Some(op) => {
+                require_keys_eq!(op.key(), m.operator, ErrorCode::WrongOperator);
+                calculate_insurance_cut(amount, op.platform_cut_bps)?
+            }
+            None => {
+                require!(m.operator == Pubkey::default(), ErrorCode::WrongOperator);
+                0
+            }
+        };
+        if platform_cut > 0 {
+            let platform_ata = ctx.accounts.platform_ata.as_ref().ok_or(ErrorCode::WrongOperator)?;
+            require_keys_eq!(platform_ata.owner, fee_wallet_pubkey(), ErrorCode::InvalidFeeReceiver);
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.vault.to_account_info(),
+                        mint: ctx.accounts.mint.to_account_info(),
+                        to: platform_ata.to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    signer,
+                ),
+                platform_cut,
+                ctx.accounts.mint.decimals,
+            )?;
+
+            emit!(PlatformFeeTaken {
+                market: market_key,
+                operator: m.operator,
+                amount: platform_cut,
+            });
+        }
+        let amount = amount.checked_sub(platform_cut).ok_or(ErrorCode::Overflow)?;
+
+        if amount > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.vault.to_account_info(),
+                        mint: ctx.accounts.mint.to_account_info(),
+                        to: ctx.accounts.fee_receiver_ata.to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    signer,
+                ),
+                amount,
+                ctx.accounts.mint.decimals,
+            )?;
+        }
+
+        mm.fees_accrued = 0;
+
+        emit!(FeesCollected {
+            market: market_key,
+            amount,
+            recipient: ctx.accounts.market.fee_receiver,
+        });
+
+        Ok(())
+    }
+
+    /// Caps positions in USD via `price_feed`; zero falls back to `MAX_BET_LIMIT`.
+    pub fn set_usd_bet_cap(ctx: Context<SetUsdBetCap>, usd_cap: u64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+
+        if usd_cap == 0 {
+            m.price_feed = Pubkey::default();
+            m.usd_bet_cap = 0;
+        } else {
+            let feed = ctx.accounts.price_feed.as_ref().ok_or(ErrorCode::WrongOracle)?;
+            m.price_feed = feed.key();
+            m.usd_bet_cap = usd_cap;
+        }
+
+        emit!(UsdBetCapUpdated {
+            market: m.key(),
+            price_feed: m.price_feed,
+            usd_cap: m.usd_bet_cap,
+        });
+
+        Ok(())
+    }
+
+    /// Registers the loyalty reward mint.
+    pub fn init_rewards(ctx: Context<InitRewards>, emission_per_thousand: u64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(
+            ctx.accounts.reward_mint.mint_authority == Some(ctx.accounts.reward_mint_authority.key()).into(),
+            ErrorCode::InvalidRewardMint
+        );
+
+        let rc = &mut ctx.accounts.reward_config;
+        rc.reward_mint = ctx.accounts.reward_mint.key();
+        rc.emission_per_thousand = emission_per_thousand;
+        rc.enabled = true;
+        rc.mint_authority_bump = ctx.bumps.reward_mint_authority;
+        rc.bump = ctx.bumps.reward_config;
+
+        emit!(RewardRateUpdated {
+            reward_mint: rc.reward_mint,
+            emission_per_thousand,
+            enabled: true,
+        });
+
+        Ok(())
+    }
+
+    /// Registers the Merkle tree for compressed bet receipts.
+    pub fn init_receipts(ctx: Context<InitReceipts>, uri_base: String) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(uri_base.len() <= MAX_RECEIPT_URI_BASE_LEN, ErrorCode::MetadataTooLong);
+
+        let rc = &mut ctx.accounts.receipt_config;
+        rc.merkle_tree = ctx.accounts.merkle_tree.key();
+        rc.uri_base = uri_base;
+        rc.enabled = true;
+        rc.authority_bump = ctx.bumps.receipt_authority;
+        rc.bump = ctx.bumps.receipt_config;
+
+        emit!(ReceiptsUpdated {
+            merkle_tree: rc.merkle_tree,
+            enabled: true,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_receipts_enabled(ctx: Context<SetReceiptsEnabled>, enabled: bool) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+        let rc = &mut ctx.accounts.receipt_config;
+        rc.enabled = enabled;
+
+        emit!(ReceiptsUpdated {
+            merkle_tree: rc.merkle_tree,
+            enabled,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_reward_rate(ctx: Context<SetRewardRate>, emission_per_thousand: u64, enabled: bool) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+        let rc = &mut ctx.accounts.reward_config;
+        rc.emission_per_thousand = emission_per_thousand;
+        rc.enabled = enabled;
+
+        emit!(RewardRateUpdated {
+            reward_mint: rc.reward_mint,
+            emission_per_thousand,
+            enabled,
+        });
+
+        Ok(())
+    }
+
+    /// Pays part of a market's accrued fees back to one recipient.
+    pub fn distribute_rebate(ctx: Context<DistributeRebate>, amount: u64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        let m = &mut ctx.accounts.market;
+        require!(amount <= m.fees_accrued, ErrorCode::NoFees);
+
+        let market_key = m.key();
+        let bump: u8 = ctx.bumps.vault_authority;
+        let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, market_key.as_ref(), &[bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault.to_account_info(),
+                    mint: ctx.accounts.bet_mint.to_account_info(),
+                    to: ctx.accounts.recipient_ata.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                signer,
+            ),
+            amount,
+            ctx.accounts.bet_mint.decimals,
+        )?;
+
+        m.fees_accrued = m.fees_accrued.checked_sub(amount).ok_or(ErrorCode::Overflow)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let r = &mut ctx.accounts.rebate;
+        r.market = market_key;
+        r.recipient = ctx.accounts.recipient.key();
+        r.amount = amount;
+        r.paid_at = now;
+
+        emit!(RebateDistributed {
+            market: market_key,
+            recipient: r.recipient,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Share of the accrued fees (in bps) handed to the winning side at resolution instead of being swept.
+    pub fn set_winner_rake(ctx: Context<SetWinnerRake>, winner_rake_bps: u16) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(ctx.accounts.config.timelock_secs == 0, ErrorCode::TimelockRequired);
+
+        apply_winner_rake(&mut ctx.accounts.market, winner_rake_bps)
+    }
+
+    pub fn init_insurance_fund(ctx: Context<InitInsuranceFund>, fee_share_bps: u16) -> Result<()> {
+        insurance::init_insurance_fund(ctx, fee_share_bps)
+    }
+
+    pub fn set_insurance_share(ctx: Context<SetInsuranceShare>, fee_share_bps: u16) -> Result<()> {
+        insurance::set_insurance_share(ctx, fee_share_bps)
+    }
+
+    /// Pays bettors out of the insurance vault, e.g. after an oracle failure.
+    pub fn pay_from_insurance(ctx: Context<PayFromInsurance>, market: Pubkey, amount: u64) -> Result<()> {
+        insurance::pay_from_insurance(ctx, market, amount)
+    }
+
+    /// Adds sponsor money to a market without opening a position.
+    pub fn subsidize_market(ctx: Context<SubsidizeMarket>, amount: u64, side: SubsidySide) -> Result<()> {
+        subsidy::subsidize_market(ctx, amount, side)
+    }
+
+    /// Refunds the donor's unpaid subsidy; the account stays open while rewards are owed.
+    pub fn claim_subsidy_refund(ctx: Context<ClaimSubsidyRefund>) -> Result<()> {
+        subsidy::claim_subsidy_refund(ctx)
+    }
+
+    /// Voids a market at any point before resolution (cancelled event, ambiguous wording).
+    pub fn void_market(ctx: Context<VoidMarket>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+        let m = &mut ctx.accounts.market;
+        require!(!m.resolved, ErrorCode::AlreadyResolved);
+
+        let now = Clock::get()?.unix_timestamp;
+        void_market_state(m, &mut ctx.accounts.program_stats, ResolutionReason::EmergencyVoid, now)
+    }
+
+    pub fn queue_emergency_withdraw(ctx: Context<QueueEmergencyWithdraw>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+        let m = &mut ctx.accounts.market;
+        let now = Clock::get()?.unix_timestamp;
+        let unlock_ts = m.cutoff_ts.checked_add(EMERGENCY_WITHDRAW_DELAY).ok_or(ErrorCode::Overflow)?;
+        require!(now >= unlock_ts, ErrorCode::TooEarly);
+
+        m.emergency_withdraw_queued_at = now;
+
+        emit!(EmergencyWithdrawQueued {
+            market: m.key(),
+            executable_at: now.checked_add(EMERGENCY_WITHDRAW_TIMELOCK).ok_or(ErrorCode::Overflow)?,
+        });
+
+        Ok(())
+    }
+
+    /// Moves everything left in one of the market's vaults to the treasury.
+    pub fn emergency_withdraw(ctx: Context<EmergencyWithdraw>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+        let m = &ctx.accounts.market;
+        let now = Clock::get()?.unix_timestamp;
+        require!(m.emergency_withdraw_queued_at > 0, ErrorCode::NotQueued);
+        require!(m.yield_deposited == 0, ErrorCode::YieldDeployed);
+        let unlock_ts = m.cutoff_ts.checked_add(EMERGENCY_WITHDRAW_DELAY).ok_or(ErrorCode::Overflow)?;
+        let timelock_ts = m.emergency_withdraw_queued_at
+            .checked_add(EMERGENCY_WITHDRAW_TIMELOCK)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(now >= unlock_ts && now >= timelock_ts, ErrorCode::TooEarly);
+
+        let amount = ctx.accounts.vault.amount;
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        let market_key = m.key();
+        let bump: u8 = ctx.bumps.vault_authority;
+        let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, market_key.as_ref(), &[bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.treasury_ata.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                signer,
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        emit!(EmergencyWithdrawn {
+            market: market_key,
+            mint: ctx.accounts.mint.key(),
+            amount,
+            treasury: ctx.accounts.treasury.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Closes a losing position after resolution, or any position past the claim deadline.
+    pub fn reclaim_position_rent(ctx: Context<ReclaimPositionRent>) -> Result<()> {
+        let m = &ctx.accounts.market;
+        let p = &ctx.accounts.position;
+        require!(m.resolved, ErrorCode::NotResolved);
+        require_keys_eq!(p.market, m.key(), ErrorCode::WrongMarket);
+
+        let now = Clock::get()?.unix_timestamp;
+        let lost = m.winning_outcome != Outcome::Void as u8
+            && m.winning_outcome != Outcome::Split as u8
+            && p.outcome != m.winning_outcome
+            && p.insured == 0;
+        let expired = now > m.claim_deadline_ts;
+        require!(lost || expired || p.claimed, ErrorCode::PositionStillClaimable);
+        require!(!m.jackpot_pending, ErrorCode::JackpotDrawPending);
+        require!(position_closable(m, p, now)?, ErrorCode::RewardsUnclaimed);
+
+        let info = ctx.accounts.position.to_account_info();
+        let tip = info.lamports()
+            .checked_mul(RENT_CRANK_BPS)
+            .ok_or(ErrorCode::Overflow)?
+            / BPS_DENOM;
+        info.sub_lamports(tip)?;
+        ctx.accounts.cranker.add_lamports(tip)?;
+
+        emit!(PositionRentReclaimed {
+            market: m.key(),
+            position: info.key(),
+            bettor: p.owner,
+            cranker: ctx.accounts.cranker.key(),
+            cranker_tip: tip,
+            expired,
+        });
+
+        Ok(())
+    }
+
+    /// Switches a voided market to Merkle refunds.
+    pub fn publish_refund_root(
+        ctx: Context<PublishRefundRoot>,
+        root: [u8; 32],
+        leaf_count: u32,
+        total: u64,
+        expected_claimed: u64,
+    ) -> Result<()> {
+        merkle_refund::publish_refund_root(ctx, root, leaf_count, total, expected_claimed)
+    }
+
+    /// Pays a batch of Merkle refunds.
+    pub fn claim_merkle_refunds<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimMerkleRefunds<'info>>,
+        claims: Vec<RefundClaim>,
+    ) -> Result<()> {
+        merkle_refund::claim_merkle_refunds(ctx, claims)
+    }
+
+    /// Closes positions on a Merkle-refunded market in bulk; their stakes are paid from the tree.
+    pub fn close_refunded_positions<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseRefundedPositions<'info>>,
+    ) -> Result<()> {
+        merkle_refund::close_refunded_positions(ctx)
+    }
+
+    /// Bounds the worst-case amount winners can be owed; zero disables it.
+    pub fn set_max_payout(ctx: Context<SetMaxPayout>, max_payout: u64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+        require!(m.shard_count == 0, ErrorCode::ShardingUnsupported);
+
+        m.max_payout = max_payout;
+
+        emit!(MaxPayoutUpdated {
+            market: m.key(),
+            max_payout,
+        });
+
+        Ok(())
+    }
+
+    /// When `block_cpi` is set, bets must be top-level instructions.
+    pub fn set_cpi_policy(ctx: Context<SetCpiPolicy>, block_cpi: bool) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+
+        m.block_cpi = block_cpi;
+
+        emit!(CpiPolicyUpdated {
+            market: m.key(),
+            block_cpi,
+        });
+
+        Ok(())
+    }
+
+    /// Caps a winner's payout at `max_multiplier_bps / 10_000` times their stake; zero disables the cap.
+    pub fn set_max_multiplier(ctx: Context<SetMaxMultiplier>, max_multiplier_bps: u32) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(ctx.accounts.config.timelock_secs == 0, ErrorCode::TimelockRequired);
+
+        apply_max_multiplier(&mut ctx.accounts.market, max_multiplier_bps)
+    }
+
+    /// Pushes the cutoff out when a large bet lands in the last `window_secs`.
+    pub fn set_anti_snipe(
+        ctx: Context<SetAntiSnipe>,
+        window_secs: i64,
+        threshold_bps: u16,
+        extension_secs: i64,
+        max_extension_secs: i64,
+    ) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(threshold_bps as u64 <= BPS_DENOM, ErrorCode::InvalidBps);
+        require!(
+            window_secs >= 0 && extension_secs >= 0 && max_extension_secs >= 0,
+            ErrorCode::InvalidAntiSnipe
+        );
+
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+        require!(m.shard_count == 0, ErrorCode::ShardingUnsupported);
+
+        m.snipe_window_secs = window_secs;
+        m.snipe_threshold_bps = threshold_bps;
+        m.snipe_extension_secs = extension_secs;
+        m.snipe_max_extension_secs = max_extension_secs;
+
+        emit!(AntiSnipeUpdated {
+            market: m.key(),
+            window_secs,
+            threshold_bps,
+            extension_secs,
+            max_extension_secs,
+        });
+
+        Ok(())
+    }
+
+    /// Moves the event time (e.g. a postponed kickoff).
+    pub fn update_event_ts(ctx: Context<UpdateEventTs>, new_event_ts: i64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(ctx.accounts.config.timelock_secs == 0, ErrorCode::TimelockRequired);
+
+        apply_event_ts(&mut ctx.accounts.market, new_event_ts)
+    }
+
+    /// Turns the market into a staged (best-of-N) market.
+    pub fn configure_rounds(ctx: Context<ConfigureRounds>, round_count: u8) -> Result<()> {
+        rounds::configure_rounds(ctx, round_count)
+    }
+
+    pub fn record_round(ctx: Context<RecordRound>, round_index: u8, result: Outcome) -> Result<()> {
+        rounds::record_round(ctx, round_index, result)
+    }
+
+    /// Resolves a staged market once one side holds a majority of rounds.
+    pub fn finalize_from_rounds(ctx: Context<FinalizeFromRounds>) -> Result<()> {
+        rounds::finalize_from_rounds(ctx)
+    }
+
+    /// Makes this market conditional on `parent_market` resolving to `required_outcome`.
+    pub fn set_parent_condition(ctx: Context<SetParentCondition>, required_outcome: Outcome) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(matches!(required_outcome, Outcome::Yes | Outcome::No), ErrorCode::InvalidOutcomeArg);
+
+        let parent_key = ctx.accounts.parent_market.key();
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(total_staked(m)? == 0, ErrorCode::MarketHasBets);
+        require_keys_neq!(parent_key, m.key(), ErrorCode::WrongParentMarket);
+        require!(ctx.accounts.parent_market.parent_market == Pubkey::default(), ErrorCode::WrongParentMarket);
+
+        m.parent_market = parent_key;
+        m.parent_condition = required_outcome as u8;
+
+        emit!(ParentConditionSet {
+            market: m.key(),
+            parent_market: parent_key,
+            required_outcome: m.parent_condition,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless crank voiding a child market whose parent resolved the other way (or was voided).
+    pub fn void_if_condition_failed(ctx: Context<VoidIfConditionFailed>) -> Result<()> {
+        let m = &mut ctx.accounts.market;
+        require!(!m.resolved, ErrorCode::AlreadyResolved);
+        require!(
+            parent_condition_failed(m, Some(&ctx.accounts.parent_market))?,
+            ErrorCode::ConditionNotFailed
+        );
+        reserve_keeper_tip(m, ctx.accounts.caller.key())?;
+
+        let now = Clock::get()?.unix_timestamp;
+        void_market_state(m, &mut ctx.accounts.program_stats, ResolutionReason::ConditionFailed, now)
+    }
+
+    /// Copies `source_market`'s settings and question into a new market closing at `new_cutoff_ts`.
+    pub fn clone_market(ctx: Context<CloneMarket>, new_cutoff_ts: i64) -> Result<()> {
+        require_market_creator(ctx.accounts.owner.key(), ctx.accounts.operator.as_ref())?;
+
+        let src = &ctx.accounts.source_market;
+        require!(!src.multi_mint, ErrorCode::MultiMintMarket);
+        require_keys_eq!(
+            ctx.accounts.operator.as_ref().map_or(Pubkey::default(), |op| op.key()),
+            src.operator,
+            ErrorCode::WrongOperator
+        );
+        require_mint_allowed(&ctx.accounts.config, ctx.accounts.approved_mint.as_ref())?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let event_offset = src.event_ts.checked_sub(src.cutoff_ts).ok_or(ErrorCode::Overflow)?;
+        let claim_window = src.claim_deadline_ts.checked_sub(src.event_ts).ok_or(ErrorCode::Overflow)?;
+        let event_ts = new_cutoff_ts.checked_add(event_offset).ok_or(ErrorCode::Overflow)?;
+        let claim_deadline_ts = event_ts.checked_add(claim_window).ok_or(ErrorCode::Overflow)?;
+        let (event_ts, claim_deadline_ts) =
+            validate_schedule(
+                now,
+                new_cutoff_ts,
+                Some(event_ts),
+                Some(claim_deadline_ts),
+                ctx.accounts.config.min_duration,
+            )?;
+
+        let stats = &mut ctx.accounts.program_stats;
+        let market_id = stats.total_markets;
+        stats.total_markets = stats.total_markets.checked_add(1).ok_or(ErrorCode::Overflow)?;
+
+        let m = &mut ctx.accounts.market;
+        init_market(
+            m,
+            ctx.accounts.owner.key(),
+            ctx.accounts.bet_mint.key(),
+            ctx.accounts.vault.key(),
+            ctx.accounts.vault_authority.key(),
+            new_cutoff_ts,
+            event_ts,
+            claim_deadline_ts,
+            now,
+        );
+        m.market_id = market_id;
+        m.bump = ctx.bumps.market;
+        m.fee_receiver = src.fee_receiver;
+        m.fee_destination = src.fee_destination;
+        m.resolve_authority = src.resolve_authority;
+        m.oracle_feed = src.oracle_feed;
+        m.oracle_threshold = src.oracle_threshold;
+        m.resolvable_early = src.resolvable_early;
+        m.report_signer = src.report_signer;
+        m.vaa_emitter_chain = src.vaa_emitter_chain;
+        m.vaa_emitter = src.vaa_emitter;
+        m.three_way = src.three_way;
+        m.over_under = src.over_under;
+        m.ou_line = src.ou_line;
+        m.ou_units = src.ou_units;
+        m.max_bet_pool_bps = src.max_bet_pool_bps;
+        m.bet_cap_min_pool = src.bet_cap_min_pool;
+        m.price_feed = src.price_feed;
+        m.usd_bet_cap = src.usd_bet_cap;
+        m.winner_rake_bps = src.winner_rake_bps;
+        m.max_payout = src.max_payout;
+        m.max_multiplier_bps = src.max_multiplier_bps;
+        m.snipe_window_secs = src.snipe_window_secs;
+        m.snipe_threshold_bps = src.snipe_threshold_bps;
+        m.snipe_extension_secs = src.snipe_extension_secs;
+        m.snipe_max_extension_secs = src.snipe_max_extension_secs;
+        m.round_count = src.round_count;
+        if let Some(op) = ctx.accounts.operator.as_mut() {
+            assign_operator(m, op)?;
+        }
+        ctx.accounts.market_guard.market = m.key();
+
+        let metadata = &mut ctx.accounts.market_metadata;
+        metadata.market = m.key();
+        metadata.question = ctx.accounts.source_metadata.question.clone();
+        metadata.category = ctx.accounts.source_metadata.category.clone();
+        metadata.created_at = now;
+
+        emit!(MarketCloned {
+            market: m.key(),
+            source_market: src.key(),
+            cutoff_ts: m.cutoff_ts,
+        });
+
+        emit!(MarketCreated {
+            market: m.key(),
+            creator: m.creator,
+            cutoff_ts: m.cutoff_ts,
+            bet_mint: m.bet_mint,
+            question: metadata.question.clone(),
+            category: metadata.category.clone(),
+            claim_deadline_ts: m.claim_deadline_ts,
+            event_ts: m.event_ts,
+        });
+
+        Ok(())
+    }
+
+    pub fn create_template(ctx: Context<CreateTemplate>, template_id: u64, params: TemplateParams) -> Result<()> {
+        template::create_template(ctx, template_id, params)
+    }
+
+    pub fn update_template(ctx: Context<UpdateTemplate>, params: TemplateParams) -> Result<()> {
+        template::update_template(ctx, params)
+    }
+
+    /// Creates a market from a stored template; only the question is supplied.
+    pub fn create_from_template(ctx: Context<CreateFromTemplate>, question: String) -> Result<()> {
+        template::create_from_template(ctx, question)
+    }
+
+    /// Creates a slate of markets sharing one bet mint.
+    pub fn create_markets<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateMarkets<'info>>,
+        specs: Vec<MarketSpec>,
+    ) -> Result<()> {
+        require_market_creator(ctx.accounts.owner.key(), ctx.accounts.operator.as_ref())?;
+        require!(!specs.is_empty() && specs.len() <= MAX_BATCH_MARKETS, ErrorCode::InvalidBatch);
+        require!(ctx.remaining_accounts.len() == specs.len() * 5, ErrorCode::InvalidBatch);
+        require_mint_allowed(&ctx.accounts.config, ctx.accounts.approved_mint.as_ref())?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let owner_info = ctx.accounts.owner.to_account_info();
+        let system_info = ctx.accounts.system_program.to_account_info();
+
+        for (i, spec) in specs.into_iter().enumerate() {
+            let accs = &ctx.remaining_accounts[i * 5..i * 5 + 5];
+            let (market_info, metadata_info, guard_info, vault_auth_info, vault_info) =
+                (&accs[0], &accs[1], &accs[2], &accs[3], &accs[4]);
+
+            let (event_ts, claim_deadline_ts) = validate_schedule(
+                now,
+                spec.cutoff_ts,
+                spec.event_ts,
+                spec.claim_deadline_ts,
+                ctx.accounts.config.min_duration,
+            )?;
+            require!(spec.question.len() <= 280, ErrorCode::MetadataTooLong);
+            require!(spec.category.len() <= 50, ErrorCode::MetadataTooLong);
+
+            let market_id = ctx.accounts.program_stats.total_markets;
+            let seed = market_seed(&ctx.accounts.program_stats, ctx.accounts.operator.as_ref());
+            let (market_key, market_bump) =
+                Pubkey::find_program_address(&[MARKET_SEED, &seed], ctx.program_id);
+            require_keys_eq!(market_info.key(), market_key, ErrorCode::InvalidBatch);
+            let (metadata_key, metadata_bump) =
+                Pubkey::find_program_address(&[b"market-metadata", market_key.as_ref()], ctx.program_id);
+            require_keys_eq!(metadata_info.key(), metadata_key, ErrorCode::InvalidBatch);
+            let (vault_auth_key, _) =
+                Pubkey::find_program_address(&[VAULT_AUTH_SEED, market_key.as_ref()], ctx.program_id);
+            require_keys_eq!(vault_auth_info.key(), vault_auth_key, ErrorCode::InvalidBatch);
+
+            create_pda_account(
+                &owner_info,
+                market_info,
+                &system_info,
+                Market::LEN,
+                ctx.program_id,
+                &[MARKET_SEED, &seed, &[market_bump]],
+            )?;
+            create_pda_account(
+                &owner_info,
+                metadata_info,
+                &system_info,
+                MarketMetadata::LEN,
+                ctx.program_id,
+                &[b"market-metadata", market_key.as_ref(), &[metadata_bump]],
+            )?;
+            claim_market_guard(
+                &owner_info,
+                guard_info,
+                &system_info,
+                ctx.program_id,
+                market_key,
+                market_guard_hash(&spec.question, spec.cutoff_ts, &ctx.accounts.bet_mint.key()),
+            )?;
+            associated_token::create(CpiContext::new(
+                ctx.accounts.associated_token_program.to_account_info(),
+                associated_token::Create {
+                    payer: owner_info.clone(),
+                    associated_token: vault_info.clone(),
+                    authority: vault_auth_info.clone(),
+                    mint: ctx.accounts.bet_mint.to_account_info(),
+                    system_program: system_info.clone(),
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                },
+            ))?;
+
+            let mut m = Market::try_deserialize_unchecked(&mut &market_info.try_borrow_data()?[..])?;
+            init_market(
+                &mut m,
+                ctx.accounts.owner.key(),
+                ctx.accounts.bet_mint.key(),
+                vault_info.key(),
+                vault_auth_key,
+                spec.cutoff_ts,
+                event_ts,
+                claim_deadline_ts,
+                now,
+            );
+            m.market_id = market_id;
+            m.bump = market_bump;
+            if let Some(op) = ctx.accounts.operator.as_mut() {
+                assign_operator(&mut m, op)?;
+            }
+            m.try_serialize(&mut &mut market_info.try_borrow_mut_data()?[..])?;
+
+            let metadata = MarketMetadata {
+                market: market_key,
+                question: spec.question,
+                category: spec.category,
+                created_at: now,
+            };
+            metadata.try_serialize(&mut &mut metadata_info.try_borrow_mut_data()?[..])?;
+
+            let stats = &mut ctx.accounts.program_stats;
+            stats.total_markets = stats.total_markets.checked_add(1).ok_or(ErrorCode::Overflow)?;
+
+            emit!(MarketCreated {
+                market: market_key,
+                creator: m.creator,
+                cutoff_ts: m.cutoff_ts,
+                bet_mint: m.bet_mint,
+                question: metadata.question,
+                category: metadata.category,
+                claim_deadline_ts: m.claim_deadline_ts,
+                event_ts: m.event_ts,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Resolves a slate of past-cutoff markets in one transaction.
+    pub fn resolve_many<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ResolveMany<'info>>,
+        outcomes: Vec<Outcome>,
+        resolution_reason: ResolutionReason,
+    ) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(
+            !outcomes.is_empty() && outcomes.len() == ctx.remaining_accounts.len(),
+            ErrorCode::InvalidBatch
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        for (info, outcome) in ctx.remaining_accounts.iter().zip(outcomes) {
+            require!(info.is_writable, ErrorCode::InvalidBatch);
+            let mut m: Account<'info, Market> = Account::try_from(info)?;
+            require!(!m.resolved, ErrorCode::AlreadyResolved);
+            require!(m.vote_end_ts == 0, ErrorCode::VoteInProgress);
+            require!(m.proposed_outcome == Outcome::Unset as u8, ErrorCode::ProposalPending);
+            require!(m.round_count == 0, ErrorCode::StagedMarket);
+            require!(m.parent_market == Pubkey::default(), ErrorCode::WrongParentMarket);
+            require!(now >= m.cutoff_ts && now >= m.event_ts, ErrorCode::TooEarly);
+
+            emit!(settle_market(&mut m, &mut ctx.accounts.program_stats, outcome, resolution_reason, &ctx.accounts.instructions)?);
+            m.exit(ctx.program_id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Binds an outcome oracle: Yes when the feed reads at or above `threshold` after the event.
+    pub fn bind_resolution_oracle(ctx: Context<BindResolutionOracle>, threshold: i64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(total_staked(m)? == 0, ErrorCode::MarketHasBets);
+
+        m.oracle_feed = ctx.accounts.oracle_feed.key();
+        m.oracle_threshold = threshold;
+
+        emit!(ResolutionOracleBound {
+            market: m.key(),
+            oracle_feed: m.oracle_feed,
+            threshold,
+        });
+
+        Ok(())
+    }
+
+    /// Makes this an over/under market on `line`, in the bound oracle's units.
+    pub fn set_over_under(ctx: Context<SetOverUnder>, line: i64, units: [u8; 16]) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(!m.three_way, ErrorCode::InvalidOutcomeArg);
+        require!(total_staked(m)? == 0, ErrorCode::MarketHasBets);
+
+        m.over_under = true;
+        m.ou_line = line;
+        m.ou_units = units;
+
+        emit!(OverUnderSet {
+            market: m.key(),
+            line,
+            units,
+        });
+
+        Ok(())
+    }
+
+    /// Fixes the question or category before anyone has bet on the market.
+    pub fn update_market_meta(
+        ctx: Context<UpdateMarketMeta>,
+        question: Option<String>,
+        category: Option<String>,
+    ) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        let m = &ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(total_staked(m)? == 0, ErrorCode::MarketHasBets);
+
+        let metadata = &mut ctx.accounts.market_metadata;
+        if let Some(question) = question {
+            require!(question.len() <= 280, ErrorCode::MetadataTooLong);
+            metadata.question = question;
+        }
+        if let Some(category) = category {
+            require!(category.len() <= 50, ErrorCode::MetadataTooLong);
+            metadata.category = category;
+        }
+
+        emit!(MarketMetaUpdated {
+            market: metadata.market,
+            question: metadata.question.clone(),
+            category: metadata.category.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Limits a bet to `max_bps` of the opposing pool once it holds `min_pool`; zero disables.
+    pub fn set_bet_pool_cap(ctx: Context<SetBetPoolCap>, max_bps: u16, min_pool: u64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(m.shard_count == 0, ErrorCode::ShardingUnsupported);
+
+        m.max_bet_pool_bps = max_bps;
+        m.bet_cap_min_pool = min_pool;
+
+        emit!(BetPoolCapUpdated {
+            market: m.key(),
+            max_bps,
+            min_pool,
+        });
+
+        Ok(())
+    }
+
+    /// Binds a Solend reserve that idle vault funds can be lent to.
+    pub fn enable_yield(ctx: Context<EnableYield>, to_pool: bool) -> Result<()> {
+        idle_yield::enable_yield(ctx, to_pool)
+    }
+
+    /// Lends `amount` of the vault's idle balance to the bound reserve.
+    pub fn deposit_idle(ctx: Context<MoveIdleFunds>, amount: u64) -> Result<()> {
+        idle_yield::deposit_idle(ctx, amount)
+    }
+
+    /// Redeems all collateral back into the vault.
+    pub fn withdraw_idle(ctx: Context<MoveIdleFunds>) -> Result<()> {
+        idle_yield::withdraw_idle(ctx)
+    }
+
+    /// Binds an SPL stake pool that a WSOL market's idle balance can be staked with.
+    pub fn enable_lst_staking(ctx: Context<EnableLstStaking>, to_pool: bool) -> Result<()> {
+        idle_yield::enable_lst_staking(ctx, to_pool)
+    }
+
+    /// Unwraps `amount` of idle WSOL and deposits the lamports into the stake pool.
+    pub fn stake_idle(ctx: Context<StakeIdle>, amount: u64) -> Result<()> {
+        idle_yield::stake_idle(ctx, amount)
+    }
+
+    /// Redeems the whole LST balance for SOL and re-wraps it into the vault.
+    pub fn unstake_idle(ctx: Context<UnstakeIdle>) -> Result<()> {
+        idle_yield::unstake_idle(ctx)
+    }
+
+    /// Adds the next pool shard (up to `MAX_POOL_SHARDS`) with its own vault.
+    pub fn add_pool_shard(ctx: Context<AddPoolShard>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+        require!((m.shard_count as usize) < MAX_POOL_SHARDS, ErrorCode::InvalidBatch);
+        require!(
+            m.snipe_window_secs == 0
+                && m.max_payout == 0
+                && m.max_bet_pool_bps == 0
+                && m.house_limit == 0
+                && m.reward_rate == 0,
+            ErrorCode::ShardingUnsupported
+        );
+
+        let s = &mut ctx.accounts.shard;
+        s.market = m.key();
+        s.vault = ctx.accounts.shard_vault.key();
+        s.index = m.shard_count;
+        s.total_yes = 0;
+        s.total_no = 0;
+        s.total_draw = 0;
+        s.fees_accrued = 0;
+        s.gross_volume = 0;
+        s.new_bettors = 0;
+        s.bump = ctx.bumps.shard;
+
+        m.shard_count += 1;
+
+        emit!(PoolShardAdded {
+            market: m.key(),
+            shard: s.key(),
+            index: s.index,
+            vault: s.vault,
+        });
+
+        Ok(())
+    }
+
+    /// `place_bet` against a pool shard.
+    pub fn place_bet_sharded(
+        ctx: Context<PlaceBetSharded>,
+        outcome: Outcome,
+        amount: u64,
+        position_index: u16,
+    ) -> Result<()> {
+        require!(amount >= MIN_BET_AMOUNT, ErrorCode::BetTooSmall);
+
+        let now = Clock::get()?.unix_timestamp;
+        let m = &ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+        validate_bet_caller(m)?;
+        require!(m.attestation_issuer == Pubkey::default(), ErrorCode::AttestationRequired);
+        require!(!m.emergency_paused, ErrorCode::EmergencyPaused);
+        require!(!m.bracket_locked, ErrorCode::BracketMatchLocked);
+        require!(now >= m.open_ts, ErrorCode::BettingNotOpen);
+        require!(now < m.cutoff_ts, ErrorCode::BettingClosed);
+        match outcome {
+            Outcome::Yes | Outcome::No => {}
+            Outcome::Draw if m.three_way => {}
+            _ => return err!(ErrorCode::InvalidOutcomeArg),
+        }
+
+        validate_position_cap(
+            m,
+            &ctx.accounts.bet_mint,
+            &ctx.accounts.position,
+            amount,
+            ctx.accounts.price_feed.as_ref(),
+            now,
+        )?;
+
+        let vault_before = ctx.accounts.shard_vault.amount;
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.bettor_ata.to_account_info(),
+                    mint: ctx.accounts.bet_mint.to_account_info(),
+                    to: ctx.accounts.shard_vault.to_account_info(),
+                    authority: ctx.accounts.bettor.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.bet_mint.decimals,
+        )?;
+        ctx.accounts.shard_vault.reload()?;
+        let received = ctx.accounts.shard_vault.amount
+            .checked_sub(vault_before)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(received > 0, ErrorCode::InvalidAmount);
+
+        let m = &ctx.accounts.market;
+        let p = &mut ctx.accounts.position;
+        apply_bet_cooldown(m, p, position_index, now)?;
+
+        let fee = calculate_fee(m, received)?;
+        let net = received.checked_sub(fee).ok_or(ErrorCode::Overflow)?;
+
+        let s = &mut ctx.accounts.shard;
+        s.fees_accrued = s.fees_accrued.checked_add(fee).ok_or(ErrorCode::Overflow)?;
+        s.gross_volume = s.gross_volume.checked_add(received).ok_or(ErrorCode::Overflow)?;
+        if p.amount == 0 {
+            s.new_bettors = s.new_bettors.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        }
+        match outcome {
+            Outcome::Yes => s.total_yes = s.total_yes.checked_add(net).ok_or(ErrorCode::Overflow)?,
+            Outcome::No => s.total_no = s.total_no.checked_add(net).ok_or(ErrorCode::Overflow)?,
+            _ => s.total_draw = s.total_draw.checked_add(net).ok_or(ErrorCode::Overflow)?,
+        }
+
+        let market_key = m.key();
+        credit_bettor(
+            p,
+            &mut ctx.accounts.user_stats,
+            ctx.accounts.portfolio.as_deref_mut(),
+            market_key,
+            ctx.accounts.bettor.key(),
+            ctx.bumps.user_stats,
+            outcome,
+            received,
+            fee,
+            net,
+            position_index,
+        )?;
+
+        emit_cpi!(BetPlaced {
+            market: market_key,
+            bettor: ctx.accounts.bettor.key(),
+            outcome,
+            amount,
+            received,
+            fee,
+            net_amount: net,
+            tag: String::new(),
+        });
+
+        Ok(())
+    }
+
+    /// Folds shard totals into the market and moves shard balances into the main vault.
+    pub fn merge_pool_shards<'info>(ctx: Context<'_, '_, 'info, 'info, MergePoolShards<'info>>) -> Result<()> {
+        let m = &ctx.accounts.market;
+        validate_market_active(m)?;
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= m.cutoff_ts || m.emergency_paused, ErrorCode::TooEarly);
+        let pairs = ctx.remaining_accounts;
+        require!(!pairs.is_empty() && pairs.len() % 2 == 0, ErrorCode::InvalidBatch);
+
+        let market_key = m.key();
+        for pair in pairs.chunks(2) {
+            let shard: Account<'info, PoolShard> = Account::try_from(&pair[0])?;
+            require_keys_eq!(shard.market, market_key, ErrorCode::WrongMarket);
+            require_keys_eq!(pair[1].key(), shard.vault, ErrorCode::WrongMarket);
+            let bit = 1u8 << shard.index;
+            require!(ctx.accounts.market.shards_merged & bit == 0, ErrorCode::InvalidBatch);
+
+            let shard_vault: InterfaceAccount<'info, TokenAccount> = InterfaceAccount::try_from(&pair[1])?;
+            if shard_vault.amount > 0 {
+                let seeds: &[&[u8]] = &[POOL_SHARD_SEED, market_key.as_ref(), &[shard.index], &[shard.bump]];
+                token_interface::transfer_checked(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        TransferChecked {
+                            from: pair[1].clone(),
+                            mint: ctx.accounts.bet_mint.to_account_info(),
+                            to: ctx.accounts.vault.to_account_info(),
+                            authority: pair[0].clone(),
+                        },
+                        &[seeds],
+                    ),
+                    shard_vault.amount,
+                    ctx.accounts.bet_mint.decimals,
+                )?;
+            }
+
+            let m = &mut ctx.accounts.market;
+            m.total_yes = m.total_yes.checked_add(shard.total_yes).ok_or(ErrorCode::Overflow)?;
+            m.total_no = m.total_no.checked_add(shard.total_no).ok_or(ErrorCode::Overflow)?;
+            m.total_draw = m.total_draw.checked_add(shard.total_draw).ok_or(ErrorCode::Overflow)?;
+            m.fees_accrued = m.fees_accrued.checked_add(shard.fees_accrued).ok_or(ErrorCode::Overflow)?;
+            m.gross_volume = m.gross_volume.checked_add(shard.gross_volume).ok_or(ErrorCode::Overflow)?;
+            m.shards_merged |= bit;
+
+            let stats = &mut ctx.accounts.program_stats;
+            stats.total_volume = stats.total_volume.checked_add(shard.gross_volume).ok_or(ErrorCode::Overflow)?;
+            stats.total_fees = stats.total_fees.checked_add(shard.fees_accrued).ok_or(ErrorCode::Overflow)?;
+            stats.total_bettors = stats.total_bettors.checked_add(shard.new_bettors).ok_or(ErrorCode::Overflow)?;
+
+            emit!(PoolShardMerged {
+                market: market_key,
+                index: shard.index,
+                total_yes: shard.total_yes,
+                total_no: shard.total_no,
+                total_draw: shard.total_draw,
+                fees_accrued: shard.fees_accrued,
+                swept: shard_vault.amount,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Minimum gap between bets from the same wallet; zero disables it.
+    pub fn set_bet_cooldown(ctx: Context<SetBetCooldown>, cooldown_secs: i64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(cooldown_secs >= 0, ErrorCode::InvalidAmount);
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+
+        m.bet_cooldown_secs = cooldown_secs;
+
+        emit!(BetCooldownUpdated {
+            market: m.key(),
+            cooldown_secs,
+        });
+
+        Ok(())
+    }
+
+    /// Turns a market into a 1X2 market with a third Draw pool.
+    pub fn set_three_way(ctx: Context<SetThreeWay>, enabled: bool) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+        require!(total_staked(m)? == 0, ErrorCode::MarketHasBets);
+        require!(!enabled || !m.over_under, ErrorCode::InvalidOutcomeArg);
+        // Outcome tokens only pay out on Yes or No; a Draw would strand sets.
+        require!(!enabled || m.yes_mint == Pubkey::default(), ErrorCode::InvalidOutcomeArg);
+
+        m.three_way = enabled;
+
+        emit!(ThreeWayUpdated {
+            market: m.key(),
+            enabled,
+        });
+
+        Ok(())
+    }
+
+    /// Lets an oracle-bound market resolve Yes before cutoff once the threshold is hit.
+    pub fn set_resolvable_early(ctx: Context<SetResolvableEarly>, enabled: bool) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(!enabled || m.oracle_feed != Pubkey::default(), ErrorCode::WrongOracle);
+
+        m.resolvable_early = enabled;
+
+        emit!(ResolvableEarlyUpdated {
+            market: m.key(),
+            enabled,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless resolution for oracle-bound markets.
+    pub fn resolve_from_oracle(ctx: Context<ResolveFromOracle>) -> Result<()> {
+        let m = &mut ctx.accounts.market;
+        require!(!m.resolved, ErrorCode::AlreadyResolved);
+        require!(m.vote_end_ts == 0, ErrorCode::VoteInProgress);
+        require!(m.proposed_outcome == Outcome::Unset as u8, ErrorCode::ProposalPending);
+        require!(m.oracle_feed != Pubkey::default(), ErrorCode::WrongOracle);
+        require!(m.round_count == 0, ErrorCode::StagedMarket);
+
+        let now = Clock::get()?.unix_timestamp;
+        let early = now < m.cutoff_ts || now < m.event_ts;
+        require!(!early || m.resolvable_early, ErrorCode::TooEarly);
+        reserve_keeper_tip(m, ctx.accounts.caller.key())?;
+
+        if parent_condition_failed(m, ctx.accounts.parent_market.as_ref())? {
+            return void_market_state(m, &mut ctx.accounts.program_stats, ResolutionReason::ConditionFailed, now);
+        }
+
+        let feed = &ctx.accounts.oracle_feed;
+        if m.over_under {
+            require!(!early, ErrorCode::TooEarly);
+            require!(feed.updated_ts >= m.event_ts, ErrorCode::OracleStale);
+            // Landing exactly on the line is a push: everyone is refunded.
+            let outcome = match feed.value.cmp(&m.ou_line) {
+                std::cmp::Ordering::Greater => Outcome::Yes,
+                std::cmp::Ordering::Less => Outcome::No,
+                std::cmp::Ordering::Equal => {
+                    return void_market_state(m, &mut ctx.accounts.program_stats, ResolutionReason::Push, now);
+                }
+            };
+            emit!(settle_market(m, &mut ctx.accounts.program_stats, outcome, ResolutionReason::Oracle, &ctx.accounts.instructions)?);
+            return Ok(());
+        }
+        if early {
+            // Only a fresh reading at or above the threshold can end the
+            // market early; betting closes with it.
+            read_oracle_price(feed, now)?;
+            require!(
+                feed.updated_ts >= m.created_at && feed.value >= m.oracle_threshold,
+                ErrorCode::ConditionNotMet
+            );
+            m.cutoff_ts = m.cutoff_ts.min(now);
+            emit!(settle_market(m, &mut ctx.accounts.program_stats, Outcome::Yes, ResolutionReason::OracleEarly, &ctx.accounts.instructions)?);
+            return Ok(());
+        }
+        require!(feed.updated_ts >= m.event_ts, ErrorCode::OracleStale);
+
+        let outcome = if feed.value >= m.oracle_threshold {
+            Outcome::Yes
+        } else {
+            Outcome::No
+        };
+        emit!(settle_market(m, &mut ctx.accounts.program_stats, outcome, ResolutionReason::Oracle, &ctx.accounts.instructions)?);
+
+        Ok(())
+    }
+
+    /// Returns the deployment's version and fee/cap parameters via return_data.
+    pub fn get_program_info(_ctx: Context<GetProgramInfo>) -> Result<ProgramInfo> {
+        Ok(ProgramInfo {
+            version: PROGRAM_VERSION.to_string(),
+            fee_bps: FEE_BPS,
+            bps_denom: BPS_DENOM,
+            max_bet_limit: MAX_BET_LIMIT,
+            min_bet_amount: MIN_BET_AMOUNT,
+            max_market_duration: MAX_MARKET_DURATION,
+            default_claim_window: DEFAULT_CLAIM_WINDOW,
+            max_market_mints: MAX_MARKET_MINTS,
+            max_rounds: MAX_ROUNDS as u8,
+            owner: owner_pubkey(),
+            treasury: fee_wallet_pubkey(),
+        })
+    }
+
+    /// Permissionless: records the pool totals, at most once per `SNAPSHOT_INTERVAL`.
+    pub fn snapshot_market(ctx: Context<SnapshotMarket>) -> Result<()> {
+        let m = &ctx.accounts.market;
+        require!(!m.resolved, ErrorCode::MarketResolved);
+
+        let now = Clock::get()?.unix_timestamp;
+        let s = &mut ctx.accounts.snapshots;
+        if s.market == Pubkey::default() {
+            s.market = m.key();
+            s.bump = ctx.bumps.snapshots;
+        } else {
+            require!(
+                now >= s.last_ts.checked_add(SNAPSHOT_INTERVAL).ok_or(ErrorCode::Overflow)?,
+                ErrorCode::SnapshotTooSoon
+            );
+        }
+
+        let index = s.head as usize;
+        s.entries[index] = SnapshotEntry {
+            ts: now,
+            total_yes: m.total_yes,
+            total_no: m.total_no,
+        };
+        s.head = ((index + 1) % SNAPSHOT_CAPACITY) as u16;
+        if (s.count as usize) < SNAPSHOT_CAPACITY {
+            s.count += 1;
+        }
+        s.last_ts = now;
+
+        emit!(MarketSnapshotted {
+            market: m.key(),
+            ts: now,
+            total_yes: m.total_yes,
+            total_no: m.total_no,
+        });
+
+        Ok(())
+    }
+
+    pub fn init_config(ctx: Context<InitConfig>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+        let c = &mut ctx.accounts.config;
+        c.min_duration = DEFAULT_MIN_DURATION;
+        c.timelock_secs = 0;
+        c.governance_mint = Pubkey::default();
+        c.vote_quorum = 0;
+        c.bump = ctx.bumps.config;
+        c.mint_allowlist = false;
+
+        emit!(MinDurationUpdated { min_duration: c.min_duration });
+
+        Ok(())
+    }
+
+    pub fn set_min_duration(ctx: Context<SetConfig>, min_duration: i64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(ctx.accounts.config.timelock_secs == 0, ErrorCode::TimelockRequired);
+
+        apply_min_duration(&mut ctx.accounts.config, min_duration)
+    }
+
+    /// Raises the timelock immediately; lowering it must go through `queue_change`.
+    pub fn increase_timelock(ctx: Context<SetConfig>, timelock_secs: i64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(timelock_secs > ctx.accounts.config.timelock_secs, ErrorCode::InvalidTimelock);
+
+        apply_timelock(&mut ctx.accounts.config, timelock_secs)
+    }
+
+    pub fn queue_change(ctx: Context<QueueChange>, change_id: u64, change: ConfigChange) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        validate_config_change(&change)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let pc = &mut ctx.accounts.pending_change;
+        pc.change_id = change_id;
+        pc.change = change.clone();
+        pc.queued_at = now;
+        pc.eta = now.checked_add(ctx.accounts.config.timelock_secs).ok_or(ErrorCode::Overflow)?;
+        pc.bump = ctx.bumps.pending_change;
+
+        emit!(ChangeQueued {
+            change_id,
+            change,
+            eta: pc.eta,
+        });
+
+        Ok(())
+    }
+
+    /// Applies a queued change once its delay has elapsed and before the grace window closes.
+    pub fn execute_change(ctx: Context<ExecuteChange>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+        let now = Clock::get()?.unix_timestamp;
+        let pc = &ctx.accounts.pending_change;
+        require!(now >= pc.eta, ErrorCode::TooEarly);
+        require!(
+            now <= pc.eta.checked_add(CHANGE_GRACE_PERIOD).ok_or(ErrorCode::Overflow)?,
+            ErrorCode::ChangeExpired
+        );
+
+        let change_id = pc.change_id;
+        match pc.change.clone() {
+            ConfigChange::FeeReceiver { market, receiver } => {
+                apply_fee_receiver(change_target(&mut ctx.accounts.market, market)?, receiver)?;
+            }
+            ConfigChange::Cutoff { market, cutoff_ts } => {
+                let min_duration = ctx.accounts.config.min_duration;
+                apply_cutoff(change_target(&mut ctx.accounts.market, market)?, cutoff_ts, min_duration, now)?;
+            }
+            ConfigChange::MinDuration { min_duration } => {
+                apply_min_duration(&mut ctx.accounts.config, min_duration)?;
+            }
+            ConfigChange::Timelock { timelock_secs } => {
+                apply_timelock(&mut ctx.accounts.config, timelock_secs)?;
+            }
+            ConfigChange::WinnerRake { market, winner_rake_bps } => {
+                apply_winner_rake(change_target(&mut ctx.accounts.market, market)?, winner_rake_bps)?;
+            }
+            ConfigChange::InsuranceShare { fee_share_bps } => {
+                let f = ctx.accounts.insurance_fund.as_mut().ok_or(ErrorCode::InsuranceAccountsMissing)?;
+                apply_insurance_share(f, fee_share_bps)?;
+            }
+            ConfigChange::MaxMultiplier { market, max_multiplier_bps } => {
+                apply_max_multiplier(change_target(&mut ctx.accounts.market, market)?, max_multiplier_bps)?;
+            }
+            ConfigChange::EventTs { market, event_ts } => {
+                apply_event_ts(change_target(&mut ctx.accounts.market, market)?, event_ts)?;
+            }
+            ConfigChange::ReportSigner { market, report_signer } => {
+                apply_report_signer(change_target(&mut ctx.accounts.market, market)?, report_signer)?;
+            }
+            ConfigChange::JackpotShare { bet_mint, fee_share_bps, trigger_amount } => {
+                let j = ctx.accounts.jackpot.as_mut().ok_or(ErrorCode::JackpotAccountsMissing)?;
+                require_keys_eq!(j.bet_mint, bet_mint, ErrorCode::WrongMint);
+                let f = ctx.accounts.insurance_fund.as_mut().ok_or(ErrorCode::InsuranceAccountsMissing)?;
+                apply_jackpot(j, f, fee_share_bps, trigger_amount)?;
+            }
+            ConfigChange::VaaEmitter { market, emitter_chain, emitter } => {
+                apply_vaa_emitter(change_target(&mut ctx.accounts.market, market)?, emitter_chain, emitter)?;
+            }
+            ConfigChange::Governance { governance_mint, vote_quorum } => {
+                apply_governance(&mut ctx.accounts.config, governance_mint, vote_quorum)?;
+            }
+            ConfigChange::FeeDiscount { key, discount_bps, is_collection, enabled } => {
+                let d = ctx.accounts.fee_discount.as_mut().ok_or(ErrorCode::WrongFeeDiscount)?;
+                require_keys_eq!(d.key, key, ErrorCode::WrongFeeDiscount);
+                apply_fee_discount(d, key, discount_bps, is_collection, enabled)?;
+            }
+        }
+
+        emit!(ChangeExecuted { change_id });
+
+        Ok(())
+    }
+
+    pub fn cancel_change(ctx: Context<CancelChange>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+        emit!(ChangeCancelled {
+            change_id: ctx.accounts.pending_change.change_id,
+        });
+
+        Ok(())
+    }
+
+    /// Folds `source` into `target` (same owner, market and side) and closes `source`.
+    pub fn merge_positions(ctx: Context<MergePositions>) -> Result<()> {
+        let target = &mut ctx.accounts.target;
+        let source = &ctx.accounts.source;
+        require_keys_neq!(target.key(), source.key(), ErrorCode::InvalidPosition);
+        require!(!ctx.accounts.market.jackpot_pending, ErrorCode::JackpotDrawPending);
+        require!(!target.claimed && !source.claimed, ErrorCode::AlreadyClaimed);
+        require!(target.amount_claimed == 0 && source.amount_claimed == 0, ErrorCode::AlreadyClaimed);
+        require!(target.outcome == source.outcome, ErrorCode::CannotSwitchSide);
+        require!(!target.frozen && !source.frozen, ErrorCode::PositionFrozen);
+
+        // Settle both at the current accumulator; the market's total stake
+        // is unchanged, so it need not be accrued first.
+        let acc = ctx.accounts.market.reward_per_stake;
+        (target.reward_debt, target.rewards_owed) = settle_rewards(acc, target.amount, target.reward_debt, target.rewards_owed)?;
+        target.rewards_owed = pending_rewards(acc, source.amount, source.reward_debt)?
+            .checked_add(source.rewards_owed)
+            .and_then(|v| v.checked_add(target.rewards_owed))
+            .ok_or(ErrorCode::Overflow)?;
+
+        target.amount = target.amount.checked_add(source.amount).ok_or(ErrorCode::Overflow)?;
+        target.reward_debt = reward_debt(acc, target.amount)?;
+        target.fees_paid = target.fees_paid.checked_add(source.fees_paid).ok_or(ErrorCode::Overflow)?;
+        target.insured = target.insured.checked_add(source.insured).ok_or(ErrorCode::Overflow)?;
+
+        emit!(PositionsMerged {
+            market: ctx.accounts.market.key(),
+            owner: ctx.accounts.owner.key(),
+            target_index: target.index,
+            source_index: source.index,
+            amount: target.amount,
+        });
+
+        Ok(())
+    }
+
+    /// Moves `amount` of an unclaimed position's stake into a fresh position at `new_index`.
+    pub fn split_position(ctx: Context<SplitPosition>, amount: u64, new_index: u16) -> Result<()> {
+        require!(!ctx.accounts.market.jackpot_pending, ErrorCode::JackpotDrawPending);
+        require!(new_index < MAX_POSITIONS_PER_MARKET, ErrorCode::PositionIndexTooHigh);
+        let source = &mut ctx.accounts.source;
+        require!(!source.claimed && source.amount_claimed == 0, ErrorCode::AlreadyClaimed);
+        require!(amount > 0 && amount < source.amount, ErrorCode::InvalidAmount);
+        require!(!source.frozen, ErrorCode::PositionFrozen);
+
+        // Fees paid follow the stake so each half reports its own PnL.
+        let moved_fees = (source.fees_paid as u128)
+            .checked_mul(amount as u128)
+            .and_then(|v| v.checked_div(source.amount as u128))
+            .ok_or(ErrorCode::Overflow)? as u64;
+        let moved_insured = (source.insured as u128)
+            .checked_mul(amount as u128)
+            .and_then(|v| v.checked_div(source.amount as u128))
+            .ok_or(ErrorCode::Overflow)? as u64;
+        let acc = ctx.accounts.market.reward_per_stake;
+        (source.reward_debt, source.rewards_owed) = settle_rewards(acc, source.amount, source.reward_debt, source.rewards_owed)?;
+        source.amount -= amount;
+        source.reward_debt = reward_debt(acc, source.amount)?;
+        source.fees_paid -= moved_fees;
+        source.insured -= moved_insured;
+
+        let p = &mut ctx.accounts.new_position;
+        p.owner = source.owner;
+        p.market = source.market;
+        p.outcome = source.outcome;
+        p.claimed = false;
+        p.amount = amount;
+        p.index = new_index;
+        p.amount_claimed = 0;
+        p.fees_paid = moved_fees;
+        p.last_bet_ts = source.last_bet_ts;
+        p.frozen = false;
+        p.insured = moved_insured;
+        p.reward_debt = reward_debt(acc, amount)?;
+        p.rewards_owed = 0;
+
+        emit!(PositionSplit {
+            market: ctx.accounts.market.key(),
+            owner: ctx.accounts.owner.key(),
+            source_index: source.index,
+            new_index,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Winnings above `threshold` unlock linearly over `period_secs` from resolution.
+    pub fn set_vesting(ctx: Context<SetVesting>, threshold: u64, period_secs: i64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+        require!(
+            period_secs >= 0 && period_secs < m.claim_deadline_ts.saturating_sub(m.event_ts),
+            ErrorCode::InvalidVesting
+        );
+
+        m.vest_threshold = threshold;
+        m.vest_period_secs = period_secs;
+
+        emit!(VestingUpdated {
+            market: m.key(),
+            threshold,
+            period_secs,
+        });
+
+        Ok(())
+    }
+
+    /// Claims from `source_market` and bets the payout on `target_market` in one go.
+    pub fn claim_and_bet(
+        ctx: Context<ClaimAndBet>,
+        outcome: Outcome,
+        position_index: u16,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require_keys_neq!(
+            ctx.accounts.source_market.key(),
+            ctx.accounts.target_market.key(),
+            ErrorCode::WrongMarket
+        );
+
+        let src = &mut ctx.accounts.source_market;
+        let (payout, voided, first_claim) =
+            take_claimable(src, &mut ctx.accounts.source_position, ctx.accounts.source_vault.amount, None, now)?;
+        require!(payout >= MIN_BET_AMOUNT, ErrorCode::BetTooSmall);
+        if !voided {
+            record_win(&mut ctx.accounts.user_stats, payout, first_claim)?;
+        }
+
+        let m = &mut ctx.accounts.target_market;
+        validate_market_active(m)?;
+        require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+        require!(m.attestation_issuer == Pubkey::default(), ErrorCode::AttestationRequired);
+        validate_bet_caller(m)?;
+        require!(!m.emergency_paused, ErrorCode::EmergencyPaused);
+        require!(!m.bracket_locked, ErrorCode::BracketMatchLocked);
+        require!(now >= m.open_ts, ErrorCode::BettingNotOpen);
+        require!(now < m.cutoff_ts, ErrorCode::BettingClosed);
+        validate_position_cap(
+            m,
+            &ctx.accounts.bet_mint,
+            &ctx.accounts.target_position,
+            payout,
+            ctx.accounts.price_feed.as_ref(),
+            now,
+        )?;
+
+        let source_key = ctx.accounts.source_market.key();
+        let bump: u8 = ctx.bumps.source_vault_authority;
+        let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, source_key.as_ref(), &[bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+
+        let vault_before = ctx.accounts.target_vault.amount;
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.source_vault.to_account_info(),
+                    mint: ctx.accounts.bet_mint.to_account_info(),
+                    to: ctx.accounts.target_vault.to_account_info(),
+                    authority: ctx.accounts.source_vault_authority.to_account_info(),
+                },
+                signer,
+            ),
+            payout,
+            ctx.accounts.bet_mint.decimals,
+        )?;
+        ctx.accounts.target_vault.reload()?;
+        let received = ctx.accounts.target_vault.amount
+            .checked_sub(vault_before)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(received > 0, ErrorCode::InvalidAmount);
+
+        let (fee, net) = record_bet(
+            &mut ctx.accounts.target_market,
+            &mut ctx.accounts.target_position,
+            &mut ctx.accounts.program_stats,
+            &mut ctx.accounts.user_stats,
+            ctx.accounts.portfolio.as_deref_mut().map(|pf| &mut **pf),
+            ctx.accounts.bettor.key(),
+            ctx.bumps.user_stats,
+            outcome,
+            received,
+            0,
+            position_index,
+            now,
+            house_leg(
+                ctx.accounts.house.as_deref_mut(),
+                ctx.accounts.house_vault.as_deref(),
+                &ctx.accounts.target_vault,
+                &ctx.accounts.bet_mint,
+                &ctx.accounts.token_program,
+            ),
+        )?;
+
+        let sp = &ctx.accounts.source_position;
+        emit_cpi!(WinningsClaimed {
+            market: source_key,
+            bettor: ctx.accounts.bettor.key(),
+            payout,
+            position_amount: sp.amount,
+            outcome: sp.outcome,
+            winning_outcome: ctx.accounts.source_market.winning_outcome,
+        });
+        emit_cpi!(pnl_realized(source_key, ctx.accounts.bettor.key(), sp, payout)?);
+        emit_cpi!(BetPlaced {
+            market: ctx.accounts.target_market.key(),
+            bettor: ctx.accounts.bettor.key(),
+            outcome,
+            amount: payout,
+            received,
+            fee,
+            net_amount: net,
+            tag: String::new(),
+        });
+
+        if ctx.accounts.source_position.claimed
+            && position_closable(&ctx.accounts.source_market, &ctx.accounts.source_position, now)?
+        {
+            if let Some(pf) = ctx.accounts.portfolio.as_mut() {
+                pf.untrack(source_key);
+            }
+            ctx.accounts.source_position.close(ctx.accounts.bettor.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    /// Closes the vault of a settled market and returns its rent to the creator.
+    pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+        let m = &ctx.accounts.market;
+        require!(m.resolved, ErrorCode::NotResolved);
+        require!(m.fees_accrued == 0 && m.keeper_tip == 0, ErrorCode::FeesNotSwept);
+        require!(m.yield_deposited == 0, ErrorCode::YieldDeployed);
+        require!(m.bridge_refunds_due == 0, ErrorCode::BridgeRefundPending);
+
+        let now = Clock::get()?.unix_timestamp;
+        let residual = ctx.accounts.vault.amount;
+        // Unredeemed outcome tokens expire with the claim window.
+        require!(residual == 0 || now > m.claim_deadline_ts, ErrorCode::VaultNotEmpty);
+
+        let market_key = m.key();
+        let bump: u8 = ctx.bumps.vault_authority;
+        let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, market_key.as_ref(), &[bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+
+        if residual > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.vault.to_account_info(),
+                        mint: ctx.accounts.bet_mint.to_account_info(),
+                        to: ctx.accounts.treasury_ata.to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    signer,
+                ),
+                residual,
+                ctx.accounts.bet_mint.decimals,
+            )?;
+        }
+
+        token_interface::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.vault.to_account_info(),
+                destination: ctx.accounts.creator.to_account_info(),
+                authority: ctx.accounts.vault_authority.to_account_info(),
+            },
+            signer,
+        ))?;
+
+        emit!(VaultClosed {
+            market: market_key,
+            residual,
+            rent_receiver: ctx.accounts.creator.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Retargets an untouched market to `new_mint`, replacing its empty vault.
+    pub fn update_bet_mint(ctx: Context<UpdateBetMint>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+        let m = &ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+        require!(total_staked(m)? == 0, ErrorCode::MarketHasBets);
+        require!(m.fees_accrued == 0, ErrorCode::MarketHasBets);
+        require!(ctx.accounts.old_vault.amount == 0, ErrorCode::VaultNotEmpty);
+
+        let market_key = m.key();
+        let bump: u8 = ctx.bumps.vault_authority;
+        let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, market_key.as_ref(), &[bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+
+        token_interface::close_account(CpiContext::new_with_signer(
+            ctx.accounts.old_token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.old_vault.to_account_info(),
+                destination: ctx.accounts.owner.to_account_info(),
+                authority: ctx.accounts.vault_authority.to_account_info(),
+            },
+            signer,
+        ))?;
+
+        let m = &mut ctx.accounts.market;
+        let old_mint = m.bet_mint;
+        m.bet_mint = ctx.accounts.new_mint.key();
+        m.vault = ctx.accounts.new_vault.key();
+
+        emit!(BetMintUpdated {
+            market: market_key,
+            old_mint,
+            new_mint: m.bet_mint,
+            vault: m.vault,
+        });
+
+        Ok(())
+    }
+
+    /// Pays the keeper who settled the market the tip reserved from its fees.
+    pub fn claim_keeper_tip(ctx: Context<ClaimKeeperTip>) -> Result<()> {
+        let m = &mut ctx.accounts.market;
+        require_keys_eq!(ctx.accounts.keeper.key(), m.keeper, ErrorCode::Unauthorized);
+        let tip = m.keeper_tip;
+        require!(tip > 0, ErrorCode::NoFees);
+        m.keeper_tip = 0;
+
+        let market_key = m.key();
+        let bump: u8 = ctx.bumps.vault_authority;
+        let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, market_key.as_ref(), &[bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault.to_account_info(),
+                    mint: ctx.accounts.bet_mint.to_account_info(),
+                    to: ctx.accounts.keeper_ata.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                signer,
+            ),
+            tip,
+            ctx.accounts.bet_mint.decimals,
+        )?;
+
+        emit!(KeeperTipPaid {
+            market: market_key,
+            keeper: ctx.accounts.keeper.key(),
+            amount: tip,
+        });
+
+        Ok(())
+    }
+
+    /// Condenses a settled, vault-less market into an `Archive` and closes the `Market`.
+    pub fn archive_market(ctx: Context<ArchiveMarket>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+        let m = &ctx.accounts.market;
+        require!(m.resolved, ErrorCode::NotResolved);
+        require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+        require!(m.fees_accrued == 0 && m.keeper_tip == 0, ErrorCode::FeesNotSwept);
+        require!(m.yield_deposited == 0, ErrorCode::YieldDeployed);
+        require!(ctx.accounts.vault.lamports() == 0, ErrorCode::VaultNotEmpty);
+
+        let now = Clock::get()?.unix_timestamp;
+        let a = &mut ctx.accounts.archive;
+        a.market = m.key();
+        a.market_id = m.market_id;
+        a.creator = m.creator;
+        a.bet_mint = m.bet_mint;
+        a.winning_outcome = m.winning_outcome;
+        a.resolution_reason = m.resolution_reason;
+        a.total_yes = m.total_yes;
+        a.total_no = m.total_no;
+        a.total_draw = m.total_draw;
+        a.gross_volume = m.gross_volume;
+        a.total_claimed = m.total_claimed;
+        a.winner_bonus = m.winner_bonus;
+        a.insurance_contributed = m.insurance_contributed;
+        a.created_at = m.created_at;
+        a.cutoff_ts = m.cutoff_ts;
+        a.resolved_ts = m.resolved_ts;
+        a.archived_ts = now;
+
+        emit!(MarketArchived {
+            market: a.market,
+            archive: a.key(),
+            winning_outcome: a.winning_outcome,
+            rent_receiver: m.creator,
+        });
+
+        Ok(())
+    }
+
+    /// Blocks claims on a position pending investigation (stolen funds, sanctioned owner).
+    pub fn freeze_position(ctx: Context<SetPositionFrozen>, reason_uri: String) -> Result<()> {
+        set_position_frozen(ctx, true, reason_uri)
+    }
+
+    /// Lifts a freeze placed by `freeze_position`.
+    pub fn unfreeze_position(ctx: Context<SetPositionFrozen>, reason_uri: String) -> Result<()> {
+        set_position_frozen(ctx, false, reason_uri)
+    }
+
+    /// Read-only check that the vault covers market liabilities within `max_surplus`.
+    pub fn verify_invariants(ctx: Context<VerifyInvariants>, max_surplus: u64) -> Result<u64> {
+        let m = &ctx.accounts.market;
+        require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+
+        let liabilities = outstanding_liabilities(m)?;
+        // Principal lent out through `deposit_idle` still backs the market.
+        let balance = ctx.accounts.vault.amount.checked_add(m.yield_deposited).ok_or(ErrorCode::Overflow)?;
+        require!(balance >= liabilities, ErrorCode::Insolvent);
+        let surplus = balance - liabilities;
+        require!(surplus <= max_surplus, ErrorCode::InvariantViolated);
+
+        Ok(surplus)
+    }
+
+    pub fn set_governance(ctx: Context<SetConfig>, governance_mint: Pubkey, vote_quorum: u64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(ctx.accounts.config.timelock_secs == 0, ErrorCode::TimelockRequired);
+
+        apply_governance(&mut ctx.accounts.config, governance_mint, vote_quorum)
+    }
+
+    /// Hands a disputed market to a governance token vote lasting `VOTE_WINDOW`.
+    pub fn open_vote(ctx: Context<OpenVote>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(ctx.accounts.config.governance_mint != Pubkey::default(), ErrorCode::GovernanceDisabled);
+
+        let m = &mut ctx.accounts.market;
+        require!(!m.resolved, ErrorCode::AlreadyResolved);
+        require!(m.vote_end_ts == 0, ErrorCode::VoteInProgress);
+        require!(m.proposed_outcome == Outcome::Unset as u8, ErrorCode::ProposalPending);
+        require!(m.round_count == 0, ErrorCode::StagedMarket);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= m.cutoff_ts && now >= m.event_ts, ErrorCode::TooEarly);
+
+        m.vote_end_ts = now.checked_add(VOTE_WINDOW).ok_or(ErrorCode::Overflow)?;
+        m.dispute_stage = DisputeStage::Voting as u8;
+
+        emit!(VoteOpened {
+            market: m.key(),
+            vote_end_ts: m.vote_end_ts,
+        });
+
+        Ok(())
+    }
+
+    /// Locks `amount` governance tokens behind `outcome`.
+    pub fn cast_vote(ctx: Context<CastVote>, outcome: Outcome, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        let m = &mut ctx.accounts.market;
+        require!(
+            outcome != Outcome::Void && outcome_from_u8(m, outcome as u8)? == outcome,
+            ErrorCode::InvalidOutcomeArg
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(!m.resolved && m.vote_end_ts > 0, ErrorCode::VoteNotOpen);
+        require!(now < m.vote_end_ts, ErrorCode::VoteNotOpen);
+
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.voter_ata.to_account_info(),
+                    mint: ctx.accounts.governance_mint.to_account_info(),
+                    to: ctx.accounts.vote_vault.to_account_info(),
+                    authority: ctx.accounts.voter.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.governance_mint.decimals,
+        )?;
+
+        let v = &mut ctx.accounts.vote_record;
+        if v.amount == 0 {
+            v.voter = ctx.accounts.voter.key();
+            v.market = m.key();
+            v.outcome = outcome as u8;
+            v.bump = ctx.bumps.vote_record;
+        } else {
+            require!(v.outcome == outcome as u8, ErrorCode::CannotSwitchSide);
+        }
+        v.amount = v.amount.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+
+        match outcome {
+            Outcome::Yes => m.votes_yes = m.votes_yes.checked_add(amount).ok_or(ErrorCode::Overflow)?,
+            Outcome::Draw => m.votes_draw = m.votes_draw.checked_add(amount).ok_or(ErrorCode::Overflow)?,
+            _ => m.votes_no = m.votes_no.checked_add(amount).ok_or(ErrorCode::Overflow)?,
+        }
+
+        emit!(VoteCast {
+            market: m.key(),
+            voter: v.voter,
+            outcome,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless once the window closes: tallies the vote and opens the appeal window.
+    pub fn finalize_vote(ctx: Context<FinalizeVote>) -> Result<()> {
+        let m = &mut ctx.accounts.market;
+        require!(
+            !m.resolved && m.dispute_stage == DisputeStage::Voting as u8,
+            ErrorCode::VoteNotOpen
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= m.vote_end_ts, ErrorCode::TooEarly);
+
+        let tally = [(Outcome::Yes, m.votes_yes), (Outcome::No, m.votes_no), (Outcome::Draw, m.votes_draw)];
+        let turnout = tally.iter().try_fold(0u64, |t, &(_, v)| t.checked_add(v)).ok_or(ErrorCode::Overflow)?;
+        let top = tally.iter().map(|&(_, v)| v).max().unwrap_or(0);
+        let leaders = tally.iter().filter(|&&(_, v)| v == top).count();
+        let decisive = turnout >= ctx.accounts.config.vote_quorum && leaders == 1;
+        let outcome = if !decisive {
+            Outcome::Void
+        } else {
+            tally.iter().find(|&&(_, v)| v == top).map_or(Outcome::Void, |&(o, _)| o)
+        };
+
+        m.vote_outcome = outcome as u8;
+        m.dispute_stage = DisputeStage::Tallied as u8;
+        m.appeal_deadline_ts = now.checked_add(APPEAL_WINDOW).ok_or(ErrorCode::Overflow)?;
+
+        emit!(VoteTallied {
+            market: m.key(),
+            outcome,
+            votes_yes: m.votes_yes,
+            votes_no: m.votes_no,
+            votes_draw: m.votes_draw,
+            appeal_deadline_ts: m.appeal_deadline_ts,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: applies the vote result once the appeal window has passed without an appeal.
+    pub fn finalize_dispute(ctx: Context<FinalizeDispute>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let m = &mut ctx.accounts.market;
+        require!(m.dispute_stage == DisputeStage::Tallied as u8, ErrorCode::WrongDisputeStage);
+        require!(now >= m.appeal_deadline_ts, ErrorCode::TooEarly);
+
+        let outcome = outcome_from_u8(m, m.vote_outcome)?;
+        reserve_keeper_tip(m, ctx.accounts.caller.key())?;
+        conclude_dispute(
+            m,
+            &mut ctx.accounts.program_stats,
+            &ctx.accounts.instructions,
+            ctx.accounts.resolver_entry.as_mut(),
+            ctx.accounts.disputer.as_ref(),
+            outcome,
+            ResolutionReason::Vote,
+            now,
+        )
+    }
+
+    /// Escalates a tallied vote to the admin by posting `APPEAL_BOND`.
+    pub fn appeal(ctx: Context<Appeal>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let m = &ctx.accounts.market;
+        require!(m.dispute_stage == DisputeStage::Tallied as u8, ErrorCode::WrongDisputeStage);
+        require!(now < m.appeal_deadline_ts, ErrorCode::DisputeWindowClosed);
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.appellant.to_account_info(),
+                    to: ctx.accounts.market.to_account_info(),
+                },
+            ),
+            APPEAL_BOND,
+        )?;
+
+        let m = &mut ctx.accounts.market;
+        m.appellant = ctx.accounts.appellant.key();
+        m.dispute_stage = DisputeStage::Appealed as u8;
+        m.appeal_ready_ts = now.checked_add(APPEAL_DELAY).ok_or(ErrorCode::Overflow)?;
+
+        emit!(VoteAppealed {
+            market: m.key(),
+            appellant: m.appellant,
+            appeal_ready_ts: m.appeal_ready_ts,
+        });
+
+        Ok(())
+    }
+
+    /// Admin ruling on an appeal.
+    pub fn resolve_appeal(ctx: Context<ResolveAppeal>, outcome: Outcome) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+        let now = Clock::get()?.unix_timestamp;
+        let m = &mut ctx.accounts.market;
+        require!(outcome_from_u8(m, outcome as u8)? == outcome, ErrorCode::InvalidOutcomeArg);
+        require!(m.dispute_stage == DisputeStage::Appealed as u8, ErrorCode::WrongDisputeStage);
+        require!(now >= m.appeal_ready_ts, ErrorCode::TooEarly);
+        require_keys_eq!(ctx.accounts.appellant.key(), m.appellant, ErrorCode::WrongDisputer);
+
+        let overturned = outcome as u8 != m.vote_outcome;
+        m.sub_lamports(APPEAL_BOND)?;
+        if overturned {
+            ctx.accounts.appellant.add_lamports(APPEAL_BOND)?;
+        } else {
+            ctx.accounts.treasury.add_lamports(APPEAL_BOND)?;
+        }
+
+        emit!(AppealResolved {
+            market: m.key(),
+            outcome,
+            overturned,
+        });
+
+        conclude_dispute(
+            m,
+            &mut ctx.accounts.program_stats,
+            &ctx.accounts.instructions,
+            ctx.accounts.resolver_entry.as_mut(),
+            ctx.accounts.disputer.as_ref(),
+            outcome,
+            ResolutionReason::Appeal,
+            now,
+        )
+    }
+
+    /// Returns locked governance tokens once the vote has been finalized.
+    pub fn withdraw_vote(ctx: Context<WithdrawVote>) -> Result<()> {
+        let m = &ctx.accounts.market;
+        require!(m.resolved, ErrorCode::NotResolved);
+
+        let amount = ctx.accounts.vote_record.amount;
+        let market_key = m.key();
+        let bump: u8 = ctx.bumps.vote_authority;
+        let seeds: &[&[u8]] = &[VOTE_AUTH_SEED, market_key.as_ref(), &[bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vote_vault.to_account_info(),
+                    mint: ctx.accounts.governance_mint.to_account_info(),
+                    to: ctx.accounts.voter_ata.to_account_info(),
+                    authority: ctx.accounts.vote_authority.to_account_info(),
+                },
+                signer,
+            ),
+            amount,
+            ctx.accounts.governance_mint.decimals,
+        )?;
+
+        emit!(VoteWithdrawn {
+            market: market_key,
+            voter: ctx.accounts.voter.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Lists a resolver with its metadata and a SOL stake held in the entry.
+    pub fn register_resolver(ctx: Context<RegisterResolver>, name: String, uri: String, stake: u64) -> Result<()> {
+        require!(name.len() <= MAX_RESOLVER_NAME_LEN, ErrorCode::MetadataTooLong);
+        require!(uri.len() <= MAX_EVIDENCE_URI_LEN, ErrorCode::MetadataTooLong);
+
+        if stake > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: ctx.accounts.entry.to_account_info(),
+                    },
+                ),
+                stake,
+            )?;
+        }
+
+        let e = &mut ctx.accounts.entry;
+        e.authority = ctx.accounts.authority.key();
+        e.name = name;
+        e.uri = uri;
+        e.stake = stake;
+        e.markets_resolved = 0;
+        e.disputes_lost = 0;
+        e.approved = false;
+        e.bump = ctx.bumps.entry;
+
+        emit!(ResolverRegistered {
+            authority: e.authority,
+            name: e.name.clone(),
+            stake,
+        });
+
+        Ok(())
+    }
+
+    pub fn approve_resolver(ctx: Context<ApproveResolver>, approved: bool) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+        let e = &mut ctx.accounts.entry;
+        e.approved = approved;
+
+        emit!(ResolverApproved {
+            authority: e.authority,
+            approved,
+        });
+
+        Ok(())
+    }
+
+    /// The registered resolver proposes an outcome, final after `DISPUTE_WINDOW` if undisputed.
+    pub fn propose_resolution(ctx: Context<ProposeResolution>, outcome: Outcome) -> Result<()> {
+        require!(
+            matches!(outcome, Outcome::Yes | Outcome::No),
+            ErrorCode::InvalidOutcomeArg
+        );
+
+        let m = &mut ctx.accounts.market;
+        require!(m.resolver != Pubkey::default(), ErrorCode::WrongResolver);
+        require_keys_eq!(ctx.accounts.resolver.key(), m.resolver, ErrorCode::WrongResolver);
+        require!(!m.resolved, ErrorCode::AlreadyResolved);
+        require!(m.vote_end_ts == 0, ErrorCode::VoteInProgress);
+        require!(m.proposed_outcome == Outcome::Unset as u8, ErrorCode::ProposalPending);
+        require!(m.round_count == 0, ErrorCode::StagedMarket);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= m.cutoff_ts && now >= m.event_ts, ErrorCode::TooEarly);
+
+        m.proposed_outcome = outcome as u8;
+        m.dispute_stage = DisputeStage::Proposed as u8;
+        m.dispute_deadline_ts = now.checked_add(DISPUTE_WINDOW).ok_or(ErrorCode::Overflow)?;
+
+        emit!(ResolutionProposed {
+            market: m.key(),
+            resolver: m.resolver,
+            outcome,
+            dispute_deadline_ts: m.dispute_deadline_ts,
+        });
+
+        Ok(())
+    }
+
+    /// Contests a pending proposal by posting `DISPUTE_BOND`; the market goes to a governance vote.
+    pub fn dispute_resolution(ctx: Context<DisputeResolution>) -> Result<()> {
+        require!(ctx.accounts.config.governance_mint != Pubkey::default(), ErrorCode::GovernanceDisabled);
+
+        let now = Clock::get()?.unix_timestamp;
+        let m = &ctx.accounts.market;
+        require!(!m.resolved, ErrorCode::AlreadyResolved);
+        require!(m.proposed_outcome != Outcome::Unset as u8, ErrorCode::NoProposal);
+        require!(m.disputer == Pubkey::default(), ErrorCode::VoteInProgress);
+        require!(now < m.dispute_deadline_ts, ErrorCode::DisputeWindowClosed);
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.disputer.to_account_info(),
+                    to: ctx.accounts.market.to_account_info(),
+                },
+            ),
+            DISPUTE_BOND,
+        )?;
+
+        let m = &mut ctx.accounts.market;
+        m.disputer = ctx.accounts.disputer.key();
+        m.vote_end_ts = now.checked_add(VOTE_WINDOW).ok_or(ErrorCode::Overflow)?;
+        m.dispute_stage = DisputeStage::Voting as u8;
+
+        emit!(ResolutionDisputed {
+            market: m.key(),
+            disputer: m.disputer,
+            vote_end_ts: m.vote_end_ts,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: settles an undisputed proposal once its window ends.
+    pub fn finalize_proposal(ctx: Context<FinalizeProposal>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let m = &mut ctx.accounts.market;
+        require!(!m.resolved, ErrorCode::AlreadyResolved);
+        require!(m.proposed_outcome != Outcome::Unset as u8, ErrorCode::NoProposal);
+        require!(m.disputer == Pubkey::default(), ErrorCode::VoteInProgress);
+        require!(now >= m.dispute_deadline_ts, ErrorCode::TooEarly);
+        reserve_keeper_tip(m, ctx.accounts.caller.key())?;
+
+        let e = &mut ctx.accounts.resolver_entry;
+        e.markets_resolved = e.markets_resolved.checked_add(1).ok_or(ErrorCode::Overflow)?;
+
+        let outcome = if m.proposed_outcome == Outcome::Yes as u8 { Outcome::Yes } else { Outcome::No };
+        m.dispute_stage = DisputeStage::Closed as u8;
+        emit!(settle_market(m, &mut ctx.accounts.program_stats, outcome, ResolutionReason::Resolver, &ctx.accounts.instructions)?);
+
+        Ok(())
+    }
+
+    /// Registers (or updates) a fee discount for holders of an NFT.
+    pub fn set_fee_discount(
+        ctx: Context<SetFeeDiscount>,
+        key: Pubkey,
+        discount_bps: u16,
+        is_collection: bool,
+        enabled: bool,
+    ) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+        let d = &mut ctx.accounts.fee_discount;
+        require!(
+            ctx.accounts.config.timelock_secs == 0 || (d.key == Pubkey::default() && !enabled),
+            ErrorCode::TimelockRequired
+        );
+        d.bump = ctx.bumps.fee_discount;
+
+        apply_fee_discount(d, key, discount_bps, is_collection, enabled)
+    }
+
+    /// Pushes payouts to a page of positions so holders don't have to come back and claim.
+    pub fn distribute_payouts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DistributePayouts<'info>>,
+    ) -> Result<()> {
+        let m = &mut ctx.accounts.market;
+        let caller = ctx.accounts.caller.key();
+        require!(
+            caller == owner_pubkey() || (m.keeper != Pubkey::default() && caller == m.keeper),
+            ErrorCode::Unauthorized
+        );
+        let groups = ctx.remaining_accounts;
+        require!(!groups.is_empty() && groups.len() % 4 == 0, ErrorCode::InvalidBatch);
+
+        let now = Clock::get()?.unix_timestamp;
+        let market_key = m.key();
+        let bump: u8 = ctx.bumps.vault_authority;
+        let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, market_key.as_ref(), &[bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+
+        let mut vault_balance = ctx.accounts.vault.amount;
+        let mut count: u32 = 0;
+        let mut total: u64 = 0;
+        for group in groups.chunks(4) {
+            let (position_info, owner_info, token_info, stats_info) = (&group[0], &group[1], &group[2], &group[3]);
+            let mut p: Account<'info, Position> = Account::try_from(position_info)?;
+            require_keys_eq!(p.owner, owner_info.key(), ErrorCode::Unauthorized);
+            // Bridged holders are paid back across the bridge by `bridge_out`.
+            require!(owner_info.owner != &crate::ID, ErrorCode::BridgedPosition);
+            let owner_token: InterfaceAccount<'info, TokenAccount> = InterfaceAccount::try_from(token_info)?;
+            require_keys_eq!(owner_token.owner, p.owner, ErrorCode::Unauthorized);
+            require_keys_eq!(owner_token.mint, m.bet_mint, ErrorCode::WrongMint);
+            let mut us: Account<'info, UserStats> = Account::try_from(stats_info)?;
+            require_keys_eq!(us.owner, p.owner, ErrorCode::Unauthorized);
+
+            let (payout, voided, first_claim) = take_claimable(m, &mut p, vault_balance, None, now)?;
+            vault_balance = vault_balance.checked_sub(payout).ok_or(ErrorCode::Overflow)?;
+
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.vault.to_account_info(),
+                        mint: ctx.accounts.bet_mint.to_account_info(),
+                        to: token_info.clone(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    signer,
+                ),
+                payout,
+                ctx.accounts.bet_mint.decimals,
+            )?;
+
+            if !voided {
+                record_win(&mut us, payout, first_claim)?;
+                us.exit(&crate::ID)?;
+            }
+
+            emit!(WinningsClaimed {
+                market: market_key,
+                bettor: p.owner,
+                payout,
+                position_amount: p.amount,
+                outcome: p.outcome,
+                winning_outcome: m.winning_outcome,
+            });
+            emit!(pnl_realized(market_key, p.owner, &p, payout)?);
+
+            if p.claimed && position_closable(m, &p, now)? {
+                p.close(owner_info.clone())?;
+            } else {
+                p.exit(&crate::ID)?;
+            }
+            count += 1;
+            total = total.checked_add(payout).ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(PayoutsDistributed {
+            market: market_key,
+            caller,
+            count,
+            total,
+        });
+
+        Ok(())
+    }
+
+    /// Trusts a data provider's ed25519 key to resolve this market; the default key disables it.
+    pub fn set_report_signer(ctx: Context<SetReportSigner>, report_signer: Pubkey) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(ctx.accounts.config.timelock_secs == 0, ErrorCode::TimelockRequired);
+
+        apply_report_signer(&mut ctx.accounts.market, report_signer)
+    }
+
+    /// Resolves from the report signer's signature over `market || outcome (u8) || report_ts (i64 LE)`.
+    pub fn resolve_from_report(ctx: Context<ResolveFromReport>, outcome: Outcome, report_ts: i64) -> Result<()> {
+        let m = &mut ctx.accounts.market;
+        require!(m.report_signer != Pubkey::default(), ErrorCode::InvalidReport);
+        require!(!m.resolved, ErrorCode::AlreadyResolved);
+        require!(m.vote_end_ts == 0, ErrorCode::VoteInProgress);
+        require!(m.proposed_outcome == Outcome::Unset as u8, ErrorCode::ProposalPending);
+        require!(m.round_count == 0, ErrorCode::StagedMarket);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= m.cutoff_ts && now >= m.event_ts, ErrorCode::TooEarly);
+        // The report must describe the finished event, not a pre-signed guess.
+        require!(report_ts >= m.event_ts.max(m.cutoff_ts) && report_ts <= now, ErrorCode::InvalidReport);
+
+        let mut message = Vec::with_capacity(32 + 1 + 8);
+        message.extend_from_slice(m.key().as_ref());
+        message.push(outcome as u8);
+        message.extend_from_slice(&report_ts.to_le_bytes());
+        verify_signed_report(&ctx.accounts.instructions, &m.report_signer, &message)?;
+
+        if parent_condition_failed(m, ctx.accounts.parent_market.as_ref())? {
+            return void_market_state(m, &mut ctx.accounts.program_stats, ResolutionReason::ConditionFailed, now);
+        }
+
+        emit!(settle_market(m, &mut ctx.accounts.program_stats, outcome, ResolutionReason::Report, &ctx.accounts.instructions)?);
+
+        Ok(())
+    }
+
+    /// Sets the Wormhole emitter whose VAAs may resolve this market; chain 0 disables.
+    pub fn set_vaa_emitter(ctx: Context<SetVaaEmitter>, emitter_chain: u16, emitter: [u8; 32]) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(ctx.accounts.config.timelock_secs == 0, ErrorCode::TimelockRequired);
+
+        apply_vaa_emitter(&mut ctx.accounts.market, emitter_chain, emitter)
+    }
+
+    /// Resolves from a guardian-verified VAA already posted to the Core Bridge by the designated emitter.
+    pub fn resolve_from_vaa(ctx: Context<ResolveFromVaa>) -> Result<()> {
+        let m = &mut ctx.accounts.market;
+        require!(m.vaa_emitter_chain != 0, ErrorCode::InvalidVaa);
+        require!(!m.resolved, ErrorCode::AlreadyResolved);
+        require!(m.vote_end_ts == 0, ErrorCode::VoteInProgress);
+        require!(m.proposed_outcome == Outcome::Unset as u8, ErrorCode::ProposalPending);
+        require!(m.round_count == 0, ErrorCode::StagedMarket);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= m.cutoff_ts && now >= m.event_ts, ErrorCode::TooEarly);
+
+        let data = ctx.accounts.posted_vaa.try_borrow_data()?;
+        let vaa = parse_posted_vaa(&data)?;
+        require!(
+            vaa.emitter_chain == m.vaa_emitter_chain && vaa.emitter == m.vaa_emitter,
+            ErrorCode::InvalidVaa
+        );
+        require!(vaa.timestamp as i64 >= m.event_ts.max(m.cutoff_ts), ErrorCode::InvalidVaa);
+        require!(vaa.payload.len() == 33 && vaa.payload[..32] == m.key().to_bytes(), ErrorCode::InvalidVaa);
+        let outcome = outcome_from_u8(m, vaa.payload[32])?;
+        drop(data);
+
+        if parent_condition_failed(m, ctx.accounts.parent_market.as_ref())? {
+            return void_market_state(m, &mut ctx.accounts.program_stats, ResolutionReason::ConditionFailed, now);
+        }
+
+        emit!(settle_market(m, &mut ctx.accounts.program_stats, outcome, ResolutionReason::Wormhole, &ctx.accounts.instructions)?);
+
+        Ok(())
+    }
+
+    /// Redeems a Token Bridge transfer-with-payload and bets it for the foreign sender.
+    pub fn bridge_deposit<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BridgeDeposit<'info>>,
+        return_chain: u16,
+        return_address: [u8; 32],
+        position_index: u16,
+        wrapped: bool,
+        as_refund: bool,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let m = &ctx.accounts.market;
+        require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+        require!(m.attestation_issuer == Pubkey::default(), ErrorCode::AttestationRequired);
+        let vault_authority = ctx.accounts.vault_authority.key();
+
+        let outcome = {
+            let data = ctx.accounts.posted_vaa.try_borrow_data()?;
+            let vaa = parse_posted_vaa(&data)?;
+            let payload = parse_bridge_payload(vaa.payload, &vault_authority)?;
+            require!(
+                payload.len() == 35
+                    && u16::from_be_bytes([payload[1], payload[2]]) == return_chain
+                    && payload[3..35] == return_address,
+                ErrorCode::InvalidVaa
+            );
+            payload[0]
+        };
+
+        let bridge_accounts = ctx.remaining_accounts;
+        require!(
+            bridge_accounts.len() >= 7
+                && bridge_accounts[2].key() == ctx.accounts.posted_vaa.key()
+                && bridge_accounts[5].key() == ctx.accounts.vault.key()
+                && bridge_accounts[6].key() == vault_authority,
+            ErrorCode::InvalidBridgeAccounts
+        );
+        let tag = if wrapped { TB_COMPLETE_WRAPPED_WITH_PAYLOAD } else { TB_COMPLETE_NATIVE_WITH_PAYLOAD };
+        let market_key = m.key();
+        let bump: u8 = ctx.bumps.vault_authority;
+        let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, market_key.as_ref(), &[bump]];
+        let vault_before = ctx.accounts.vault.amount;
+        invoke_signed(
+            &bridge_instruction(bridge_accounts, &vault_authority, vec![tag]),
+            bridge_accounts,
+            &[seeds],
+        )?;
+        ctx.accounts.vault.reload()?;
+        let received = ctx.accounts.vault.amount
+            .checked_sub(vault_before)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(received > 0, ErrorCode::InvalidAmount);
+
+        let b = &mut ctx.accounts.bridge_account;
+        b.chain = return_chain;
+        b.address = return_address;
+        b.bump = ctx.bumps.bridge_account;
+        let holder = b.key();
+
+        let m = &mut ctx.accounts.market;
+        let open = !m.resolved && !m.emergency_paused && !m.bracket_locked && now >= m.open_ts && now < m.cutoff_ts;
+        if as_refund {
+            require!(!open || ctx.accounts.payer.key() == owner_pubkey(), ErrorCode::Unauthorized);
+        }
+        if !open || as_refund {
+            let r = ctx.accounts.bridge_refund.as_mut().ok_or(ErrorCode::InvalidBridgeAccounts)?;
+            r.market = market_key;
+            r.bridge_account = holder;
+            r.amount = r.amount.checked_add(received).ok_or(ErrorCode::Overflow)?;
+            r.bump = ctx.bumps.bridge_refund.ok_or(ErrorCode::InvalidBridgeAccounts)?;
+            m.bridge_refunds_due = m.bridge_refunds_due.checked_add(received).ok_or(ErrorCode::Overflow)?;
+
+            emit!(BridgeDepositHeld {
+                market: market_key,
+                bridge_account: holder,
+                amount: received,
+            });
+            return Ok(());
+        }
+
+        let outcome = outcome_from_u8(m, outcome)?;
+        let position = ctx.accounts.position.as_mut().ok_or(ErrorCode::InvalidBridgeAccounts)?;
+        let user_stats = ctx.accounts.user_stats.as_mut().ok_or(ErrorCode::InvalidBridgeAccounts)?;
+        let user_stats_bump = ctx.bumps.user_stats.ok_or(ErrorCode::InvalidBridgeAccounts)?;
+        let house = match ctx.accounts.bet_mint.as_ref() {
+            Some(mint) => house_leg(
+                ctx.accounts.house.as_mut(),
+                ctx.accounts.house_vault.as_ref(),
+                &ctx.accounts.vault,
+                mint,
+                &ctx.accounts.token_program,
+            ),
+            None => None,
+        };
+        let (fee, net) = record_bet(
+            m,
+            position,
+            &mut ctx.accounts.program_stats,
+            user_stats,
+            // Bridge accounts are program PDAs and cannot open a portfolio.
+            None,
+            holder,
+            user_stats_bump,
+            outcome,
+            received,
+            0,
+            position_index,
+            now,
+            house,
+        )?;
+
+        emit!(BetPlaced {
+            market: market_key,
+            bettor: holder,
+            outcome,
+            amount: received,
+            received,
+            fee,
+            net_amount: net,
+            tag: String::new(),
+        });
+
+        Ok(())
+    }
+
+    /// Bridges a `BridgeAccount`'s payout and held deposit back to its return address.
+    pub fn bridge_out<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BridgeOut<'info>>,
+        nonce: u32,
+        wrapped: bool,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let holder = ctx.accounts.bridge_account.key();
+        let market_key = ctx.accounts.market.key();
+
+        let mut amount: u64 = 0;
+        if let Some(p) = ctx.accounts.position.as_mut() {
+            let (payout, _, _) = take_claimable(&mut ctx.accounts.market, p, ctx.accounts.vault.amount, None, now)?;
+            amount = payout;
+        }
+        if let Some(r) = ctx.accounts.bridge_refund.as_mut() {
+            let m = &mut ctx.accounts.market;
+            m.bridge_refunds_due = m.bridge_refunds_due.checked_sub(r.amount).ok_or(ErrorCode::Overflow)?;
+            amount = amount.checked_add(r.amount).ok_or(ErrorCode::Overflow)?;
+            r.amount = 0;
+        }
+        require!(amount > 0, ErrorCode::NoPayout);
+
+        let bridge_accounts = ctx.remaining_accounts;
+        let vault_key = ctx.accounts.vault.key();
+        require!(
+            bridge_accounts.iter().any(|a| a.key() == vault_key && a.is_writable),
+            ErrorCode::InvalidBridgeAccounts
+        );
+        let bump: u8 = ctx.bumps.vault_authority;
+        let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, market_key.as_ref(), &[bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+
+        // The bridge pulls from the vault through its authority-signer delegate.
+        let (bridge_signer, _) = Pubkey::find_program_address(&[b"authority_signer"], &TOKEN_BRIDGE_ID);
+        let delegate = bridge_accounts
+            .iter()
+            .find(|a| a.key() == bridge_signer)
+            .ok_or(ErrorCode::InvalidBridgeAccounts)?;
+        token_interface::approve(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Approve {
+                    to: ctx.accounts.vault.to_account_info(),
+                    delegate: delegate.clone(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                signer,
+            ),
+            amount,
+        )?;
+
+        let b = &ctx.accounts.bridge_account;
+        let mut data = Vec::with_capacity(1 + 4 + 8 + 8 + 32 + 2);
+        data.push(if wrapped { TB_TRANSFER_WRAPPED } else { TB_TRANSFER_NATIVE });
+        data.extend_from_slice(&nonce.to_le_bytes());
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.extend_from_slice(&0u64.to_le_bytes()); // relayer fee
+        data.extend_from_slice(&b.address);
+        data.extend_from_slice(&b.chain.to_le_bytes());
+        invoke_signed(
+            &bridge_instruction(bridge_accounts, &ctx.accounts.vault_authority.key(), data),
+            bridge_accounts,
+            signer,
+        )?;
+
+        emit!(BridgedOut {
+            market: market_key,
+            bridge_account: holder,
+            chain: b.chain,
+            address: b.address,
+            amount,
+        });
+
+        let caller = ctx.accounts.caller.to_account_info();
+        if let Some(p) = ctx.accounts.position.as_ref() {
+            if p.claimed && position_closable(&ctx.accounts.market, p, now)? {
+                p.close(caller.clone())?;
+            }
+        }
+        if let Some(r) = ctx.accounts.bridge_refund.as_ref() {
+            r.close(caller)?;
+        }
+
+        Ok(())
+    }
+
+    /// Authorizes `session_key` to bet from the wallet until `expires_at`, within limits.
+    pub fn create_session(
+        ctx: Context<CreateSession>,
+        session_key: Pubkey,
+        expires_at: i64,
+        max_per_bet: u64,
+        max_total: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            expires_at > now && expires_at - now <= MAX_SESSION_SECS,
+            ErrorCode::InvalidSession
+        );
+        require!(max_total > 0, ErrorCode::InvalidAmount);
+
+        let s = &mut ctx.accounts.session;
+        s.wallet = ctx.accounts.wallet.key();
+        s.session_key = session_key;
+        s.bet_mint = ctx.accounts.bet_mint.key();
+        s.expires_at = expires_at;
+        s.max_per_bet = max_per_bet;
+        s.max_total = max_total;
+        s.spent = 0;
+        s.bump = ctx.bumps.session;
+
+        token_interface::approve(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Approve {
+                    to: ctx.accounts.wallet_ata.to_account_info(),
+                    delegate: ctx.accounts.session.to_account_info(),
+                    authority: ctx.accounts.wallet.to_account_info(),
+                },
+            ),
+            max_total,
+        )?;
+
+        emit!(SessionCreated {
+            wallet: ctx.accounts.wallet.key(),
+            session_key,
+            expires_at,
+            max_per_bet,
+            max_total,
+        });
+
+        Ok(())
+    }
+
+    /// Ends a session early: revokes the token delegate and closes the PDA.
+    pub fn revoke_session(ctx: Context<RevokeSession>) -> Result<()> {
+        token_interface::revoke(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Revoke {
+                source: ctx.accounts.wallet_ata.to_account_info(),
+                authority: ctx.accounts.wallet.to_account_info(),
+            },
+        ))?;
+
+        emit!(SessionRevoked {
+            wallet: ctx.accounts.wallet.key(),
+            session_key: ctx.accounts.session.session_key,
+        });
+
+        Ok(())
+    }
+
+    /// `place_bet` signed by a session key instead of the wallet.
+    pub fn place_bet_with_session(
+        ctx: Context<PlaceBetWithSession>,
+        outcome: Outcome,
+        amount: u64,
+        position_index: u16,
+    ) -> Result<()> {
+        require!(amount >= MIN_BET_AMOUNT, ErrorCode::BetTooSmall);
+
+        let now = Clock::get()?.unix_timestamp;
+        let s = &mut ctx.accounts.session;
+        require!(now < s.expires_at, ErrorCode::SessionExpired);
+        require!(s.max_per_bet == 0 || amount <= s.max_per_bet, ErrorCode::SessionLimitExceeded);
+        let spent = s.spent.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        require!(spent <= s.max_total, ErrorCode::SessionLimitExceeded);
+        s.spent = spent;
+
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+        validate_bet_caller(m)?;
+        require!(m.attestation_issuer == Pubkey::default(), ErrorCode::AttestationRequired);
+        require!(!m.emergency_paused, ErrorCode::EmergencyPaused);
+        require!(!m.bracket_locked, ErrorCode::BracketMatchLocked);
+        require!(now >= m.open_ts, ErrorCode::BettingNotOpen);
+        require!(now < m.cutoff_ts, ErrorCode::BettingClosed);
+
+        validate_position_cap(
+            m,
+            &ctx.accounts.bet_mint,
+            &ctx.accounts.position,
+            amount,
+            ctx.accounts.price_feed.as_ref(),
+            now,
+        )?;
+
+        let wallet = ctx.accounts.wallet.key();
+        let session_key = ctx.accounts.session_key.key();
+        let bump = ctx.accounts.session.bump;
+        let seeds: &[&[u8]] = &[SESSION_SEED, wallet.as_ref(), session_key.as_ref(), &[bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+
+        let vault_before = ctx.accounts.vault.amount;
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.wallet_ata.to_account_info(),
+                    mint: ctx.accounts.bet_mint.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.session.to_account_info(),
+                },
+                signer,
+            ),
+            amount,
+            ctx.accounts.bet_mint.decimals,
+        )?;
+        ctx.accounts.vault.reload()?;
+        let received = ctx.accounts.vault.amount
+            .checked_sub(vault_before)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(received > 0, ErrorCode::InvalidAmount);
+
+        let (fee, net) = record_bet(
+            &mut ctx.accounts.market,
+            &mut ctx.accounts.position,
+            &mut ctx.accounts.program_stats,
+            &mut ctx.accounts.user_stats,
+            ctx.accounts.portfolio.as_deref_mut(),
+            wallet,
+            ctx.bumps.user_stats,
+            outcome,
+            received,
+            0,
+            position_index,
+            now,
+            house_leg(
+                ctx.accounts.house.as_mut(),
+                ctx.accounts.house_vault.as_ref(),
+                &ctx.accounts.vault,
+                &ctx.accounts.bet_mint,
+                &ctx.accounts.token_program,
+            ),
+        )?;
+
+        emit_cpi!(BetPlaced {
+            market: ctx.accounts.market.key(),
+            bettor: wallet,
+            outcome,
+            amount,
+            received,
+            fee,
+            net_amount: net,
+            tag: String::new(),
+        });
+
+        Ok(())
+    }
+
+    /// Creates the house for `bet_mint`: a PDA whose token account (its ATA) backs automatic counter-bets.
+    pub fn init_house(ctx: Context<InitHouse>, max_exposure: u64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+        let h = &mut ctx.accounts.house;
+        h.bet_mint = ctx.accounts.bet_mint.key();
+        h.vault = ctx.accounts.house_vault.key();
+        h.max_exposure = max_exposure;
+        h.exposure = 0;
+        h.bump = ctx.bumps.house;
+
+        emit!(HouseUpdated {
+            bet_mint: h.bet_mint,
+            max_exposure,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_house_max_exposure(ctx: Context<SetHouseMaxExposure>, max_exposure: u64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+        let h = &mut ctx.accounts.house;
+        h.max_exposure = max_exposure;
+
+        emit!(HouseUpdated {
+            bet_mint: h.bet_mint,
+            max_exposure,
+        });
+
+        Ok(())
+    }
+
+    /// Lets the house counter-bet the thinner side of this market up to `limit`; zero disables.
+    pub fn set_house_limit(ctx: Context<SetHouseLimit>, limit: u64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+        require!(!m.three_way, ErrorCode::InvalidOutcomeArg);
+        require!(m.shard_count == 0, ErrorCode::ShardingUnsupported);
+
+        m.house_limit = limit;
+
+        emit!(HouseLimitUpdated {
+            market: m.key(),
+            limit,
+        });
+
+        Ok(())
+    }
+
+    /// Pays the house its share of a settled market back into the house vault and releases its exposure.
+    pub fn settle_house(ctx: Context<SettleHouse>) -> Result<()> {
+        let m = &mut ctx.accounts.market;
+        require!(m.resolved, ErrorCode::NotResolved);
+        require!(!m.house_settled, ErrorCode::AlreadyClaimed);
+        require!(m.yield_deposited == 0, ErrorCode::YieldDeployed);
+
+        let staked = m.house_yes.checked_add(m.house_no).ok_or(ErrorCode::Overflow)?;
+        require!(staked > 0, ErrorCode::NoPayout);
+        let payout = house_payout(m)?;
+        let total_claimed = m.total_claimed.checked_add(payout).ok_or(ErrorCode::Overflow)?;
+        require!(total_claimed <= claimable_pool(m)?, ErrorCode::Insolvent);
+        m.total_claimed = total_claimed;
+        m.house_settled = true;
+
+        let h = &mut ctx.accounts.house;
+        h.exposure = h.exposure.saturating_sub(staked);
+
+        let market_key = m.key();
+        let bump: u8 = ctx.bumps.vault_authority;
+        let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, market_key.as_ref(), &[bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+
+        if payout > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.vault.to_account_info(),
+                        mint: ctx.accounts.bet_mint.to_account_info(),
+                        to: ctx.accounts.house_vault.to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    signer,
+                ),
+                payout,
+                ctx.accounts.bet_mint.decimals,
+            )?;
+        }
+
+        emit!(HouseSettled {
+            market: market_key,
+            staked,
+            payout,
+        });
+
+        Ok(())
+    }
+
+    /// Moves idle house funds to the owner.
+    pub fn withdraw_house(ctx: Context<WithdrawHouse>, amount: u64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        let mint_key = ctx.accounts.bet_mint.key();
+        let house_seeds: &[&[u8]] = &[HOUSE_SEED, mint_key.as_ref(), &[ctx.accounts.house.bump]];
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.house_vault.to_account_info(),
+                    mint: ctx.accounts.bet_mint.to_account_info(),
+                    to: ctx.accounts.owner_ata.to_account_info(),
+                    authority: ctx.accounts.house.to_account_info(),
+                },
+                &[house_seeds],
+            ),
+            amount,
+            ctx.accounts.bet_mint.decimals,
+        )?;
+
+        emit!(HouseWithdrawn {
+            bet_mint: mint_key,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Turns bet-mint curation on or off; while on, only approved mints are accepted.
+    pub fn set_mint_allowlist(ctx: Context<SetConfig>, enabled: bool) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+        ctx.accounts.config.mint_allowlist = enabled;
+
+        emit!(MintAllowlistUpdated { enabled });
+
+        Ok(())
+    }
+
+    pub fn approve_mint(ctx: Context<ApproveMint>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+        let a = &mut ctx.accounts.approved_mint;
+        a.mint = ctx.accounts.mint.key();
+        a.bump = ctx.bumps.approved_mint;
+
+        emit!(MintApprovalChanged {
+            mint: a.mint,
+            approved: true,
+        });
+
+        Ok(())
+    }
+
+    /// Existing markets in the mint are unaffected.
+    pub fn remove_mint(ctx: Context<RemoveMint>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+        emit!(MintApprovalChanged {
+            mint: ctx.accounts.approved_mint.mint,
+            approved: false,
+        });
+
+        Ok(())
+    }
+
+    /// Sends tokens transferred into the vault outside the program to the fee receiver.
+    pub fn skim_surplus(ctx: Context<SkimSurplus>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+
+        let m = &ctx.accounts.market;
+        require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+        require!(m.yield_deposited == 0, ErrorCode::YieldDeployed);
+        let liabilities = outstanding_liabilities(m)?;
+        let surplus = ctx.accounts.vault.amount.saturating_sub(liabilities);
+        require!(surplus > 0, ErrorCode::NoSurplus);
+
+        let market_key = m.key();
+        let bump: u8 = ctx.bumps.vault_authority;
+        let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, market_key.as_ref(), &[bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault.to_account_info(),
+                    mint: ctx.accounts.bet_mint.to_account_info(),
+                    to: ctx.accounts.fee_receiver_ata.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                signer,
+            ),
+            surplus,
+            ctx.accounts.bet_mint.decimals,
+        )?;
+
+        emit!(SurplusSkimmed {
+            market: market_key,
+            amount: surplus,
+            recipient: m.fee_receiver,
+        });
+
+        Ok(())
+    }
+
+    /// Refunds part of an insured losing position out of the insurance fund, where the premiums were pooled.
+    pub fn claim_insurance(ctx: Context<ClaimInsurance>) -> Result<()> {
+        insurance::claim_insurance(ctx)
+    }
+
+    /// Escrows a bet that any keeper can place once `outcome` pays at least `min_odds_bps`.
+    pub fn place_limit_bet(
+        ctx: Context<PlaceLimitBet>,
+        outcome: Outcome,
+        amount: u64,
+        min_odds_bps: u32,
+        keeper_tip: u64,
+        position_index: u16,
+    ) -> Result<()> {
+        require!(amount >= MIN_BET_AMOUNT, ErrorCode::BetTooSmall);
+        require!(min_odds_bps as u64 > BPS_DENOM, ErrorCode::InvalidBps);
+        let m = &ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+        require!(
+            matches!(outcome, Outcome::Yes | Outcome::No) || (outcome == Outcome::Draw && m.three_way),
+            ErrorCode::InvalidOutcomeArg
+        );
+        require!(Clock::get()?.unix_timestamp < m.cutoff_ts, ErrorCode::BettingClosed);
+
+        let deposit = amount.checked_add(keeper_tip).ok_or(ErrorCode::Overflow)?;
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.owner_ata.to_account_info(),
+                    mint: ctx.accounts.bet_mint.to_account_info(),
+                    to: ctx.accounts.escrow.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            deposit,
+            ctx.accounts.bet_mint.decimals,
+        )?;
+        ctx.accounts.escrow.reload()?;
+        // Transfer-fee mints deliver less; the shortfall comes out of the stake.
+        let escrowed = ctx.accounts.escrow.amount
+            .checked_sub(keeper_tip)
+            .ok_or(ErrorCode::InvalidAmount)?;
+
+        let l = &mut ctx.accounts.limit_bet;
+        l.owner = ctx.accounts.owner.key();
+        l.market = m.key();
+        l.outcome = outcome as u8;
+        l.amount = escrowed;
+        l.min_odds_bps = min_odds_bps;
+        l.keeper_tip = keeper_tip;
+        l.position_index = position_index;
+        l.bump = ctx.bumps.limit_bet;
+
+        emit!(LimitBetPlaced {
+            market: l.market,
+            owner: l.owner,
+            outcome: l.outcome,
+            amount: escrowed,
+            min_odds_bps,
+            keeper_tip,
+            position_index,
+        });
+
+        Ok(())
+    }
+
+    /// Keeper crank: places a resting limit bet and takes its tip.
+    pub fn execute_limit_bet(ctx: Context<ExecuteLimitBet>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+        validate_bet_caller(m)?;
+        require!(m.attestation_issuer == Pubkey::default(), ErrorCode::AttestationRequired);
+        require!(!m.emergency_paused, ErrorCode::EmergencyPaused);
+        require!(!m.bracket_locked, ErrorCode::BracketMatchLocked);
+        require!(now >= m.open_ts, ErrorCode::BettingNotOpen);
+        require!(now < m.cutoff_ts, ErrorCode::BettingClosed);
+
+        let l = &ctx.accounts.limit_bet;
+        let outcome = outcome_from_u8(m, l.outcome)?;
+        validate_position_cap(
+            m,
+            &ctx.accounts.bet_mint,
+            &ctx.accounts.position,
+            l.amount,
+            ctx.accounts.price_feed.as_ref(),
+            now,
+        )?;
+
+        let market_key = m.key();
+        let owner = l.owner;
+        let index_bytes = l.position_index.to_le_bytes();
+        let seeds: &[&[u8]] = &[LIMIT_BET_SEED, market_key.as_ref(), owner.as_ref(), &index_bytes, &[l.bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+
+        let vault_before = ctx.accounts.vault.amount;
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.escrow.to_account_info(),
+                    mint: ctx.accounts.bet_mint.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.limit_bet.to_account_info(),
+                },
+                signer,
+            ),
+            l.amount,
+            ctx.accounts.bet_mint.decimals,
+        )?;
+        ctx.accounts.vault.reload()?;
+        let received = ctx.accounts.vault.amount
+            .checked_sub(vault_before)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(received > 0, ErrorCode::InvalidAmount);
+
+        let (fee, net) = record_bet(
+            m,
+            &mut ctx.accounts.position,
+            &mut ctx.accounts.program_stats,
+            &mut ctx.accounts.user_stats,
+            ctx.accounts.portfolio.as_deref_mut(),
+            owner,
+            ctx.bumps.user_stats,
+            outcome,
+            received,
+            0,
+            l.position_index,
+            now,
+            house_leg(
+                ctx.accounts.house.as_mut(),
+                ctx.accounts.house_vault.as_ref(),
+                &ctx.accounts.vault,
+                &ctx.accounts.bet_mint,
+                &ctx.accounts.token_program,
+            ),
+        )?;
+        require!(implied_odds_bps(m, outcome)? >= l.min_odds_bps as u64, ErrorCode::OddsSlippage);
+
+        let tip = l.keeper_tip;
+        if tip > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.escrow.to_account_info(),
+                        mint: ctx.accounts.bet_mint.to_account_info(),
+                        to: ctx.accounts.keeper_ata.to_account_info(),
+                        authority: ctx.accounts.limit_bet.to_account_info(),
+                    },
+                    signer,
+                ),
+                tip,
+                ctx.accounts.bet_mint.decimals,
+            )?;
+        }
+        token_interface::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.escrow.to_account_info(),
+                destination: ctx.accounts.owner.to_account_info(),
+                authority: ctx.accounts.limit_bet.to_account_info(),
+            },
+            signer,
+        ))?;
+
+        emit_cpi!(BetPlaced {
+            market: market_key,
+            bettor: owner,
+            outcome,
+            amount: ctx.accounts.limit_bet.amount,
+            received,
+            fee,
+            net_amount: net,
+            tag: String::new(),
+        });
+        emit_cpi!(LimitBetExecuted {
+            market: market_key,
+            owner,
+            keeper: ctx.accounts.keeper.key(),
+            position_index: ctx.accounts.limit_bet.position_index,
+            keeper_tip: tip,
+        });
+
+        Ok(())
+    }
+
+    /// Refunds a resting limit bet, tip included, and closes it.
+    pub fn cancel_limit_bet(ctx: Context<CancelLimitBet>) -> Result<()> {
+        let l = &ctx.accounts.limit_bet;
+        let market_key = l.market;
+        let owner = l.owner;
+        let index_bytes = l.position_index.to_le_bytes();
+        let seeds: &[&[u8]] = &[LIMIT_BET_SEED, market_key.as_ref(), owner.as_ref(), &index_bytes, &[l.bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.escrow.to_account_info(),
+                    mint: ctx.accounts.bet_mint.to_account_info(),
+                    to: ctx.accounts.owner_ata.to_account_info(),
+                    authority: ctx.accounts.limit_bet.to_account_info(),
+                },
+                signer,
+            ),
+            ctx.accounts.escrow.amount,
+            ctx.accounts.bet_mint.decimals,
+        )?;
+        token_interface::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.escrow.to_account_info(),
+                destination: ctx.accounts.owner.to_account_info(),
+                authority: ctx.accounts.limit_bet.to_account_info(),
+            },
+            signer,
+        ))?;
+
+        emit!(LimitBetCancelled {
+            market: market_key,
+            owner,
+            position_index: l.position_index,
+            refunded: ctx.accounts.escrow.amount,
+        });
+
+        Ok(())
+    }
+
+    /// Opens a limit order book for the market's YES shares, quoted in the bet mint.
+    pub fn init_order_book(ctx: Context<InitOrderBook>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        validate_market_active(&ctx.accounts.market)?;
+        require!(ctx.accounts.market.yes_mint != Pubkey::default(), ErrorCode::WrongMint);
+        require!(
+            ctx.accounts.base_mint.decimals == ctx.accounts.bet_mint.decimals,
+            ErrorCode::WrongMint
+        );
+        require_keys_neq!(ctx.accounts.base_mint.key(), ctx.accounts.bet_mint.key(), ErrorCode::WrongMint);
+
+        let b = &mut ctx.accounts.order_book;
+        b.market = ctx.accounts.market.key();
+        b.base_mint = ctx.accounts.base_mint.key();
+        b.quote_mint = ctx.accounts.bet_mint.key();
+        b.next_order_id = 1;
+        b.bump = ctx.bumps.order_book;
+
+        emit!(OrderBookCreated {
+            market: b.market,
+            base_mint: b.base_mint,
+            quote_mint: b.quote_mint,
+        });
+
+        Ok(())
+    }
+
+    /// Rests an order on the book.
+    pub fn place_order(ctx: Context<PlaceOrder>, side: OrderSide, price_bps: u16, qty: u64) -> Result<()> {
+        require!(price_bps > 0 && (price_bps as u64) < BPS_DENOM, ErrorCode::InvalidBps);
+        require!(qty > 0, ErrorCode::InvalidAmount);
+
+        let b = &mut ctx.accounts.order_book;
+        let slot = b.orders(side).iter().position(|o| o.qty == 0).ok_or(ErrorCode::OrderBookFull)?;
+        let (locked, mint, vault) = match side {
+            OrderSide::Bid => (
+                order_cost(qty, price_bps, true)?,
+                &ctx.accounts.quote_mint,
+                &ctx.accounts.quote_vault,
+            ),
+            OrderSide::Ask => (qty, &ctx.accounts.base_mint, &ctx.accounts.base_vault),
+        };
+        require!(locked > 0, ErrorCode::InvalidAmount);
+        require_keys_eq!(ctx.accounts.owner_ata.mint, mint.key(), ErrorCode::WrongMint);
+
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.owner_ata.to_account_info(),
+                    mint: mint.to_account_info(),
+                    to: vault.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            locked,
+            mint.decimals,
+        )?;
+
+        let id = b.next_order_id;
+        b.next_order_id = id.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        b.orders_mut(side)[slot] = Order {
+            id,
+            owner: ctx.accounts.owner.key(),
+            price_bps,
+            qty,
+            locked,
+        };
+
+        emit!(OrderPlaced {
+            market: b.market,
+            owner: ctx.accounts.owner.key(),
+            order_id: id,
+            side,
+            price_bps,
+            qty,
+        });
+
+        Ok(())
+    }
+
+    /// Pulls an order and refunds whatever it still has locked.
+    pub fn cancel_order(ctx: Context<CancelOrder>, side: OrderSide, order_id: u64) -> Result<()> {
+        let b = &mut ctx.accounts.order_book;
+        let slot = b.find_order(side, order_id)?;
+        let order = b.orders(side)[slot];
+        require_keys_eq!(order.owner, ctx.accounts.owner.key(), ErrorCode::Unauthorized);
+        b.orders_mut(side)[slot] = Order::default();
+
+        let (mint, vault) = match side {
+            OrderSide::Bid => (&ctx.accounts.quote_mint, &ctx.accounts.quote_vault),
+            OrderSide::Ask => (&ctx.accounts.base_mint, &ctx.accounts.base_vault),
+        };
+        require_keys_eq!(ctx.accounts.owner_ata.mint, mint.key(), ErrorCode::WrongMint);
+        transfer_from_book(
+            b,
+            vault,
+            &ctx.accounts.owner_ata,
+            mint,
+            &ctx.accounts.token_program,
+            order.locked,
+        )?;
+
+        emit!(OrderCancelled {
+            market: b.market,
+            owner: order.owner,
+            order_id,
+            side,
+            refunded: order.locked,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless crank that crosses a bid with an ask at the ask's price.
+    pub fn match_orders(ctx: Context<MatchOrders>, bid_id: u64, ask_id: u64) -> Result<()> {
+        let b = &mut ctx.accounts.order_book;
+        let bid_slot = b.find_order(OrderSide::Bid, bid_id)?;
+        let ask_slot = b.find_order(OrderSide::Ask, ask_id)?;
+        let bid = b.bids[bid_slot];
+        let ask = b.asks[ask_slot];
+        require!(bid.price_bps >= ask.price_bps, ErrorCode::OrdersDoNotCross);
+
+        let a = &ctx.accounts;
+        require_keys_eq!(a.buyer_base_ata.owner, bid.owner, ErrorCode::Unauthorized);
+        require_keys_eq!(a.buyer_quote_ata.owner, bid.owner, ErrorCode::Unauthorized);
+        require_keys_eq!(a.seller_quote_ata.owner, ask.owner, ErrorCode::Unauthorized);
+
+        let fill = bid.qty.min(ask.qty);
+        let cost = order_cost(fill, ask.price_bps, false)?;
+        // What the bid keeps locked for its remaining quantity; the rest of
+        // its lock is released to pay for this fill.
+        let bid_left = bid.qty - fill;
+        let bid_locked = if bid_left == 0 { 0 } else { order_cost(bid_left, bid.price_bps, true)? };
+        let released = bid.locked.checked_sub(bid_locked).ok_or(ErrorCode::Overflow)?;
+        let improvement = released.checked_sub(cost).ok_or(ErrorCode::Overflow)?;
+
+        let b = &mut ctx.accounts.order_book;
+        if bid_left == 0 {
+            b.bids[bid_slot] = Order::default();
+        } else {
+            b.bids[bid_slot].qty = bid_left;
+            b.bids[bid_slot].locked = bid_locked;
+        }
+        let ask_left = ask.qty - fill;
+        if ask_left == 0 {
+            b.asks[ask_slot] = Order::default();
+        } else {
+            b.asks[ask_slot].qty = ask_left;
+            b.asks[ask_slot].locked = ask_left;
+        }
+
+        let a = &ctx.accounts;
+        let b = &a.order_book;
+        transfer_from_book(b, &a.base_vault, &a.buyer_base_ata, &a.base_mint, &a.token_program, fill)?;
+        transfer_from_book(b, &a.quote_vault, &a.seller_quote_ata, &a.quote_mint, &a.token_program, cost)?;
+        transfer_from_book(b, &a.quote_vault, &a.buyer_quote_ata, &a.quote_mint, &a.token_program, improvement)?;
+
+        emit!(OrdersMatched {
+            market: b.market,
+            bid_id,
+            ask_id,
+            price_bps: ask.price_bps,
+            qty: fill,
+        });
+
+        Ok(())
+    }
+
+    /// Creates the market's YES and NO token mints, with the vault authority as mint authority.
+    pub fn init_outcome_mints(ctx: Context<InitOutcomeMints>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+        require!(!m.three_way, ErrorCode::InvalidOutcomeArg);
+
+        m.yes_mint = ctx.accounts.yes_mint.key();
+        m.no_mint = ctx.accounts.no_mint.key();
+
+        emit!(OutcomeMintsCreated {
+            market: m.key(),
+            yes_mint: m.yes_mint,
+            no_mint: m.no_mint,
+        });
+
+        Ok(())
+    }
+
+    /// Deposits collateral and mints one YES and one NO token per unit received.
+    pub fn split_set(ctx: Context<OutcomeTokens>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        validate_market_active(&ctx.accounts.market)?;
+
+        let vault_before = ctx.accounts.vault.amount;
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.user_ata.to_account_info(),
+                    mint: ctx.accounts.bet_mint.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.bet_mint.decimals,
+        )?;
+        ctx.accounts.vault.reload()?;
+        let received = ctx.accounts.vault.amount
+            .checked_sub(vault_before)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(received > 0, ErrorCode::InvalidAmount);
+
+        let market_key = ctx.accounts.market.key();
+        let bump: u8 = ctx.bumps.vault_authority;
+        let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, market_key.as_ref(), &[bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+        for (mint, to) in [
+            (&ctx.accounts.yes_mint, &ctx.accounts.user_yes),
+            (&ctx.accounts.no_mint, &ctx.accounts.user_no),
+        ] {
+            token_interface::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    MintTo {
+                        mint: mint.to_account_info(),
+                        to: to.to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    signer,
+                ),
+                received,
+            )?;
+        }
+
+        let m = &mut ctx.accounts.market;
+        m.set_collateral = m.set_collateral.checked_add(received).ok_or(ErrorCode::Overflow)?;
+
+        emit!(SetSplit {
+            market: market_key,
+            user: ctx.accounts.user.key(),
+            amount: received,
+        });
+
+        Ok(())
+    }
+
+    /// Burns `amount` YES and `amount` NO tokens and returns the collateral.
+    pub fn merge_set(ctx: Context<OutcomeTokens>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(amount <= ctx.accounts.market.set_collateral, ErrorCode::Insolvent);
+
+        for (mint, from) in [
+            (&ctx.accounts.yes_mint, &ctx.accounts.user_yes),
+            (&ctx.accounts.no_mint, &ctx.accounts.user_no),
+        ] {
+            token_interface::burn(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Burn {
+                        mint: mint.to_account_info(),
+                        from: from.to_account_info(),
+                        authority: ctx.accounts.user.to_account_info(),
+                    },
+                ),
+                amount,
+            )?;
+        }
+        pay_set_collateral(&ctx, amount)?;
+
+        let m = &mut ctx.accounts.market;
+        m.set_collateral -= amount;
+
+        emit!(SetMerged {
+            market: m.key(),
+            user: ctx.accounts.user.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// After resolution, burns `amount` of `outcome` tokens for their share of collateral.
+    pub fn redeem_outcome(ctx: Context<OutcomeTokens>, outcome: Outcome, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        let m = &ctx.accounts.market;
+        require!(m.resolved, ErrorCode::NotResolved);
+        require!(Clock::get()?.unix_timestamp <= m.claim_deadline_ts, ErrorCode::ClaimExpired);
+
+        let (mint, from) = match outcome {
+            Outcome::Yes => (&ctx.accounts.yes_mint, &ctx.accounts.user_yes),
+            Outcome::No => (&ctx.accounts.no_mint, &ctx.accounts.user_no),
+            _ => return err!(ErrorCode::InvalidOutcomeArg),
+        };
+        let payout = amount
+            .checked_mul(outcome_token_share_bps(m, outcome))
+            .ok_or(ErrorCode::Overflow)?
+            / BPS_DENOM;
+        require!(payout <= m.set_collateral, ErrorCode::Insolvent);
+
+        token_interface::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: mint.to_account_info(),
+                    from: from.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        pay_set_collateral(&ctx, payout)?;
+
+        let m = &mut ctx.accounts.market;
+        m.set_collateral -= payout;
+
+        emit!(OutcomeRedeemed {
+            market: m.key(),
+            user: ctx.accounts.user.key(),
+            outcome,
+            burned: amount,
+            payout,
+        });
+
+        Ok(())
+    }
+
+    /// Creates the wallet's portfolio index.
+    pub fn init_portfolio(ctx: Context<InitPortfolio>) -> Result<()> {
+        let pf = &mut ctx.accounts.portfolio;
+        pf.wallet = ctx.accounts.wallet.key();
+        pf.bump = ctx.bumps.portfolio;
+        Ok(())
+    }
+
+    /// Drops `market` from the portfolio once it has no open positions there.
+    pub fn prune_portfolio(ctx: Context<PrunePortfolio>, market: Pubkey) -> Result<()> {
+        let pf = &mut ctx.accounts.portfolio;
+        let before = pf.entries.len();
+        pf.entries.retain(|e| e.market != market);
+        require!(pf.entries.len() < before, ErrorCode::WrongMarket);
+        Ok(())
+    }
+
+    /// Requires `place_bet` callers to hold an unexpired attestation from `issuer`.
+    pub fn set_attestation_issuer(ctx: Context<SetAttestationIssuer>, issuer: Pubkey) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+
+        m.attestation_issuer = issuer;
+
+        emit!(AttestationIssuerUpdated {
+            market: m.key(),
+            issuer,
+        });
+
+        Ok(())
+    }
+
+    /// Issues (or renews) `wallet`'s credential, e.g. after an off-chain KYC check.
+    pub fn issue_attestation(ctx: Context<IssueAttestation>, wallet: Pubkey, expires_at: i64) -> Result<()> {
+        require!(
+            expires_at == 0 || expires_at > Clock::get()?.unix_timestamp,
+            ErrorCode::AttestationExpired
+        );
+        let a = &mut ctx.accounts.attestation;
+        a.wallet = wallet;
+        a.issuer = ctx.accounts.issuer.key();
+        a.expires_at = expires_at;
+        a.bump = ctx.bumps.attestation;
+
+        emit!(AttestationIssued {
+            issuer: a.issuer,
+            wallet,
+            expires_at,
+        });
+
+        Ok(())
+    }
+
+    pub fn revoke_attestation(ctx: Context<RevokeAttestation>) -> Result<()> {
+        emit!(AttestationRevoked {
+            issuer: ctx.accounts.issuer.key(),
+            wallet: ctx.accounts.attestation.wallet,
+        });
+
+        Ok(())
+    }
+
+    /// Marks an empty market as the successor of `legacy_market` for `import_position`.
+    pub fn import_market(ctx: Context<ImportMarket>, legacy_market: Pubkey) -> Result<()> {
+        let caller = ctx.accounts.owner.key();
+        require!(
+            caller == owner_pubkey()
+                || is_operator_admin(&ctx.accounts.market, ctx.accounts.operator.as_ref(), caller),
+            ErrorCode::Unauthorized
+        );
+        require_keys_neq!(legacy_market, Pubkey::default(), ErrorCode::WrongMarket);
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+        require!(m.legacy_market == Pubkey::default(), ErrorCode::AlreadyImported);
+        require!(total_staked(m)? == 0, ErrorCode::MarketHasBets);
+
+        m.legacy_market = legacy_market;
+
+        emit!(MarketImported {
+            market: m.key(),
+            legacy_market,
+        });
+
+        Ok(())
+    }
+
+    /// Recreates a legacy position fee-free.
+    pub fn import_position(
+        ctx: Context<ImportPosition>,
+        wallet: Pubkey,
+        outcome: Outcome,
+        amount: u64,
+        position_index: u16,
+    ) -> Result<()> {
+        let caller = ctx.accounts.owner.key();
+        require!(
+            caller == owner_pubkey()
+                || is_operator_admin(&ctx.accounts.market, ctx.accounts.operator.as_ref(), caller),
+            ErrorCode::Unauthorized
+        );
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(m.legacy_market != Pubkey::default(), ErrorCode::NotImported);
+        accrue_rewards(m, Clock::get()?.unix_timestamp)?;
+
+        credit_bettor(
+            &mut ctx.accounts.position,
+            &mut ctx.accounts.user_stats,
+            ctx.accounts.portfolio.as_deref_mut(),
+            m.key(),
+            wallet,
+            ctx.bumps.user_stats,
+            outcome,
+            amount,
+            0,
+            amount,
+            position_index,
+        )?;
+        ctx.accounts.position.reward_debt = reward_debt(m.reward_per_stake, amount)?;
+        match outcome {
+            Outcome::Yes => m.total_yes = m.total_yes.checked_add(amount).ok_or(ErrorCode::Overflow)?,
+            Outcome::No => m.total_no = m.total_no.checked_add(amount).ok_or(ErrorCode::Overflow)?,
+            Outcome::Draw if m.three_way => {
+                m.total_draw = m.total_draw.checked_add(amount).ok_or(ErrorCode::Overflow)?
+            }
+            _ => return err!(ErrorCode::InvalidOutcomeArg),
+        }
+        let stats = &mut ctx.accounts.program_stats;
+        stats.total_bettors = stats.total_bettors.checked_add(1).ok_or(ErrorCode::Overflow)?;
+
+        require!(
+            ctx.accounts.vault.amount >= outstanding_liabilities(m)?,
+            ErrorCode::Insolvent
+        );
+
+        emit!(PositionImported {
+            market: m.key(),
+            legacy_market: m.legacy_market,
+            wallet,
+            outcome,
+            amount,
+            position_index,
+        });
+
+        Ok(())
+    }
+
+    /// Turns an empty binary market into a random one won by Yes with `threshold_bps` odds.
+    pub fn set_vrf_threshold(ctx: Context<SetVrfThreshold>, threshold_bps: u16) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(threshold_bps > 0 && (threshold_bps as u64) < BPS_DENOM, ErrorCode::InvalidBps);
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(!m.three_way && !m.multi_mint, ErrorCode::InvalidOutcomeArg);
+        require!(total_staked(m)? == 0, ErrorCode::MarketHasBets);
+
+        m.vrf_threshold_bps = threshold_bps;
+
+        emit!(VrfThresholdSet {
+            market: m.key(),
+            threshold_bps,
+        });
+
+        Ok(())
+    }
+
+    /// Commits the market to a Switchboard randomness account seeded in the previous slot.
+    pub fn request_vrf(ctx: Context<RequestVrf>) -> Result<()> {
+        let m = &mut ctx.accounts.market;
+        require!(m.vrf_threshold_bps > 0, ErrorCode::NotVrfMarket);
+        require!(!m.resolved, ErrorCode::AlreadyResolved);
+        require!(m.vrf_randomness == Pubkey::default(), ErrorCode::VrfAlreadyRequested);
+        let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp >= m.cutoff_ts && clock.unix_timestamp >= m.event_ts,
+            ErrorCode::TooEarly
+        );
+
+        let slot = clock.slot;
+        let data = ctx.accounts.randomness.try_borrow_data()?;
+        let randomness = parse_randomness(&data)?;
+        require!(randomness.seed_slot == slot.saturating_sub(1), ErrorCode::InvalidRandomness);
+        require!(randomness.reveal_slot == 0, ErrorCode::InvalidRandomness);
+        drop(data);
+
+        m.vrf_randomness = ctx.accounts.randomness.key();
+        m.vrf_commit_slot = slot;
+
+        emit!(VrfRequested {
+            market: m.key(),
+            randomness: m.vrf_randomness,
+            slot,
+        });
+
+        Ok(())
+    }
+
+    /// Settles a random market from the revealed value once the oracle has revealed it.
+    pub fn consume_vrf(ctx: Context<ConsumeVrf>) -> Result<()> {
+        let m = &mut ctx.accounts.market;
+        require!(!m.resolved, ErrorCode::AlreadyResolved);
+        require_keys_eq!(ctx.accounts.randomness.key(), m.vrf_randomness, ErrorCode::InvalidRandomness);
+
+        let data = ctx.accounts.randomness.try_borrow_data()?;
+        let randomness = parse_randomness(&data)?;
+        require!(randomness.reveal_slot > m.vrf_commit_slot, ErrorCode::RandomnessNotRevealed);
+        let value = randomness.value;
+        drop(data);
+
+        m.vrf_value = value;
+        let roll = u64::from_le_bytes(value[..8].try_into().map_err(|_| ErrorCode::InvalidRandomness)?) % BPS_DENOM;
+        let outcome = if roll < m.vrf_threshold_bps as u64 { Outcome::Yes } else { Outcome::No };
+
+        emit!(VrfConsumed {
+            market: m.key(),
+            value,
+            roll: roll as u16,
+        });
+        emit!(settle_market(m, &mut ctx.accounts.program_stats, outcome, ResolutionReason::Vrf, &ctx.accounts.instructions)?);
+
+        Ok(())
+    }
+
+    /// Creates the progressive jackpot for `bet_mint`.
+    pub fn init_jackpot(ctx: Context<InitJackpot>, fee_share_bps: u16, trigger_amount: u64) -> Result<()> {
+        jackpot::init_jackpot(ctx, fee_share_bps, trigger_amount)
+    }
+
+    pub fn set_jackpot(ctx: Context<SetJackpot>, fee_share_bps: u16, trigger_amount: u64) -> Result<()> {
+        jackpot::set_jackpot(ctx, fee_share_bps, trigger_amount)
+    }
+
+    /// Grows an insurance fund created before jackpot shares were tracked.
+    pub fn migrate_insurance_fund<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MigrateInsuranceFund<'info>>,
+    ) -> Result<()> {
+        insurance::migrate_insurance_fund(ctx)
+    }
+
+    /// Grows a jackpot created before draws were paged; the cursor starts empty.
+    pub fn migrate_jackpot(ctx: Context<MigrateJackpot>) -> Result<()> {
+        jackpot::migrate_jackpot(ctx)
+    }
+
+    /// Picks the market the next draw runs on.
+    pub fn set_jackpot_market(ctx: Context<SetJackpotMarket>) -> Result<()> {
+        jackpot::set_jackpot_market(ctx)
+    }
+
+    /// Starts a draw among the winners of the chosen market, committing to Switchboard randomness.
+    pub fn request_jackpot_draw(ctx: Context<RequestJackpotDraw>) -> Result<()> {
+        jackpot::request_jackpot_draw(ctx)
+    }
+
+    /// Pays the whole pot to the winning position drawn with probability proportional to stake.
+    pub fn settle_jackpot_draw<'info>(ctx: Context<'_, '_, 'info, 'info, SettleJackpotDraw<'info>>) -> Result<()> {
+        jackpot::settle_jackpot_draw(ctx)
+    }
+
+    /// Abandons the chosen market's draw and lets its paid positions close.
+    pub fn cancel_jackpot_draw(ctx: Context<CancelJackpotDraw>) -> Result<()> {
+        jackpot::cancel_jackpot_draw(ctx)
+    }
+
+    /// Emits `rate` reward tokens per second over the market's stake until `end_ts` or cutoff.
+    pub fn set_reward_schedule(ctx: Context<SetRewardSchedule>, rate: u64, end_ts: i64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        let now = Clock::get()?.unix_timestamp;
+        require!(rate == 0 || end_ts > now, ErrorCode::InvalidRewardSchedule);
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+        require!(m.shard_count == 0, ErrorCode::ShardsUnmerged);
+
+        accrue_rewards(m, now)?;
+        m.reward_last_ts = now;
+        m.reward_rate = rate;
+        m.reward_end_ts = end_ts;
+
+        emit!(RewardScheduleSet {
+            market: m.key(),
+            rate,
+            end_ts,
+        });
+
+        Ok(())
+    }
+
+    /// Mints the liquidity-mining rewards accrued by the caller's position and/or subsidy on a market.
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let m = &mut ctx.accounts.market;
+        accrue_rewards(m, now)?;
+        let acc = m.reward_per_stake;
+
+        let mut reward: u64 = 0;
+        if let Some(p) = ctx.accounts.position.as_mut() {
+            require_keys_eq!(p.market, m.key(), ErrorCode::WrongMarket);
+            (p.reward_debt, p.rewards_owed) = settle_rewards(acc, p.amount, p.reward_debt, p.rewards_owed)?;
+            reward = reward.checked_add(p.rewards_owed).ok_or(ErrorCode::Overflow)?;
+            p.rewards_owed = 0;
+        }
+        if let Some(s) = ctx.accounts.subsidy.as_mut() {
+            let staked = s.amount_yes.checked_add(s.amount_no).ok_or(ErrorCode::Overflow)?;
+            (s.reward_debt, s.rewards_owed) = settle_rewards(acc, staked, s.reward_debt, s.rewards_owed)?;
+            reward = reward.checked_add(s.rewards_owed).ok_or(ErrorCode::Overflow)?;
+            s.rewards_owed = 0;
+        }
+        require!(reward > 0, ErrorCode::NoPayout);
+
+        let rc = &ctx.accounts.reward_config;
+        require!(rc.enabled, ErrorCode::RewardsDisabled);
+        let seeds: &[&[u8]] = &[REWARD_MINT_AUTH_SEED, &[rc.mint_authority_bump]];
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.reward_mint.to_account_info(),
+                    to: ctx.accounts.owner_reward_ata.to_account_info(),
+                    authority: ctx.accounts.reward_mint_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            reward,
+        )?;
+
+        emit!(StakeRewardsClaimed {
+            market: m.key(),
+            owner: ctx.accounts.owner.key(),
+            amount: reward,
+        });
+
+        if let Some(p) = ctx.accounts.position.as_ref() {
+            if p.claimed && position_closable(&ctx.accounts.market, p, now)? {
+                p.close(ctx.accounts.owner.to_account_info())?;
+            }
+        }
+        // A subsidy left open by `claim_subsidy_refund` only held rewards.
+        if let Some(s) = ctx.accounts.subsidy.as_ref() {
+            if s.amount_yes == 0 && s.amount_no == 0 {
+                s.close(ctx.accounts.owner.to_account_info())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Creates an empty single-elimination bracket of `rounds` rounds.
+    pub fn create_bracket(ctx: Context<CreateBracket>, bracket_id: u64, rounds: u8) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(rounds > 0 && rounds <= MAX_BRACKET_ROUNDS, ErrorCode::InvalidRoundCount);
+
+        let b = &mut ctx.accounts.bracket;
+        b.bracket_id = bracket_id;
+        b.rounds = rounds;
+        b.champion = BRACKET_TBD;
+        b.bump = ctx.bumps.bracket;
+        b.matches = vec![BracketMatch::default(); (1usize << rounds) - 1];
+
+        emit!(BracketCreated {
+            bracket: b.key(),
+            bracket_id,
+            rounds,
+        });
+
+        Ok(())
+    }
+
+    /// Binds `market` to match `slot`.
+    pub fn set_bracket_match(ctx: Context<SetBracketMatch>, slot: u16) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        let b = &mut ctx.accounts.bracket;
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(!m.three_way && !m.multi_mint, ErrorCode::InvalidOutcomeArg);
+        require!(total_staked(m)? == 0, ErrorCode::MarketHasBets);
+        require!(m.bracket == Pubkey::default(), ErrorCode::WrongBracket);
+
+        let slot = slot as usize;
+        require!(slot < b.matches.len(), ErrorCode::InvalidRoundIndex);
+        let first_leaf = b.matches.len() / 2;
+        let bm = &mut b.matches[slot];
+        require!(bm.market == Pubkey::default(), ErrorCode::WrongBracket);
+
+        bm.market = m.key();
+        if slot >= first_leaf {
+            let k = (slot - first_leaf) as u8;
+            bm.yes_entrant = 2 * k;
+            bm.no_entrant = 2 * k + 1;
+        }
+        m.bracket = b.key();
+        m.bracket_locked = slot < first_leaf;
+
+        emit!(BracketMatchSet {
+            bracket: b.key(),
+            slot: slot as u16,
+            market: m.key(),
+            locked: m.bracket_locked,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless crank recording the winner of a resolved match.
+    pub fn advance_bracket(ctx: Context<AdvanceBracket>, slot: u16) -> Result<()> {
+        let b = &mut ctx.accounts.bracket;
+        let m = &ctx.accounts.market;
+        let slot = slot as usize;
+        require!(slot < b.matches.len(), ErrorCode::InvalidRoundIndex);
+        require_keys_eq!(b.matches[slot].market, m.key(), ErrorCode::WrongBracket);
+        require!(m.resolved, ErrorCode::NotResolved);
+        require!(b.matches[slot].winner == BRACKET_TBD, ErrorCode::AlreadyAdvanced);
+
+        let bm = b.matches[slot];
+        require!(bm.yes_entrant != BRACKET_TBD && bm.no_entrant != BRACKET_TBD, ErrorCode::BracketMatchLocked);
+        let winner = match m.winning_outcome {
+            x if x == Outcome::Yes as u8 => bm.yes_entrant,
+            x if x == Outcome::No as u8 => bm.no_entrant,
+            _ => return err!(ErrorCode::BracketMatchUndecided),
+        };
+        let unlocked = record_bracket_winner(b, slot, winner, ctx.accounts.next_market.as_mut())?;
+
+        emit!(BracketAdvanced {
+            bracket: b.key(),
+            slot: slot as u16,
+            winner,
+            unlocked_market: unlocked,
+        });
+
+        Ok(())
+    }
+
+    /// Owner override naming the `side` that advances from a match with no winner.
+    pub fn set_bracket_winner(ctx: Context<SetBracketWinner>, slot: u16, side: Outcome) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        let b = &mut ctx.accounts.bracket;
+        let m = &ctx.accounts.market;
+        let slot = slot as usize;
+        require!(slot < b.matches.len(), ErrorCode::InvalidRoundIndex);
+        require_keys_eq!(b.matches[slot].market, m.key(), ErrorCode::WrongBracket);
+        require!(m.resolved, ErrorCode::NotResolved);
+        require!(b.matches[slot].winner == BRACKET_TBD, ErrorCode::AlreadyAdvanced);
+        require!(
+            m.winning_outcome != Outcome::Yes as u8 && m.winning_outcome != Outcome::No as u8,
+            ErrorCode::BracketMatchDecided
+        );
+
+        let bm = b.matches[slot];
+        require!(bm.yes_entrant != BRACKET_TBD && bm.no_entrant != BRACKET_TBD, ErrorCode::BracketMatchLocked);
+        let winner = match side {
+            Outcome::Yes => bm.yes_entrant,
+            Outcome::No => bm.no_entrant,
+            _ => return err!(ErrorCode::InvalidOutcomeArg),
+        };
+        let unlocked = record_bracket_winner(b, slot, winner, ctx.accounts.next_market.as_mut())?;
+
+        emit!(BracketAdvanced {
+            bracket: b.key(),
+            slot: slot as u16,
+            winner,
+            unlocked_market: unlocked,
+        });
+
+        Ok(())
+    }
+
+    /// Prices season passes at `price_per_day`; a pass takes `discount_bps` off the bet fee.
+    pub fn set_season_pass_config(
+        ctx: Context<SetSeasonPassConfig>,
+        mint: Pubkey,
+        price_per_day: u64,
+        discount_bps: u16,
+        enabled: bool,
+    ) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(discount_bps as u64 <= BPS_DENOM, ErrorCode::InvalidBps);
+        require!(price_per_day > 0, ErrorCode::InvalidAmount);
+
+        let c = &mut ctx.accounts.season_pass_config;
+        c.mint = mint;
+        c.price_per_day = price_per_day;
+        c.discount_bps = discount_bps;
+        c.enabled = enabled;
+        c.bump = ctx.bumps.season_pass_config;
+
+        emit!(SeasonPassConfigUpdated {
+            mint,
+            price_per_day,
+            discount_bps,
+            enabled,
+        });
+
+        Ok(())
+    }
+
+    /// Prepays `duration` seconds (whole days) of reduced fees.
+    pub fn buy_season_pass(ctx: Context<BuySeasonPass>, duration: i64) -> Result<()> {
+        require!(duration > 0 && duration % SECONDS_PER_DAY == 0, ErrorCode::InvalidAmount);
+        let c = &ctx.accounts.season_pass_config;
+        require!(c.enabled, ErrorCode::SeasonPassDisabled);
+
+        let cost = c.price_per_day
+            .checked_mul((duration / SECONDS_PER_DAY) as u64)
+            .ok_or(ErrorCode::Overflow)?;
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.buyer_ata.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.pass_vault.to_account_info(),
+                    authority: ctx.accounts.buyer.to_account_info(),
+                },
+            ),
+            cost,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let pass = &mut ctx.accounts.season_pass;
+        pass.wallet = ctx.accounts.buyer.key();
+        pass.bump = ctx.bumps.season_pass;
+        pass.expires_at = pass.expires_at.max(now).checked_add(duration).ok_or(ErrorCode::Overflow)?;
+        pass.total_paid = pass.total_paid.checked_add(cost).ok_or(ErrorCode::Overflow)?;
+
+        let c = &mut ctx.accounts.season_pass_config;
+        c.total_collected = c.total_collected.checked_add(cost).ok_or(ErrorCode::Overflow)?;
+
+        emit!(SeasonPassBought {
+            wallet: pass.wallet,
+            cost,
+            expires_at: pass.expires_at,
+        });
+
+        Ok(())
+    }
+
+    pub fn withdraw_season_pass_revenue(ctx: Context<WithdrawSeasonPassRevenue>, amount: u64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        let bump = ctx.accounts.season_pass_config.bump;
+        let seeds: &[&[u8]] = &[SEASON_PASS_CONFIG_SEED, &[bump]];
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.pass_vault.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.recipient_ata.to_account_info(),
+                    authority: ctx.accounts.season_pass_config.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        emit!(SeasonPassRevenueWithdrawn {
+            recipient: ctx.accounts.recipient_ata.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Holds the market closed to bets until `open_ts`, so it can be listed ahead of time.
+    pub fn set_open_ts(ctx: Context<SetOpenTs>, open_ts: i64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+        let m = &mut ctx.accounts.market;
+        validate_market_active(m)?;
+        require!(total_staked(m)? == 0, ErrorCode::MarketHasBets);
+        require!(open_ts < m.cutoff_ts, ErrorCode::InvalidCutoff);
+
+        m.open_ts = open_ts;
+
+        emit!(MarketOpenScheduled {
+            market: m.key(),
+            open_ts,
+        });
+
+        Ok(())
+    }
+
+    /// Registers a whitelabel operator under namespace `operator_id`.
+    pub fn register_operator(
+        ctx: Context<RegisterOperator>,
+        operator_id: u64,
+        admin: Pubkey,
+        fee_receiver: Pubkey,
+        platform_cut_bps: u16,
+    ) -> Result<()> {
+        operator::register_operator(ctx, operator_id, admin, fee_receiver, platform_cut_bps)
+    }
+
+    /// Protocol-side controls: the platform cut and whether the operator may create new markets.
+    pub fn set_operator_platform(ctx: Context<SetOperatorPlatform>, platform_cut_bps: u16, enabled: bool) -> Result<()> {
+        operator::set_operator_platform(ctx, platform_cut_bps, enabled)
+    }
+
+    /// Operator-side config for new markets: admin key, fee receiver and bet fee.
+    pub fn update_operator(
+        ctx: Context<UpdateOperator>,
+        new_admin: Pubkey,
+        fee_receiver: Pubkey,
+        fee_bps: u16,
+    ) -> Result<()> {
+        operator::update_operator(ctx, new_admin, fee_receiver, fee_bps)
+    }
+}
+
+#[account]
+pub struct Market {
+    pub creator: Pubkey,
+    pub bet_mint: Pubkey,
+    pub vault: Pubkey,
+    pub vault_authority: Pubkey,
+    pub cutoff_ts: i64,
+    pub resolved: bool,
+    pub winning_outcome: u8,
+    pub total_yes: u64,
+    pub total_no: u64,
+    pub fees_accrued: u64,
+    pub emergency_paused: bool,
+    pub fee_receiver: Pubkey,
+    pub created_at: i64,
+    pub resolution_reason: u8,
+    pub multi_mint: bool,
+    pub mint_count: u8,
+    pub price_feed: Pubkey,
+    pub usd_bet_cap: u64,
+    pub winner_rake_bps: u16,
+    pub winner_bonus: u64,
+    pub insurance_contributed: u64,
+    pub subsidy_yes: u64,
+    pub subsidy_no: u64,
+    pub emergency_withdraw_queued_at: i64,
+    pub claim_deadline_ts: i64,
+    pub max_payout: u64,
+    pub max_multiplier_bps: u32,
+    pub clipped_excess: u64,
+    pub snipe_window_secs: i64,
+    pub snipe_threshold_bps: u16,
+    pub snipe_extension_secs: i64,
+    pub snipe_max_extension_secs: i64,
+    pub snipe_extended: i64,
+    pub event_ts: i64,
+    pub evidence_hash: [u8; 32],
+    pub evidence_uri: String,
+    pub round_count: u8,
+    pub round_results: [u8; MAX_ROUNDS],
+    pub parent_market: Pubkey,
+    pub parent_condition: u8,
+    pub oracle_feed: Pubkey,
+    pub oracle_threshold: i64,
+    /// Cumulative implied Yes bps times seconds held, for TWAPs.
+    pub twap_cumulative: u128,
+    pub twap_last_ts: i64,
+    pub twap_last_bps: u16,
+    pub resolved_ts: i64,
+    pub vest_threshold: u64,
+    pub vest_period_secs: i64,
+    pub total_claimed: u64,
+    pub subsidy_refunded: u64,
+    pub vote_end_ts: i64,
+    pub votes_yes: u64,
+    pub votes_no: u64,
+    pub resolver: Pubkey,
+    pub proposed_outcome: u8,
+    pub dispute_deadline_ts: i64,
+    pub disputer: Pubkey,
+    pub dispute_stage: u8,
+    pub vote_outcome: u8,
+    pub appeal_deadline_ts: i64,
+    pub appellant: Pubkey,
+    pub appeal_ready_ts: i64,
+    pub fee_destination: Pubkey,
+    pub merkle_refund: bool,
+    pub block_cpi: bool,
+    pub market_id: u64,
+    pub bump: u8,
+    pub keeper: Pubkey,
+    pub keeper_tip: u64,
+    pub resolvable_early: bool,
+    pub three_way: bool,
+    pub total_draw: u64,
+    pub bet_cooldown_secs: i64,
+    pub resolve_authority: Pubkey,
+    pub over_under: bool,
+    pub ou_line: i64,
+    pub ou_units: [u8; 16],
+    pub max_bet_pool_bps: u16,
+    pub bet_cap_min_pool: u64,
+    pub yield_program: Pubkey,
+    pub yield_reserve: Pubkey,
+    pub yield_to_pool: bool,
+    pub yield_deposited: u64,
+    pub yield_earned: u64,
+    pub lst_program: Pubkey,
+    pub lst_pool: Pubkey,
+    /// Pre-fee deposits; the outcome pools only hold amounts net of fees.
+    pub gross_volume: u64,
+    pub shard_count: u8,
+    pub shards_merged: u8,
+    pub report_signer: Pubkey,
+    pub vaa_emitter_chain: u16,
+    pub vaa_emitter: [u8; 32],
+    pub bridge_refunds_due: u64,
+    pub house_limit: u64,
+    pub house_yes: u64,
+    pub house_no: u64,
+    pub house_settled: bool,
+    pub split_yes_bps: u16,
+    pub premium_pool: u64,
+    pub insured_claimed: u64,
+    pub yes_mint: Pubkey,
+    pub no_mint: Pubkey,
+    pub set_collateral: u64,
+    pub attestation_issuer: Pubkey,
+    pub legacy_market: Pubkey,
+    pub vrf_threshold_bps: u16,
+    pub vrf_randomness: Pubkey,
+    pub vrf_commit_slot: u64,
+    pub vrf_value: [u8; 32],
+    /// Chosen for the jackpot draw; paid positions stay open until it settles.
+    pub jackpot_pending: bool,
+    pub reward_rate: u64,
+    pub reward_end_ts: i64,
+    pub reward_last_ts: i64,
+    pub reward_per_stake: u128,
+    pub bracket: Pubkey,
+    pub bracket_locked: bool,
+    pub open_ts: i64,
+    pub operator: Pubkey,
+    pub fee_bps: u16,
+    /// Yield shortfall fees could not cover; payouts are cut pro rata by it.
+    pub yield_loss: u64,
+    pub votes_draw: u64,
+}
+impl Market {
+    pub const LEN: usize = 8
+        + 32 + 32 + 32 + 32
+        + 8 + 1 + 1 + 8 + 8 + 8
+        + 1 + 32 + 8 + 1
+        + 1 + 1
+        + 32 + 8
+        + 2 + 8
+        + 8
+        + 8 + 8
+        + 8 + 8
+        + 8
+        + 4 + 8
+        + 8 + 2 + 8 + 8 + 8
+        + 8
+        + 32 + (4 + MAX_EVIDENCE_URI_LEN)
+        + 1 + MAX_ROUNDS
+        + 32 + 1
+        + 32 + 8
+        + 16 + 8 + 2
+        + 8 + 8 + 8
+        + 8
+        + 8
+        + 8 + 8 + 8
+        + 32 + 1 + 8 + 32
+        + 1 + 1 + 8 + 32 + 8
+        + 32
+        + 1
+        + 1
+        + 8 + 1
+        + 32 + 8
+        + 1
+        + 1 + 8
+        + 8
+        + 32
+        + 1 + 8 + 16
+        + 2 + 8
+        + 32 + 32 + 1 + 8 + 8
+        + 32 + 32
+        + 8
+        + 1 + 1
+        + 32
+        + 2 + 32
+        + 8
+        + 8 + 8 + 8 + 1
+        + 2
+        + 8 + 8
+        + 32 + 32 + 8
+        + 32
+        + 32
+        + 2 + 32 + 8 + 32
+        + 1
+        + 8 + 8 + 8 + 16
+        + 32 + 1
+        + 8
+        + 32 + 2
+        + 8
+        + 8;
+}
+
+#[account]
+pub struct Position {
+    pub owner: Pubkey,
+    pub market: Pubkey,
+    pub outcome: u8,
+    pub claimed: bool,
+    pub amount: u64,
+    /// Caller-chosen sub-account index, part of the PDA seeds.
+    pub index: u16,
+    pub amount_claimed: u64,
+    pub fees_paid: u64,
+    pub last_bet_ts: i64,
+    pub frozen: bool,
+    /// Net stake covered by bet insurance; zeroed once the cover is claimed.
+    pub insured: u64,
+    /// Liquidity-mining bookkeeping; see `accrue_rewards`.
+    pub reward_debt: u128,
+    pub rewards_owed: u64,
+}
+impl Position {
+    pub const LEN: usize = 8 + 32 + 32 + 1 + 1 + 8 + 2 + 8 + 8 + 8 + 1 + 8 + 16 + 8;
+}
+
+#[account]
+pub struct MarketMetadata {
+    pub market: Pubkey,
+    pub question: String,
+    pub category: String,
+    pub created_at: i64,
+}
+impl MarketMetadata {
+    pub const LEN: usize = 8 + 32 + (4 + 280) + (4 + 50) + 8;
+}
+
+#[account]
+pub struct ProgramStats {
+    pub authority: Pubkey,
+    pub total_markets: u64,
+    pub total_volume: u64,
+    pub total_fees: u64,
+    pub total_bettors: u64,
+    pub is_initialized: bool,
+    pub markets_resolved: u64,
+    pub fees_swept: u64,
+}
+impl ProgramStats {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 1 + 8 + 8;
+
+    /// Markets created but not yet resolved or voided.
+    pub fn open_markets(&self) -> u64 {
+        self.total_markets.saturating_sub(self.markets_resolved)
+    }
+}
+
+/// Price feed pushed by a single `reporter` and hosted by this program.
+#[account]
+pub struct OracleFeed {
+    pub reporter: Pubkey,
+    pub value: i64,
+    pub expo: i32,
+    pub updated_ts: i64,
+}
+impl OracleFeed {
+    pub const LEN: usize = 8 + 32 + 8 + 4 + 8;
+}
+
+#[account]
+pub struct MarketMint {
+    pub market: Pubkey,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub price_feed: Pubkey,
+    pub decimals: u8,
+    pub index: u8,
+    pub total_yes: u64,
+    pub total_no: u64,
+    pub fees_accrued: u64,
+    pub bump: u8,
+    /// Accounting units booked per side from this mint, fixing its claim conversion rate.
+    pub units_yes: u64,
+    pub units_no: u64,
+}
+impl MarketMint {
+    pub const LEN: usize = 8
+        + 32 + 32 + 32 + 32
+        + 1 + 1 + 8 + 8 + 8 + 1
+        + 8 + 8;
+}
+
+#[account]
+pub struct MintPosition {
+    pub owner: Pubkey,
+    pub market: Pubkey,
+    pub mint: Pubkey,
+    pub outcome: u8,
+    pub claimed_mask: u8,
+    pub deposited: u64,
+    pub units: u64,
+}
+impl MintPosition {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 1 + 1 + 8 + 8;
+}
+
+/// Lifetime activity of a single wallet across standard markets.
+#[account]
+pub struct UserStats {
+    pub owner: Pubkey,
+    pub total_wagered: u64,
+    pub total_won: u64,
+    pub markets_participated: u64,
+    pub win_count: u64,
+    pub bump: u8,
+}
+impl UserStats {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 1;
+}
+
+#[account]
+pub struct RewardConfig {
+    pub reward_mint: Pubkey,
+    pub emission_per_thousand: u64, // reward base units per 1,000 wagered base units
+    pub enabled: bool,
+    pub mint_authority_bump: u8,
+    pub bump: u8,
+}
+impl RewardConfig {
+    pub const LEN: usize = 8 + 32 + 8 + 1 + 1 + 1;
+}
+
+#[account]
+pub struct ReceiptConfig {
+    pub merkle_tree: Pubkey,
+    pub uri_base: String,
+    pub enabled: bool,
+    pub authority_bump: u8,
+    pub bump: u8,
+}
+impl ReceiptConfig {
+    pub const LEN: usize = 8 + 32 + (4 + MAX_RECEIPT_URI_BASE_LEN) + 1 + 1 + 1;
+}
+
+#[account]
+pub struct Rebate {
+    pub market: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub paid_at: i64,
+}
+impl Rebate {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8;
+}
+
+/// Ring buffer of pool totals; `head` is the next slot to write.
+#[account]
+pub struct MarketSnapshots {
+    pub market: Pubkey,
+    pub head: u16,
+    pub count: u16,
+    pub last_ts: i64,
+    pub bump: u8,
+    pub entries: [SnapshotEntry; SNAPSHOT_CAPACITY],
+}
+impl MarketSnapshots {
+    pub const LEN: usize = 8 + 32 + 2 + 2 + 8 + 1 + SnapshotEntry::LEN * SNAPSHOT_CAPACITY;
+}
+
+/// Program-wide settings the owner can tune without a redeploy.
+#[account]
+pub struct ProgramConfig {
+    pub min_duration: i64,
+    /// Delay before queued changes execute; non-zero disables the instant setters.
+    pub timelock_secs: i64,
+    /// Mint whose holders vote on disputed markets; default disables voting.
+    pub governance_mint: Pubkey,
+    /// Minimum locked tokens for a vote to be decisive.
+    pub vote_quorum: u64,
+    pub bump: u8,
+    /// When set, new markets need an `ApprovedMint` for their bet mint.
+    pub mint_allowlist: bool,
+}
+impl ProgramConfig {
+    pub const LEN: usize = 8 + 8 + 8 + 32 + 8 + 1 + 1;
+}
+
+/// A sensitive change waiting out the config timelock.
+#[account]
+pub struct PendingChange {
+    pub change_id: u64,
+    pub change: ConfigChange,
+    pub queued_at: i64,
+    pub eta: i64,
+    pub bump: u8,
+}
+impl PendingChange {
+    pub const LEN: usize = 8 + 8 + ConfigChange::LEN + 8 + 8 + 1;
+}
+
+/// Governance tokens a voter has locked on one market's dispute vote.
+#[account]
+pub struct VoteRecord {
+    pub voter: Pubkey,
+    pub market: Pubkey,
+    pub outcome: u8,
+    pub amount: u64,
+    pub bump: u8,
+}
+impl VoteRecord {
+    pub const LEN: usize = 8 + 32 + 32 + 1 + 8 + 1;
+}
+
+/// A registered resolver and its on-chain track record.
+#[account]
+pub struct ResolverEntry {
+    pub authority: Pubkey,
+    pub name: String,
+    pub uri: String,
+    pub stake: u64,
+    pub markets_resolved: u64,
+    pub disputes_lost: u64,
+    pub approved: bool,
+    pub bump: u8,
+}
+impl ResolverEntry {
+    pub const LEN: usize = 8 + 32 + (4 + MAX_RESOLVER_NAME_LEN) + (4 + MAX_EVIDENCE_URI_LEN) + 8 + 8 + 8 + 1 + 1;
+}
+
+/// Marks a (question, cutoff, mint) combination as taken.
+#[account]
+pub struct MarketGuard {
+    pub market: Pubkey,
+}
+impl MarketGuard {
+    pub const LEN: usize = 8 + 32;
+}
+
+/// Compact record of a settled market, left behind by `archive_market`.
+#[account]
+pub struct Archive {
+    pub market: Pubkey,
+    pub market_id: u64,
+    pub creator: Pubkey,
+    pub bet_mint: Pubkey,
+    pub winning_outcome: u8,
+    pub resolution_reason: u8,
+    pub total_yes: u64,
+    pub total_no: u64,
+    pub total_draw: u64,
+    pub gross_volume: u64,
+    pub total_claimed: u64,
+    pub winner_bonus: u64,
+    pub insurance_contributed: u64,
+    pub created_at: i64,
+    pub cutoff_ts: i64,
+    pub resolved_ts: i64,
+    pub archived_ts: i64,
+}
+impl Archive {
+    pub const LEN: usize = 8 + 32 + 8 + 32 + 32 + 1 + 1 + 8 * 7 + 8 * 4;
+}
+
+/// Pool shard that bets accumulate in until `merge_pool_shards` folds it in.
+#[account]
+pub struct PoolShard {
+    pub market: Pubkey,
+    pub vault: Pubkey,
+    pub index: u8,
+    pub total_yes: u64,
+    pub total_no: u64,
+    pub total_draw: u64,
+    pub fees_accrued: u64,
+    pub gross_volume: u64,
+    pub new_bettors: u64,
+    pub bump: u8,
+}
+impl PoolShard {
+    pub const LEN: usize = 8 + 32 + 32 + 1 + 8 * 6 + 1;
+}
+
+#[account]
+pub struct FeeDiscount {
+    pub key: Pubkey, // NFT mint, or collection mint when `is_collection`
+    pub discount_bps: u16,
+    pub is_collection: bool,
+    pub enabled: bool,
+    pub bump: u8,
+}
+impl FeeDiscount {
+    pub const LEN: usize = 8 + 32 + 2 + 1 + 1 + 1;
+}
+
+/// Stand-in owner for a holder on another chain; payouts are bridged back to `address`.
+#[account]
+pub struct BridgeAccount {
+    pub chain: u16,
+    pub address: [u8; 32],
+    pub bump: u8,
+}
+impl BridgeAccount {
+    pub const LEN: usize = 8 + 2 + 32 + 1;
+}
+
+/// Bridged deposit that arrived after betting closed, owed back in full.
+#[account]
+pub struct BridgeRefund {
+    pub market: Pubkey,
+    pub bridge_account: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+impl BridgeRefund {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 1;
+}
+
+/// Ephemeral betting key authorized by `wallet`; see `create_session`.
+#[account]
+pub struct Session {
+    pub wallet: Pubkey,
+    pub session_key: Pubkey,
+    pub bet_mint: Pubkey,
+    pub expires_at: i64,
+    pub max_per_bet: u64, // 0 = no per-bet limit
+    pub max_total: u64,
+    pub spent: u64,
+    pub bump: u8,
+}
+impl Session {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 1;
+}
+
+/// Per-mint counter-betting bankroll; see `set_house_limit`.
+#[account]
+pub struct House {
+    pub bet_mint: Pubkey,
+    pub vault: Pubkey,
+    pub max_exposure: u64,
+    pub exposure: u64, // staked in markets not yet settled by `settle_house`
+    pub bump: u8,
+}
+impl House {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 1;
+}
+
+#[account]
+pub struct ApprovedMint {
+    pub mint: Pubkey,
+    pub bump: u8,
+}
+impl ApprovedMint {
+    pub const LEN: usize = 8 + 32 + 1;
+}
+
+/// A bet escrowed until the market's odds reach `min_odds_bps`.
+#[account]
+pub struct LimitBet {
+    pub owner: Pubkey,
+    pub market: Pubkey,
+    pub outcome: u8,
+    pub amount: u64,
+    pub min_odds_bps: u32,
+    pub keeper_tip: u64,
+    pub position_index: u16,
+    pub bump: u8,
+}
+impl LimitBet {
+    pub const LEN: usize = 8 + 32 + 32 + 1 + 8 + 4 + 8 + 2 + 1;
+}
+
+/// Central limit order book for one market's YES shares.
+#[account]
+pub struct OrderBook {
+    pub market: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub next_order_id: u64,
+    pub bump: u8,
+    pub bids: [Order; ORDER_BOOK_DEPTH],
+    pub asks: [Order; ORDER_BOOK_DEPTH],
+}
+impl OrderBook {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 1 + 2 * Order::LEN * ORDER_BOOK_DEPTH;
+
+    fn orders(&self, side: OrderSide) -> &[Order; ORDER_BOOK_DEPTH] {
+        match side {
+            OrderSide::Bid => &self.bids,
+            OrderSide::Ask => &self.asks,
+        }
+    }
+
+    fn orders_mut(&mut self, side: OrderSide) -> &mut [Order; ORDER_BOOK_DEPTH] {
+        match side {
+            OrderSide::Bid => &mut self.bids,
+            OrderSide::Ask => &mut self.asks,
+        }
+    }
+
+    fn find_order(&self, side: OrderSide, order_id: u64) -> Result<usize> {
+        self.orders(side)
+            .iter()
+            .position(|o| o.qty > 0 && o.id == order_id)
+            .ok_or(error!(ErrorCode::OrderNotFound))
+    }
+}
+
+/// Index of the markets a wallet has open positions in.
+#[account]
+pub struct Portfolio {
+    pub wallet: Pubkey,
+    pub bump: u8,
+    pub entries: Vec<PortfolioEntry>,
+}
+impl Portfolio {
+    pub const LEN: usize = 8 + 32 + 1 + 4 + PortfolioEntry::LEN * MAX_PORTFOLIO_MARKETS;
+
+    fn track(&mut self, market: Pubkey) -> Result<()> {
+        if let Some(e) = self.entries.iter_mut().find(|e| e.market == market) {
+            e.positions = e.positions.checked_add(1).ok_or(ErrorCode::Overflow)?;
+            return Ok(());
+        }
+        require!(self.entries.len() < MAX_PORTFOLIO_MARKETS, ErrorCode::PortfolioFull);
+        self.entries.push(PortfolioEntry { market, positions: 1 });
+        Ok(())
+    }
+
+    fn untrack(&mut self, market: Pubkey) {
+        if let Some(i) = self.entries.iter().position(|e| e.market == market) {
+            if self.entries[i].positions > 1 {
+                self.entries[i].positions -= 1;
+            } else {
+                self.entries.swap_remove(i);
+            }
+        }
+    }
+}
+
+/// A compliance credential for `wallet`, created by `issuer`.
+#[account]
+pub struct Attestation {
+    pub wallet: Pubkey,
+    pub issuer: Pubkey,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+impl Attestation {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 1;
+}
+
+/// A single-elimination tournament of linked markets.
+#[account]
+pub struct Bracket {
+    pub bracket_id: u64,
+    pub rounds: u8,
+    pub champion: u8,
+    pub bump: u8,
+    pub matches: Vec<BracketMatch>,
+}
+impl Bracket {
+    pub fn space(rounds: u8) -> usize {
+        8 + 8 + 1 + 1 + 1 + 4 + BracketMatch::LEN * ((1usize << rounds) - 1)
+    }
+}
+
+#[account]
+pub struct SeasonPassConfig {
+    pub mint: Pubkey,
+    pub price_per_day: u64,
+    pub discount_bps: u16,
+    pub enabled: bool,
+    pub total_collected: u64,
+    pub bump: u8,
+}
+impl SeasonPassConfig {
+    pub const LEN: usize = 8 + 32 + 8 + 2 + 1 + 8 + 1;
+}
+
+#[account]
+pub struct SeasonPass {
+    pub wallet: Pubkey,
+    pub expires_at: i64,
+    pub total_paid: u64,
+    pub bump: u8,
+}
+impl SeasonPass {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 1;
+}
+
+#[repr(u8)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Unset = 0,
+    Yes   = 1,
+    No    = 2,
+    Void  = 3,
+    Draw  = 4, // 1X2 markets only; Yes/No read as home/away
+    Split = 5, // resolution only; see `resolve_split`
+}
+
+#[repr(u8)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ResolutionReason {
+    Unset = 0,
+    Manual = 1,
+    Oracle = 2,
+    AutoVoid = 3,
+    Consensus = 4,
+    EmergencyVoid = 5,
+    Rounds = 6,
+    ConditionFailed = 7,
+    Vote = 8,
+    Resolver = 9,
+    Appeal = 10,
+    OracleEarly = 11,
+    Push = 12,
+    Report = 13,
+    Wormhole = 14,
+    Split = 15,
+    Vrf = 16,
+}
+
+/// Where a contested market stands; stored on the market for clients.
+#[repr(u8)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DisputeStage {
+    None = 0,
+    Proposed = 1,
+    Voting = 2,
+    Tallied = 3,
+    Appealed = 4,
+    Closed = 5,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct MarketSpec {
+    pub cutoff_ts: i64,
+    pub event_ts: Option<i64>,
+    pub claim_deadline_ts: Option<i64>,
+    pub question: String,
+    pub category: String,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct ProgramInfo {
+    pub version: String,
+    pub fee_bps: u64,
+    pub bps_denom: u64,
+    pub max_bet_limit: u64,
+    pub min_bet_amount: u64,
+    pub max_market_duration: i64,
+    pub default_claim_window: i64,
+    pub max_market_mints: u8,
+    pub max_rounds: u8,
+    pub owner: Pubkey,
+    pub treasury: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SnapshotEntry {
+    pub ts: i64,
+    pub total_yes: u64,
+    pub total_no: u64,
+}
+impl SnapshotEntry {
+    pub const LEN: usize = 8 + 8 + 8;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum ConfigChange {
+    FeeReceiver { market: Pubkey, receiver: Pubkey },
+    Cutoff { market: Pubkey, cutoff_ts: i64 },
+    MinDuration { min_duration: i64 },
+    Timelock { timelock_secs: i64 },
+    WinnerRake { market: Pubkey, winner_rake_bps: u16 },
+    InsuranceShare { fee_share_bps: u16 },
+    MaxMultiplier { market: Pubkey, max_multiplier_bps: u32 },
+    EventTs { market: Pubkey, event_ts: i64 },
+    ReportSigner { market: Pubkey, report_signer: Pubkey },
+    JackpotShare { bet_mint: Pubkey, fee_share_bps: u16, trigger_amount: u64 },
+    VaaEmitter { market: Pubkey, emitter_chain: u16, emitter: [u8; 32] },
+    Governance { governance_mint: Pubkey, vote_quorum: u64 },
+    FeeDiscount { key: Pubkey, discount_bps: u16, is_collection: bool, enabled: bool },
+}
+impl ConfigChange {
+    pub const LEN: usize = 1 + 32 + 2 + 32;
+}
+
+/// The parts of a Switchboard randomness account this program reads.
+struct SbRandomness {
+    seed_slot: u64,
+    reveal_slot: u64,
+    value: [u8; 32],
+}
+
+/// The parts of a Core Bridge `PostedVAAV1` account this program reads.
+struct PostedVaa<'a> {
+    timestamp: u32,
+    emitter_chain: u16,
+    emitter: [u8; 32],
+    payload: &'a [u8],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum OrderSide {
+    Bid,
+    Ask,
+}
+
+/// `locked` is the bet mint (bids) or shares (asks) still escrowed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Order {
+    pub id: u64,
+    pub owner: Pubkey,
+    pub price_bps: u16,
+    pub qty: u64,
+    pub locked: u64,
+}
+impl Order {
+    pub const LEN: usize = 8 + 32 + 2 + 8 + 8;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct PortfolioEntry {
+    pub market: Pubkey,
+    pub positions: u16,
+}
+impl PortfolioEntry {
+    pub const LEN: usize = 32 + 2;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct BracketMatch {
+    pub market: Pubkey,
+    pub yes_entrant: u8,
+    pub no_entrant: u8,
+    pub winner: u8,
+}
+impl BracketMatch {
+    pub const LEN: usize = 32 + 1 + 1 + 1;
+}
+impl Default for BracketMatch {
+    fn default() -> Self {
+        Self {
+            market: Pubkey::default(),
+            yes_entrant: BRACKET_TBD,
+            no_entrant: BRACKET_TBD,
+            winner: BRACKET_TBD,
+        }
+    }
+}
+
+#[event]
+pub struct ProgramInitialized {
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarketCreated {
+    pub market: Pubkey,
+    pub creator: Pubkey,
+    pub cutoff_ts: i64,
+    pub bet_mint: Pubkey,
+    pub question: String,
+    pub category: String,
+    pub claim_deadline_ts: i64,
+    pub event_ts: i64,
+}
+
+#[event]
+pub struct CutoffUpdated {
+    pub market: Pubkey,
+    pub new_cutoff_ts: i64,
+}
+
+#[event]
+pub struct BetPlaced {
+    pub market: Pubkey,
+    pub bettor: Pubkey,
+    pub outcome: Outcome,
+    pub amount: u64,
+    pub received: u64,
+    pub fee: u64,
+    pub net_amount: u64,
+    /// Client-supplied attribution code; not stored on chain.
+    pub tag: String,
+}
+
+#[event]
+pub struct MarketResolved {
+    pub market: Pubkey,
+    pub winning_outcome: u8,
+    pub auto_void: bool,
+    pub total_yes: u64,
+    pub total_no: u64,
+    pub fees_accrued: u64,
+    pub resolution_reason: u8,
+    pub winner_bonus: u64,
+    pub evidence_hash: [u8; 32],
+    pub evidence_uri: String,
+}
+
+#[event]
+pub struct WinningsClaimed {
+    pub market: Pubkey,
+    pub bettor: Pubkey,
+    pub payout: u64,
+    pub position_amount: u64,
+    pub outcome: u8,
+    pub winning_outcome: u8,
+}
+
+#[event]
+pub struct FeesCollected {
+    pub market: Pubkey,
+    pub amount: u64,
+    pub recipient: Pubkey,
+}
+
+#[event]
+pub struct MarketPaused {
+    pub market: Pubkey,
+    pub paused: bool,
+}
+
+#[event]
+pub struct FeeReceiverUpdated {
+    pub market: Pubkey,
+    pub new_receiver: Pubkey,
+}
+
+#[event]
+pub struct OracleFeedCreated {
+    pub feed: Pubkey,
+    pub reporter: Pubkey,
+    pub expo: i32,
+}
+
+#[event]
+pub struct OracleFeedUpdated {
+    pub feed: Pubkey,
+    pub value: i64,
+    pub expo: i32,
+    pub updated_ts: i64,
+}
+
+#[event]
+pub struct MarketMintAdded {
+    pub market: Pubkey,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub price_feed: Pubkey,
+    pub index: u8,
+}
+
+#[event]
+pub struct MultiMintBetPlaced {
+    pub market: Pubkey,
+    pub bettor: Pubkey,
+    pub mint: Pubkey,
+    pub outcome: Outcome,
+    pub amount: u64,
+    pub received: u64,
+    pub fee: u64,
+    pub net_amount: u64,
+    pub units: u64,
+    pub price: i64,
+    pub expo: i32,
+}
+
+#[event]
+pub struct MultiMintClaimed {
+    pub market: Pubkey,
+    pub bettor: Pubkey,
+    pub deposit_mint: Pubkey,
+    pub payout_mint: Pubkey,
+    pub payout: u64,
+    pub units: u64,
+}
+
+#[event]
+pub struct UsdBetCapUpdated {
+    pub market: Pubkey,
+    pub price_feed: Pubkey,
+    pub usd_cap: u64,
+}
+
+#[event]
+pub struct RewardRateUpdated {
+    pub reward_mint: Pubkey,
+    pub emission_per_thousand: u64,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct RewardsEmitted {
+    pub market: Pubkey,
+    pub bettor: Pubkey,
+    pub wagered: u64,
+    pub reward: u64,
+}
+
+#[event]
+pub struct RebateDistributed {
+    pub market: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct WinnerRakeUpdated {
+    pub market: Pubkey,
+    pub winner_rake_bps: u16,
+}
+
+#[event]
+pub struct MarketVoided {
+    pub market: Pubkey,
+    pub before_cutoff: bool,
+    pub total_yes: u64,
+    pub total_no: u64,
+    pub timestamp: i64,
+    pub resolution_reason: u8,
+}
+
+#[event]
+pub struct EmergencyWithdrawQueued {
+    pub market: Pubkey,
+    pub executable_at: i64,
+}
+
+#[event]
+pub struct EmergencyWithdrawn {
+    pub market: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub treasury: Pubkey,
+}
+
+#[event]
+pub struct PositionRentReclaimed {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub bettor: Pubkey,
+    pub cranker: Pubkey,
+    pub cranker_tip: u64,
+    pub expired: bool,
+}
+
+#[event]
+pub struct MaxPayoutUpdated {
+    pub market: Pubkey,
+    pub max_payout: u64,
+}
+
+#[event]
+pub struct MaxMultiplierUpdated {
+    pub market: Pubkey,
+    pub max_multiplier_bps: u32,
+}
+
+#[event]
+pub struct AntiSnipeUpdated {
+    pub market: Pubkey,
+    pub window_secs: i64,
+    pub threshold_bps: u16,
+    pub extension_secs: i64,
+    pub max_extension_secs: i64,
+}
+
+#[event]
+pub struct EventTsUpdated {
+    pub market: Pubkey,
+    pub new_event_ts: i64,
+}
+
+#[event]
+pub struct ParentConditionSet {
+    pub market: Pubkey,
+    pub parent_market: Pubkey,
+    pub required_outcome: u8,
+}
+
+#[event]
+pub struct MarketCloned {
+    pub market: Pubkey,
+    pub source_market: Pubkey,
+    pub cutoff_ts: i64,
+}
+
+#[event]
+pub struct ResolutionOracleBound {
+    pub market: Pubkey,
+    pub oracle_feed: Pubkey,
+    pub threshold: i64,
+}
+
+#[event]
+pub struct MarketSnapshotted {
+    pub market: Pubkey,
+    pub ts: i64,
+    pub total_yes: u64,
+    pub total_no: u64,
+}
+
+#[event]
+pub struct MinDurationUpdated {
+    pub min_duration: i64,
+}
+
+#[event]
+pub struct PositionsMerged {
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub target_index: u16,
+    pub source_index: u16,
+    pub amount: u64,
+}
+
+#[event]
+pub struct PositionSplit {
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub source_index: u16,
+    pub new_index: u16,
+    pub amount: u64,
+}
+
+#[event]
+pub struct VestingUpdated {
+    pub market: Pubkey,
+    pub threshold: u64,
+    pub period_secs: i64,
+}
+
+#[event]
+pub struct VaultClosed {
+    pub market: Pubkey,
+    pub residual: u64,
+    pub rent_receiver: Pubkey,
+}
+
+#[event]
+pub struct ChangeQueued {
+    pub change_id: u64,
+    pub change: ConfigChange,
+    pub eta: i64,
+}
+
+#[event]
+pub struct ChangeExecuted {
+    pub change_id: u64,
+}
+
+#[event]
+pub struct ChangeCancelled {
+    pub change_id: u64,
+}
+
+#[event]
+pub struct TimelockUpdated {
+    pub timelock_secs: i64,
+}
+
+#[event]
+pub struct GovernanceUpdated {
+    pub governance_mint: Pubkey,
+    pub vote_quorum: u64,
+}
+
+#[event]
+pub struct VoteOpened {
+    pub market: Pubkey,
+    pub vote_end_ts: i64,
+}
+
+#[event]
+pub struct VoteCast {
+    pub market: Pubkey,
+    pub voter: Pubkey,
+    pub outcome: Outcome,
+    pub amount: u64,
+}
+
+#[event]
+pub struct VoteWithdrawn {
+    pub market: Pubkey,
+    pub voter: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ResolverRegistered {
+    pub authority: Pubkey,
+    pub name: String,
+    pub stake: u64,
+}
+
+#[event]
+pub struct ResolverApproved {
+    pub authority: Pubkey,
+    pub approved: bool,
+}
+
+#[event]
+pub struct ResolutionProposed {
+    pub market: Pubkey,
+    pub resolver: Pubkey,
+    pub outcome: Outcome,
+    pub dispute_deadline_ts: i64,
+}
+
+#[event]
+pub struct ResolutionDisputed {
+    pub market: Pubkey,
+    pub disputer: Pubkey,
+    pub vote_end_ts: i64,
+}
+
+#[event]
+pub struct DisputeSettled {
+    pub market: Pubkey,
+    pub resolver: Pubkey,
+    pub disputer: Pubkey,
+    pub upheld: bool,
+}
+
+#[event]
+pub struct VoteTallied {
+    pub market: Pubkey,
+    pub outcome: Outcome,
+    pub votes_yes: u64,
+    pub votes_no: u64,
+    pub votes_draw: u64,
+    pub appeal_deadline_ts: i64,
+}
+
+#[event]
+pub struct VoteAppealed {
+    pub market: Pubkey,
+    pub appellant: Pubkey,
+    pub appeal_ready_ts: i64,
+}
+
+#[event]
+pub struct AppealResolved {
+    pub market: Pubkey,
+    pub outcome: Outcome,
+    pub overturned: bool,
+}
+
+#[event]
+pub struct BetMintUpdated {
+    pub market: Pubkey,
+    pub old_mint: Pubkey,
+    pub new_mint: Pubkey,
+    pub vault: Pubkey,
+}
+
+/// Per-claim PnL summary.
+#[event]
+pub struct PnlRealized {
+    pub market: Pubkey,
+    pub bettor: Pubkey,
+    pub position_index: u16,
+    pub stake: u64,
+    pub fee_paid: u64,
+    pub payout: u64,
+    pub total_payout: u64,
+    pub pnl: i64,
+    pub closed: bool,
+}
+
+#[event]
+pub struct LegacyPositionMigrated {
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub legacy_position: Pubkey,
+    pub position: Pubkey,
+    pub index: u16,
+}
+
+#[event]
+pub struct PositionMigrated {
+    pub position: Pubkey,
+    pub old_len: u32,
+    pub new_len: u32,
+}
+
+#[event]
+pub struct ProgramStatsMigrated {
+    pub old_len: u32,
+    pub new_len: u32,
+}
+
+#[event]
+pub struct ReceiptsUpdated {
+    pub merkle_tree: Pubkey,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct CpiPolicyUpdated {
+    pub market: Pubkey,
+    pub block_cpi: bool,
+}
+
+#[event]
+pub struct KeeperTipReserved {
+    pub market: Pubkey,
+    pub keeper: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct KeeperTipPaid {
+    pub market: Pubkey,
+    pub keeper: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ResolvableEarlyUpdated {
+    pub market: Pubkey,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct ThreeWayUpdated {
+    pub market: Pubkey,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct BetCooldownUpdated {
+    pub market: Pubkey,
+    pub cooldown_secs: i64,
+}
+
+#[event]
+pub struct MarketMetaUpdated {
+    pub market: Pubkey,
+    pub question: String,
+    pub category: String,
+}
+
+#[event]
+pub struct OverUnderSet {
+    pub market: Pubkey,
+    pub line: i64,
+    pub units: [u8; 16],
+}
+
+#[event]
+pub struct BetPoolCapUpdated {
+    pub market: Pubkey,
+    pub max_bps: u16,
+    pub min_pool: u64,
+}
+
+#[event]
+pub struct MarketArchived {
+    pub market: Pubkey,
+    pub archive: Pubkey,
+    pub winning_outcome: u8,
+    pub rent_receiver: Pubkey,
+}
+
+#[event]
+pub struct PositionFreezeChanged {
+    pub market: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub frozen: bool,
+    pub reason_uri: String,
+    pub ts: i64,
+}
+
+#[event]
+pub struct PoolShardAdded {
+    pub market: Pubkey,
+    pub shard: Pubkey,
+    pub index: u8,
+    pub vault: Pubkey,
+}
+
+#[event]
+pub struct PoolShardMerged {
+    pub market: Pubkey,
+    pub index: u8,
+    pub total_yes: u64,
+    pub total_no: u64,
+    pub total_draw: u64,
+    pub fees_accrued: u64,
+    pub swept: u64,
+}
+
+#[event]
+pub struct FeeDiscountUpdated {
+    pub key: Pubkey,
+    pub discount_bps: u16,
+    pub is_collection: bool,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct PayoutsDistributed {
+    pub market: Pubkey,
+    pub caller: Pubkey,
+    pub count: u32,
+    pub total: u64,
+}
+
+#[event]
+pub struct ReportSignerUpdated {
+    pub market: Pubkey,
+    pub report_signer: Pubkey,
+}
+
+#[event]
+pub struct VaaEmitterUpdated {
+    pub market: Pubkey,
+    pub emitter_chain: u16,
+    pub emitter: [u8; 32],
+}
+
+#[event]
+pub struct BridgeDepositHeld {
+    pub market: Pubkey,
+    pub bridge_account: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct BridgedOut {
+    pub market: Pubkey,
+    pub bridge_account: Pubkey,
+    pub chain: u16,
+    pub address: [u8; 32],
+    pub amount: u64,
+}
+
+#[event]
+pub struct SessionCreated {
+    pub wallet: Pubkey,
+    pub session_key: Pubkey,
+    pub expires_at: i64,
+    pub max_per_bet: u64,
+    pub max_total: u64,
+}
+
+#[event]
+pub struct SessionRevoked {
+    pub wallet: Pubkey,
+    pub session_key: Pubkey,
+}
+
+#[event]
+pub struct HouseUpdated {
+    pub bet_mint: Pubkey,
+    pub max_exposure: u64,
+}
+
+#[event]
+pub struct HouseLimitUpdated {
+    pub market: Pubkey,
+    pub limit: u64,
+}
+
+#[event]
+pub struct HouseMatched {
+    pub market: Pubkey,
+    pub outcome: Outcome,
+    pub amount: u64,
+    pub exposure: u64,
+}
+
+#[event]
+pub struct HouseSettled {
+    pub market: Pubkey,
+    pub staked: u64,
+    pub payout: u64,
+}
+
+#[event]
+pub struct HouseWithdrawn {
+    pub bet_mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct MintAllowlistUpdated {
+    pub enabled: bool,
+}
+
+#[event]
+pub struct MintApprovalChanged {
+    pub mint: Pubkey,
+    pub approved: bool,
+}
+
+#[event]
+pub struct SurplusSkimmed {
+    pub market: Pubkey,
+    pub amount: u64,
+    pub recipient: Pubkey,
+}
+
+#[event]
+pub struct LimitBetPlaced {
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub outcome: u8,
+    pub amount: u64,
+    pub min_odds_bps: u32,
+    pub keeper_tip: u64,
+    pub position_index: u16,
+}
+
+#[event]
+pub struct LimitBetExecuted {
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub keeper: Pubkey,
+    pub position_index: u16,
+    pub keeper_tip: u64,
+}
+
+#[event]
+pub struct LimitBetCancelled {
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub position_index: u16,
+    pub refunded: u64,
+}
+
+#[event]
+pub struct OrderBookCreated {
+    pub market: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+}
+
+#[event]
+pub struct OrderPlaced {
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub order_id: u64,
+    pub side: OrderSide,
+    pub price_bps: u16,
+    pub qty: u64,
+}
+
+#[event]
+pub struct OrderCancelled {
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub order_id: u64,
+    pub side: OrderSide,
+    pub refunded: u64,
+}
+
+#[event]
+pub struct OrdersMatched {
+    pub market: Pubkey,
+    pub bid_id: u64,
+    pub ask_id: u64,
+    pub price_bps: u16,
+    pub qty: u64,
+}
+
+#[event]
+pub struct OutcomeMintsCreated {
+    pub market: Pubkey,
+    pub yes_mint: Pubkey,
+    pub no_mint: Pubkey,
+}
+
+#[event]
+pub struct SetSplit {
+    pub market: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct SetMerged {
+    pub market: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct OutcomeRedeemed {
+    pub market: Pubkey,
+    pub user: Pubkey,
+    pub outcome: Outcome,
+    pub burned: u64,
+    pub payout: u64,
+}
+
+#[event]
+pub struct AttestationIssuerUpdated {
+    pub market: Pubkey,
+    pub issuer: Pubkey,
+}
+
+#[event]
+pub struct AttestationIssued {
+    pub issuer: Pubkey,
+    pub wallet: Pubkey,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct AttestationRevoked {
+    pub issuer: Pubkey,
+    pub wallet: Pubkey,
+}
+
+#[event]
+pub struct MarketImported {
+    pub market: Pubkey,
+    pub legacy_market: Pubkey,
+}
+
+#[event]
+pub struct PositionImported {
+    pub market: Pubkey,
+    pub legacy_market: Pubkey,
+    pub wallet: Pubkey,
+    pub outcome: Outcome,
+    pub amount: u64,
+    pub position_index: u16,
+}
+
+#[event]
+pub struct VrfThresholdSet {
+    pub market: Pubkey,
+    pub threshold_bps: u16,
+}
+
+#[event]
+pub struct VrfRequested {
+    pub market: Pubkey,
+    pub randomness: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct VrfConsumed {
+    pub market: Pubkey,
+    pub value: [u8; 32],
+    pub roll: u16,
+}
+
+#[event]
+pub struct RewardScheduleSet {
+    pub market: Pubkey,
+    pub rate: u64,
+    pub end_ts: i64,
+}
+
+#[event]
+pub struct StakeRewardsClaimed {
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct BracketCreated {
+    pub bracket: Pubkey,
+    pub bracket_id: u64,
+    pub rounds: u8,
+}
+
+#[event]
+pub struct BracketMatchSet {
+    pub bracket: Pubkey,
+    pub slot: u16,
+    pub market: Pubkey,
+    pub locked: bool,
+}
+
+#[event]
+pub struct BracketAdvanced {
+    pub bracket: Pubkey,
+    pub slot: u16,
+    pub winner: u8,
+    pub unlocked_market: Pubkey, // default when nothing was unlocked
+}
+
+#[event]
+pub struct SeasonPassConfigUpdated {
+    pub mint: Pubkey,
+    pub price_per_day: u64,
+    pub discount_bps: u16,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct SeasonPassBought {
+    pub wallet: Pubkey,
+    pub cost: u64,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct SeasonPassRevenueWithdrawn {
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct MarketOpenScheduled {
+    pub market: Pubkey,
+    pub open_ts: i64,
+}
+
+#[derive(Accounts)]
+pub struct InitializeProgram<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = ProgramStats::LEN,
+        seeds = [b"program-stats"],
+        bump
+    )]
+    pub program_stats: Account<'info, ProgramStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cutoff_ts: i64, question: String)]
+pub struct CreateMarket<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+
+    /// Operator creating the market in its namespace; `owner` must be its admin.
+    #[account(mut, seeds = [OPERATOR_SEED, &operator.operator_id.to_le_bytes()], bump = operator.bump)]
+    pub operator: Option<Account<'info, Operator>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Market::LEN,
+        seeds = [MARKET_SEED, &market_seed(&program_stats, operator.as_ref())],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = MarketMetadata::LEN,
+        seeds = [b"market-metadata", market.key().as_ref()],
+        bump
+    )]
+    pub market_metadata: Account<'info, MarketMetadata>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    /// Required while the mint allowlist is on.
+    #[account(seeds = [APPROVED_MINT_SEED, bet_mint.key().as_ref()], bump = approved_mint.bump)]
+    pub approved_mint: Option<Account<'info, ApprovedMint>>,
+
+    /// Duplicate guard for (question, cutoff, mint); creating it fails if the combination is taken.
+    #[account(
+        init,
+        payer = owner,
+        space = MarketGuard::LEN,
+        seeds = [MARKET_GUARD_SEED, &market_guard_hash(&question, cutoff_ts, &bet_mint.key())],
+        bump
+    )]
+    pub market_guard: Option<Account<'info, MarketGuard>>,
+
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = bet_mint,
+        associated_token::authority = vault_authority,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Registered resolver that will propose the outcome, if any.
+    pub resolver: Option<Account<'info, ResolverEntry>>,
+
+    /// Outcome feed for oracle-resolved markets; `resolve_from_oracle` only accepts this exact account.
+    pub oracle_feed: Option<Account<'info, OracleFeed>>,
+
+    /// Partner token account that receives swept fees; defaults to the fee receiver's ATA when omitted.
+    #[account(token::mint = bet_mint, token::token_program = token_program)]
+    pub fee_destination: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCutoff<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(outcome: Outcome, amount: u64, position_index: u16)]
+pub struct PlaceBet<'info> {
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+
+    /// Pays rent for new accounts; pass the bettor again unless a platform is sponsoring it.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    // The hot path avoids PDA derivations where a stored key already pins the
+    // account: the vault is checked against `market.vault` and the bettor's
+    // token account by owner and mint rather than as an ATA.
+    #[account(mut, has_one = bet_mint, has_one = vault)]
+    pub market: Account<'info, Market>,
+
+    /// Singleton created only by `initialize_program`, so its seeds aren't re-derived.
+    #[account(mut)]
+    pub program_stats: Account<'info, ProgramStats>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = bet_mint,
+        token::authority = bettor,
+        token::token_program = token_program
+    )]
+    pub bettor_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = Position::LEN,
+        seeds = [
+            POSITION_SEED,
+            market.key().as_ref(),
+            bettor.key().as_ref(),
+            &position_index.to_le_bytes()
+        ],
+        bump
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = UserStats::LEN,
+        seeds = [USER_STATS_SEED, bettor.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Account<'info, UserStats>,
+
+    /// Required when the market caps bets in USD.
+    pub price_feed: Option<Account<'info, OracleFeed>>,
+
+    // Required on markets with a house limit.
+    #[account(mut, seeds = [HOUSE_SEED, bet_mint.key().as_ref()], bump = house.bump)]
+    pub house: Option<Account<'info, House>>,
+
+    /// Must be `house.vault`; checked in `record_bet`.
+    #[account(mut)]
+    pub house_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Required on markets gated by `set_attestation_issuer`.
+    #[account(
+        seeds = [ATTESTATION_SEED, attestation.issuer.as_ref(), bettor.key().as_ref()],
+        bump = attestation.bump
+    )]
+    pub attestation: Option<Account<'info, Attestation>>,
+
+    // Optional portfolio index; a new position is added to it when passed.
+    #[account(mut, seeds = [PORTFOLIO_SEED, bettor.key().as_ref()], bump = portfolio.bump)]
+    pub portfolio: Option<Account<'info, Portfolio>>,
+
+    // Required when the bet is insured.
+    #[account(mut, seeds = [INSURANCE_FUND_SEED], bump = insurance_fund.bump)]
+    pub insurance_fund: Option<Account<'info, InsuranceFund>>,
+
+    #[account(
+        mut,
+        associated_token::mint = bet_mint,
+        associated_token::authority = insurance_fund,
+        associated_token::token_program = token_program
+    )]
+    pub insurance_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ResolveMarket<'info> {
+    /// Admin, the market's `resolve_authority`, or its operator's admin.
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+    /// Required for conditional markets.
+    pub parent_market: Option<Account<'info, Market>>,
+    /// CHECK: instructions sysvar, pinned by address.
+    #[account(address = ix_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    /// Required when an operator admin resolves.
+    pub operator: Option<Account<'info, Operator>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimWinnings<'info> {
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+
+    #[account(mut, has_one = bet_mint)]
+    pub market: Account<'info, Market>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = bet_mint,
+        associated_token::authority = bettor,
+        associated_token::token_program = token_program
+    )]
+    pub bettor_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = bet_mint,
+        associated_token::authority = vault_authority,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Closed once fully paid out; vesting positions stay open between tranches.
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED,
+            market.key().as_ref(),
+            bettor.key().as_ref(),
+            &position.index.to_le_bytes()
+        ],
+        bump,
+        constraint = position.owner == bettor.key() @ ErrorCode::Unauthorized
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        init_if_needed,
+        payer = bettor,
+        space = UserStats::LEN,
+        seeds = [USER_STATS_SEED, bettor.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Account<'info, UserStats>,
+
+    /// Optional; the market is dropped from it when the position closes.
+    #[account(mut, seeds = [PORTFOLIO_SEED, bettor.key().as_ref()], bump = portfolio.bump)]
+    pub portfolio: Option<Account<'info, Portfolio>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct SweepFees<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut, has_one = bet_mint)]
+    pub market: Account<'info, Market>,
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA signer for the vault, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = bet_mint,
+        associated_token::authority = vault_authority,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: only used as the ATA authority; must match the market's fee receiver.
+    #[account(address = market.fee_receiver @ ErrorCode::InvalidFeeReceiver)]
+    pub fee_receiver: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = bet_mint,
+        associated_token::authority = fee_receiver,
+        associated_token::token_program = token_program
+    )]
+    pub fee_receiver_ata: InterfaceAccount<'info, TokenAccount>,
+
+    /// Required when the market has a stored `fee_destination`.
+    #[account(mut)]
+    pub fee_destination: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, seeds = [INSURANCE_FUND_SEED], bump = insurance_fund.bump)]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = bet_mint,
+        associated_token::authority = insurance_fund,
+        associated_token::token_program = token_program
+    )]
+    pub insurance_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: the bet mint's jackpot PDA; its fee share is taken whenever it exists.
+    #[account(seeds = [JACKPOT_SEED, bet_mint.key().as_ref()], bump)]
+    pub jackpot: UncheckedAccount<'info>,
+
+    /// Must be `jackpot.vault`; checked in the handler.
+    #[account(mut)]
+    pub jackpot_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Required for operator markets.
+    #[account(seeds = [OPERATOR_SEED, &operator.operator_id.to_le_bytes()], bump = operator.bump)]
+    pub operator: Option<Account<'info, Operator>>,
+
+    /// Treasury token account receiving the platform cut on operator markets.
+    #[account(mut, token::mint = bet_mint, token::token_program = token_program)]
+    pub platform_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyPause<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateFeeReceiver<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct GetPotentialPayout<'info> {
+    pub market: Account<'info, Market>,
+    pub position: Account<'info, Position>,
+}
+
+#[derive(Accounts)]
+pub struct CreateOracleFeed<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(init, payer = owner, space = OracleFeed::LEN)]
+    pub feed: Account<'info, OracleFeed>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateOracleFeed<'info> {
+    pub reporter: Signer<'info>,
+    #[account(mut)]
+    pub feed: Account<'info, OracleFeed>,
+}
+
+#[derive(Accounts)]
+pub struct AddMarketMint<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub price_feed: Account<'info, OracleFeed>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = MarketMint::LEN,
+        seeds = [MARKET_MINT_SEED, market.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub market_mint: Account<'info, MarketMint>,
+
+    /// CHECK: PDA signer for the vaults, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint,
+        associated_token::authority = vault_authority,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct PlaceBetMulti<'info> {
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_MINT_SEED, market.key().as_ref(), mint.key().as_ref()],
+        bump = market_mint.bump
+    )]
+    pub market_mint: Account<'info, MarketMint>,
+
+    #[account(address = market_mint.price_feed @ ErrorCode::WrongOracle)]
+    pub price_feed: Account<'info, OracleFeed>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = bettor,
+        associated_token::token_program = token_program
+    )]
+    pub bettor_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, address = market_mint.vault)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = bettor,
+        space = MintPosition::LEN,
+        seeds = [POSITION_SEED, market.key().as_ref(), bettor.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, MintPosition>,
+
+    /// Optional portfolio index; kept in step with the positions opened and closed here when passed.
+    #[account(mut, seeds = [PORTFOLIO_SEED, bettor.key().as_ref()], bump = portfolio.bump)]
+    pub portfolio: Option<Account<'info, Portfolio>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimMultiMint<'info> {
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, market.key().as_ref(), bettor.key().as_ref(), position.mint.as_ref()],
+        bump,
+        constraint = position.owner == bettor.key() @ ErrorCode::Unauthorized
+    )]
+    pub position: Account<'info, MintPosition>,
+
+    /// Optional portfolio index; kept in step with the positions opened and closed here when passed.
+    #[account(mut, seeds = [PORTFOLIO_SEED, bettor.key().as_ref()], bump = portfolio.bump)]
+    pub portfolio: Option<Account<'info, Portfolio>>,
+
+    pub payout_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MARKET_MINT_SEED, market.key().as_ref(), payout_mint.key().as_ref()],
+        bump = payout_market_mint.bump
+    )]
+    pub payout_market_mint: Account<'info, MarketMint>,
+
+    #[account(
+        init_if_needed,
+        payer = bettor,
+        associated_token::mint = payout_mint,
+        associated_token::authority = bettor,
+        associated_token::token_program = token_program
+    )]
+    pub bettor_ata: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: PDA signer for the vaults, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(mut, address = payout_market_mint.vault)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct SweepMintFees<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_MINT_SEED, market.key().as_ref(), mint.key().as_ref()],
+        bump = market_mint.bump
+    )]
+    pub market_mint: Account<'info, MarketMint>,
+
+    /// CHECK: PDA signer for the vaults, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(mut, address = market_mint.vault)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: only used as the ATA authority; must match the market's fee receiver.
+    #[account(address = market.fee_receiver @ ErrorCode::InvalidFeeReceiver)]
+    pub fee_receiver: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint,
+        associated_token::authority = fee_receiver,
+        associated_token::token_program = token_program
+    )]
+    pub fee_receiver_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, seeds = [INSURANCE_FUND_SEED], bump = insurance_fund.bump)]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint,
+        associated_token::authority = insurance_fund,
+        associated_token::token_program = token_program
+    )]
+    pub insurance_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Required for operator markets.
+    #[account(seeds = [OPERATOR_SEED, &operator.operator_id.to_le_bytes()], bump = operator.bump)]
+    pub operator: Option<Account<'info, Operator>>,
+
+    /// Treasury token account for the platform cut on operator markets.
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub platform_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct SetUsdBetCap<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    pub price_feed: Option<Account<'info, OracleFeed>>,
+}
+
+#[derive(Accounts)]
+pub struct InitRewards<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = RewardConfig::LEN,
+        seeds = [REWARD_CONFIG_SEED],
+        bump
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: program-held mint authority, validated by seeds.
+    #[account(seeds = [REWARD_MINT_AUTH_SEED], bump)]
+    pub reward_mint_authority: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitReceipts<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = ReceiptConfig::LEN,
+        seeds = [RECEIPT_CONFIG_SEED],
+        bump
+    )]
+    pub receipt_config: Account<'info, ReceiptConfig>,
+
+    /// CHECK: Bubblegum Merkle tree; only its address is stored.
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: program PDA that must be the tree delegate, validated by seeds.
+    #[account(seeds = [RECEIPT_AUTH_SEED], bump)]
+    pub receipt_authority: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetReceiptsEnabled<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [RECEIPT_CONFIG_SEED], bump = receipt_config.bump)]
+    pub receipt_config: Account<'info, ReceiptConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetRewardRate<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [REWARD_CONFIG_SEED], bump = reward_config.bump)]
+    pub reward_config: Account<'info, RewardConfig>,
+}
+
+#[derive(Accounts)]
+pub struct DistributeRebate<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut, has_one = bet_mint)]
+    pub market: Account<'info, Market>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA signer for the vault, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = bet_mint,
+        associated_token::authority = vault_authority,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: wallet being rebated; only used for seeds and ATA authority.
+    pub recipient: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = bet_mint,
+        token::authority = recipient,
+        token::token_program = token_program
+    )]
+    pub recipient_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Rebate::LEN,
+        seeds = [REBATE_SEED, market.key().as_ref(), recipient.key().as_ref()],
+        bump
+    )]
+    pub rebate: Account<'info, Rebate>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SetWinnerRake<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct VoidMarket<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+}
+
+#[derive(Accounts)]
+pub struct QueueEmergencyWithdraw<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyWithdraw<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub market: Account<'info, Market>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA signer for the vaults, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: hardcoded treasury wallet.
+    #[account(address = fee_wallet_pubkey())]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimPositionRent<'info> {
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    pub market: Account<'info, Market>,
+
+    /// CHECK: original bettor, receives the remaining rent.
+    #[account(mut, address = position.owner)]
+    pub bettor: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = bettor,
+        seeds = [
+            POSITION_SEED,
+            market.key().as_ref(),
+            position.owner.as_ref(),
+            &position.index.to_le_bytes()
+        ],
+        bump
+    )]
+    pub position: Account<'info, Position>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxPayout<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxMultiplier<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetAntiSnipe<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateEventTs<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetParentCondition<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    pub parent_market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct VoidIfConditionFailed<'info> {
+    pub caller: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+    pub parent_market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_cutoff_ts: i64)]
+pub struct CloneMarket<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(has_one = bet_mint)]
+    pub source_market: Account<'info, Market>,
+
+    #[account(seeds = [b"market-metadata", source_market.key().as_ref()], bump)]
+    pub source_metadata: Account<'info, MarketMetadata>,
+
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+
+    /// Operator creating the market in its namespace; `owner` must be its admin.
+    #[account(mut, seeds = [OPERATOR_SEED, &operator.operator_id.to_le_bytes()], bump = operator.bump)]
+    pub operator: Option<Account<'info, Operator>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Market::LEN,
+        seeds = [MARKET_SEED, &market_seed(&program_stats, operator.as_ref())],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = MarketMetadata::LEN,
+        seeds = [b"market-metadata", market.key().as_ref()],
+        bump
+    )]
+    pub market_metadata: Account<'info, MarketMetadata>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    /// Required while the mint allowlist is on.
+    #[account(seeds = [APPROVED_MINT_SEED, bet_mint.key().as_ref()], bump = approved_mint.bump)]
+    pub approved_mint: Option<Account<'info, ApprovedMint>>,
+
+    /// Duplicate guard for the cloned question at the new cutoff.
+    #[account(
+        init,
+        payer = owner,
+        space = MarketGuard::LEN,
+        seeds = [
+            MARKET_GUARD_SEED,
+            &market_guard_hash(&source_metadata.question, new_cutoff_ts, &bet_mint.key())
+        ],
+        bump
+    )]
+    pub market_guard: Account<'info, MarketGuard>,
+
+    /// CHECK: PDA signer for the vault, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = bet_mint,
+        associated_token::authority = vault_authority,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMarkets<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    /// Required while the mint allowlist is on.
+    #[account(seeds = [APPROVED_MINT_SEED, bet_mint.key().as_ref()], bump = approved_mint.bump)]
+    pub approved_mint: Option<Account<'info, ApprovedMint>>,
+
+    /// Operator creating the market in its namespace; `owner` must be its admin.
+    #[account(mut, seeds = [OPERATOR_SEED, &operator.operator_id.to_le_bytes()], bump = operator.bump)]
+    pub operator: Option<Account<'info, Operator>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveMany<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+    /// CHECK: instructions sysvar, pinned by address.
+    #[account(address = ix_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BindResolutionOracle<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    pub oracle_feed: Account<'info, OracleFeed>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveFromOracle<'info> {
+    pub caller: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+    #[account(address = market.oracle_feed @ ErrorCode::WrongOracle)]
+    pub oracle_feed: Account<'info, OracleFeed>,
+    /// Required for conditional markets.
+    pub parent_market: Option<Account<'info, Market>>,
+    /// CHECK: instructions sysvar, pinned by address.
+    #[account(address = ix_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetProgramInfo {}
+
+#[derive(Accounts)]
+pub struct SnapshotMarket<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub market: Account<'info, Market>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = MarketSnapshots::LEN,
+        seeds = [SNAPSHOT_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub snapshots: Box<Account<'info, MarketSnapshots>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitConfig<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = ProgramConfig::LEN,
+        seeds = [CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetConfig<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct MergePositions<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED,
+            market.key().as_ref(),
+            owner.key().as_ref(),
+            &target.index.to_le_bytes()
+        ],
+        bump,
+        constraint = target.owner == owner.key() @ ErrorCode::Unauthorized
+    )]
+    pub target: Account<'info, Position>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [
+            POSITION_SEED,
+            market.key().as_ref(),
+            owner.key().as_ref(),
+            &source.index.to_le_bytes()
+        ],
+        bump,
+        constraint = source.owner == owner.key() @ ErrorCode::Unauthorized
+    )]
+    pub source: Account<'info, Position>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, new_index: u16)]
+pub struct SplitPosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED,
+            market.key().as_ref(),
+            owner.key().as_ref(),
+            &source.index.to_le_bytes()
+        ],
+        bump,
+        constraint = source.owner == owner.key() @ ErrorCode::Unauthorized
+    )]
+    pub source: Account<'info, Position>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Position::LEN,
+        seeds = [
+            POSITION_SEED,
+            market.key().as_ref(),
+            owner.key().as_ref(),
+            &new_index.to_le_bytes()
+        ],
+        bump
+    )]
+    pub new_position: Account<'info, Position>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetVesting<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(outcome: Outcome, position_index: u16)]
+pub struct ClaimAndBet<'info> {
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+
+    #[account(mut, has_one = bet_mint)]
+    pub source_market: Box<Account<'info, Market>>,
+
+    /// CHECK: PDA signer for the source vault, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, source_market.key().as_ref()], bump)]
+    pub source_vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = bet_mint,
+        associated_token::authority = source_vault_authority,
+        associated_token::token_program = token_program
+    )]
+    pub source_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED,
+            source_market.key().as_ref(),
+            bettor.key().as_ref(),
+            &source_position.index.to_le_bytes()
+        ],
+        bump,
+        constraint = source_position.owner == bettor.key() @ ErrorCode::Unauthorized
+    )]
+    pub source_position: Box<Account<'info, Position>>,
+
+    #[account(mut, has_one = bet_mint)]
+    pub target_market: Box<Account<'info, Market>>,
+
+    /// CHECK: PDA owner of the target vault, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, target_market.key().as_ref()], bump)]
+    pub target_vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = bet_mint,
+        associated_token::authority = target_vault_authority,
+        associated_token::token_program = token_program
+    )]
+    pub target_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = bettor,
+        space = Position::LEN,
+        seeds = [
+            POSITION_SEED,
+            target_market.key().as_ref(),
+            bettor.key().as_ref(),
+            &position_index.to_le_bytes()
+        ],
+        bump
+    )]
+    pub target_position: Box<Account<'info, Position>>,
+
+    #[account(
+        init_if_needed,
+        payer = bettor,
+        space = UserStats::LEN,
+        seeds = [USER_STATS_SEED, bettor.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Box<Account<'info, UserStats>>,
+
+    /// Optional portfolio index; kept in step with the positions opened and closed here when passed.
+    #[account(mut, seeds = [PORTFOLIO_SEED, bettor.key().as_ref()], bump = portfolio.bump)]
+    pub portfolio: Option<Box<Account<'info, Portfolio>>>,
+
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: Box<Account<'info, ProgramStats>>,
+
+    /// Required when the target market caps bets in USD.
+    pub price_feed: Option<Account<'info, OracleFeed>>,
+
+    pub bet_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Required on markets with a house limit.
+    #[account(mut, seeds = [HOUSE_SEED, bet_mint.key().as_ref()], bump = house.bump)]
+    pub house: Option<Box<Account<'info, House>>>,
+
+    /// Must be `house.vault`; checked in `record_bet`.
+    #[account(mut)]
+    pub house_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseVault<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(has_one = bet_mint, has_one = vault, has_one = creator)]
+    pub market: Account<'info, Market>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA signer for the vault, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = bet_mint,
+        associated_token::authority = vault_authority,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: market creator, receives the vault rent.
+    #[account(mut)]
+    pub creator: UncheckedAccount<'info>,
+
+    /// CHECK: hardcoded treasury wallet.
+    #[account(address = fee_wallet_pubkey())]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = bet_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyInvariants<'info> {
+    #[account(has_one = vault)]
+    pub market: Account<'info, Market>,
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+#[instruction(change_id: u64)]
+pub struct QueueChange<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        init,
+        payer = owner,
+        space = PendingChange::LEN,
+        seeds = [PENDING_CHANGE_SEED, &change_id.to_le_bytes()],
+        bump
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteChange<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        close = owner,
+        seeds = [PENDING_CHANGE_SEED, &pending_change.change_id.to_le_bytes()],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+    /// Target of market-scoped changes.
+    #[account(mut)]
+    pub market: Option<Account<'info, Market>>,
+    /// Target of `InsuranceShare`; also needed for `JackpotShare`.
+    #[account(mut, seeds = [INSURANCE_FUND_SEED], bump = insurance_fund.bump)]
+    pub insurance_fund: Option<Account<'info, InsuranceFund>>,
+    /// Target of `JackpotShare`.
+    #[account(mut, seeds = [JACKPOT_SEED, jackpot.bet_mint.as_ref()], bump = jackpot.bump)]
+    pub jackpot: Option<Account<'info, Jackpot>>,
+    /// Target of `FeeDiscount`; registered beforehand with `set_fee_discount`.
+    #[account(mut, seeds = [FEE_DISCOUNT_SEED, fee_discount.key.as_ref()], bump = fee_discount.bump)]
+    pub fee_discount: Option<Account<'info, FeeDiscount>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelChange<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = owner,
+        seeds = [PENDING_CHANGE_SEED, &pending_change.change_id.to_le_bytes()],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+}
+
+#[derive(Accounts)]
+pub struct OpenVote<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(address = config.governance_mint @ ErrorCode::WrongMint)]
+    pub governance_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = governance_mint,
+        associated_token::authority = voter,
+        associated_token::token_program = token_program
+    )]
+    pub voter_ata: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: PDA owner of the vote escrow, validated by seeds.
+    #[account(seeds = [VOTE_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vote_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = voter,
+        associated_token::mint = governance_mint,
+        associated_token::authority = vote_authority,
+        associated_token::token_program = token_program
+    )]
+    pub vote_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = voter,
+        space = VoteRecord::LEN,
+        seeds = [VOTE_SEED, market.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeVote<'info> {
+    pub caller: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeDispute<'info> {
+    pub caller: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+    /// Required when the vote settles a disputed resolver proposal.
+    #[account(mut)]
+    pub resolver_entry: Option<Account<'info, ResolverEntry>>,
+    /// CHECK: receives the bond back if the dispute succeeds; checked against the market.
+    #[account(mut)]
+    pub disputer: Option<UncheckedAccount<'info>>,
+    /// CHECK: instructions sysvar, pinned by address.
+    #[account(address = ix_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Appeal<'info> {
+    #[account(mut)]
+    pub appellant: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveAppeal<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+    /// CHECK: appellant recorded on the market, refunded if the appeal succeeds.
+    #[account(mut)]
+    pub appellant: UncheckedAccount<'info>,
+    /// CHECK: hardcoded treasury wallet.
+    #[account(mut, address = fee_wallet_pubkey())]
+    pub treasury: UncheckedAccount<'info>,
+    /// Required when the appeal settles a disputed resolver proposal.
+    #[account(mut)]
+    pub resolver_entry: Option<Account<'info, ResolverEntry>>,
+    /// CHECK: receives the dispute bond back if the dispute succeeds; checked against the market.
+    #[account(mut)]
+    pub disputer: Option<UncheckedAccount<'info>>,
+    /// CHECK: instructions sysvar, pinned by address.
+    #[account(address = ix_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawVote<'info> {
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    pub market: Account<'info, Market>,
+
+    pub governance_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = governance_mint,
+        associated_token::authority = voter,
+        associated_token::token_program = token_program
+    )]
+    pub voter_ata: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: PDA signer for the vote escrow, validated by seeds.
+    #[account(seeds = [VOTE_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vote_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = governance_mint,
+        associated_token::authority = vote_authority,
+        associated_token::token_program = token_program
+    )]
+    pub vote_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = voter,
+        seeds = [VOTE_SEED, market.key().as_ref(), voter.key().as_ref()],
+        bump = vote_record.bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterResolver<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = ResolverEntry::LEN,
+        seeds = [RESOLVER_SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub entry: Account<'info, ResolverEntry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveResolver<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [RESOLVER_SEED, entry.authority.as_ref()], bump = entry.bump)]
+    pub entry: Account<'info, ResolverEntry>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeResolution<'info> {
+    pub resolver: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct DisputeResolution<'info> {
+    #[account(mut)]
+    pub disputer: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeProposal<'info> {
+    pub caller: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+    #[account(
+        mut,
+        seeds = [RESOLVER_SEED, market.resolver.as_ref()],
+        bump = resolver_entry.bump
+    )]
+    pub resolver_entry: Account<'info, ResolverEntry>,
+    /// CHECK: instructions sysvar, pinned by address.
+    #[account(address = ix_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateBetMint<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = market.bet_mint == old_mint.key() @ ErrorCode::WrongMint,
+        constraint = market.vault == old_vault.key() @ ErrorCode::WrongMint
+    )]
+    pub market: Account<'info, Market>,
+
+    pub old_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = old_mint,
+        associated_token::authority = vault_authority,
+        associated_token::token_program = old_token_program
+    )]
+    pub old_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub new_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = new_mint,
+        associated_token::authority = vault_authority,
+        associated_token::token_program = token_program
+    )]
+    pub new_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: PDA signer for the vault, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub old_token_program: Interface<'info, TokenInterface>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateProgramStats<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// CHECK: may be shorter than `ProgramStats::LEN`, so it is not deserialized; seeds and owner are checked.
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u16)]
+pub struct MigrateLegacyPosition<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub market: Account<'info, Market>,
+    /// CHECK: the position's owner; receives the legacy account's rent.
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+    /// CHECK: pre-index layout, so it is parsed by hand; seeds, owner, length and discriminator are checked.
+    #[account(mut, seeds = [POSITION_SEED, market.key().as_ref(), owner.key().as_ref()], bump)]
+    pub legacy_position: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = Position::LEN,
+        seeds = [POSITION_SEED, market.key().as_ref(), owner.key().as_ref(), &index.to_le_bytes()],
+        bump
+    )]
+    pub position: Account<'info, Position>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u16)]
+pub struct MigratePosition<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub market: Account<'info, Market>,
+    /// CHECK: the position's owner; only used for the seeds.
+    pub owner: UncheckedAccount<'info>,
+    /// CHECK: may be shorter than `Position::LEN`, so it is not deserialized; seeds, owner and discriminator are checked.
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, market.key().as_ref(), owner.key().as_ref(), &index.to_le_bytes()],
+        bump
+    )]
+    pub position: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetCpiPolicy<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimKeeperTip<'info> {
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    #[account(mut, has_one = bet_mint, has_one = vault)]
+    pub market: Account<'info, Market>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA signer for the vault, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        associated_token::mint = bet_mint,
+        associated_token::authority = keeper,
+        associated_token::token_program = token_program
+    )]
+    pub keeper_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct SetResolvableEarly<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct SetThreeWay<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct SetBetCooldown<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMarketMeta<'info> {
+    pub owner: Signer<'info>,
+    pub market: Account<'info, Market>,
+    #[account(
+        mut,
+        seeds = [b"market-metadata", market.key().as_ref()],
+        bump
+    )]
+    pub market_metadata: Account<'info, MarketMetadata>,
+}
+
+#[derive(Accounts)]
+pub struct SetOverUnder<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct SetBetPoolCap<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct ArchiveMarket<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut, close = creator, has_one = vault, has_one = creator)]
+    pub market: Account<'info, Market>,
+
+    /// CHECK: must already be closed by `close_vault`; only its lamports are read.
+    pub vault: UncheckedAccount<'info>,
+
+    /// CHECK: market creator, receives the market rent.
+    #[account(mut)]
+    pub creator: UncheckedAccount<'info>,
+
+    /// The market's duplicate guard, when it has one; closed to the creator.
+    #[account(mut, close = creator, has_one = market)]
+    pub market_guard: Option<Account<'info, MarketGuard>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Archive::LEN,
+        seeds = [ARCHIVE_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub archive: Account<'info, Archive>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPositionFrozen<'info> {
+    pub owner: Signer<'info>,
+    pub market: Account<'info, Market>,
+    #[account(mut, constraint = position.market == market.key() @ ErrorCode::WrongMarket)]
+    pub position: Account<'info, Position>,
+}
+
+#[derive(Accounts)]
+pub struct AddPoolShard<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut, has_one = bet_mint)]
+    pub market: Account<'info, Market>,
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = PoolShard::LEN,
+        seeds = [POOL_SHARD_SEED, market.key().as_ref(), &[market.shard_count]],
+        bump
+    )]
+    pub shard: Account<'info, PoolShard>,
+
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = bet_mint,
+        associated_token::authority = shard,
+        associated_token::token_program = token_program
+    )]
+    pub shard_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(outcome: Outcome, amount: u64, position_index: u16)]
+pub struct PlaceBetSharded<'info> {
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+
+    /// Read-only so bets on different shards do not contend for it.
+    #[account(has_one = bet_mint)]
+    pub market: Account<'info, Market>,
+
+    #[account(mut, has_one = market @ ErrorCode::WrongMarket)]
+    pub shard: Account<'info, PoolShard>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = bet_mint,
+        token::authority = bettor,
+        token::token_program = token_program
+    )]
+    pub bettor_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, address = shard.vault @ ErrorCode::WrongMarket)]
+    pub shard_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = bettor,
+        space = Position::LEN,
+        seeds = [
+            POSITION_SEED,
+            market.key().as_ref(),
+            bettor.key().as_ref(),
+            &position_index.to_le_bytes()
+        ],
+        bump
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        init_if_needed,
+        payer = bettor,
+        space = UserStats::LEN,
+        seeds = [USER_STATS_SEED, bettor.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Account<'info, UserStats>,
+
+    /// Optional portfolio index; kept in step with the positions opened and closed here when passed.
+    #[account(mut, seeds = [PORTFOLIO_SEED, bettor.key().as_ref()], bump = portfolio.bump)]
+    pub portfolio: Option<Account<'info, Portfolio>>,
+
+    /// Required when the market caps bets in USD.
+    pub price_feed: Option<Account<'info, OracleFeed>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct MergePoolShards<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(mut, has_one = bet_mint, has_one = vault)]
+    pub market: Account<'info, Market>,
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(key: Pubkey)]
+pub struct SetFeeDiscount<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = FeeDiscount::LEN,
+        seeds = [FEE_DISCOUNT_SEED, key.as_ref()],
+        bump
+    )]
+    pub fee_discount: Account<'info, FeeDiscount>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DistributePayouts<'info> {
+    /// Admin or the market's keeper.
+    pub caller: Signer<'info>,
+
+    #[account(mut, has_one = bet_mint, has_one = vault)]
+    pub market: Account<'info, Market>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA signer for the vault, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SetReportSigner<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveFromReport<'info> {
+    pub caller: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+    /// Required for conditional markets.
+    pub parent_market: Option<Account<'info, Market>>,
+    /// CHECK: instructions sysvar, pinned by address.
+    #[account(address = ix_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetVaaEmitter<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveFromVaa<'info> {
+    pub caller: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+    /// CHECK: posted VAA, owned by the Core Bridge which verifies guardian signatures.
+    #[account(owner = WORMHOLE_CORE_BRIDGE_ID @ ErrorCode::InvalidVaa)]
+    pub posted_vaa: UncheckedAccount<'info>,
+    /// Required for conditional markets.
+    pub parent_market: Option<Account<'info, Market>>,
+    /// CHECK: instructions sysvar, pinned by address.
+    #[account(address = ix_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(return_chain: u16, return_address: [u8; 32], position_index: u16)]
+pub struct BridgeDeposit<'info> {
+    /// Relayer; pays rent and the bridge's redemption costs.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, has_one = vault)]
+    pub market: Account<'info, Market>,
+
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+
+    /// CHECK: PDA signer for the vault and the transfer's redeemer.
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: posted VAA of the transfer; ownership proves guardian approval.
+    #[account(owner = WORMHOLE_CORE_BRIDGE_ID @ ErrorCode::InvalidVaa)]
+    pub posted_vaa: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = BridgeAccount::LEN,
+        seeds = [BRIDGE_ACCOUNT_SEED, &return_chain.to_be_bytes(), return_address.as_ref()],
+        bump
+    )]
+    pub bridge_account: Account<'info, BridgeAccount>,
+
+    /// Required when the deposit is bet.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = Position::LEN,
+        seeds = [
+            POSITION_SEED,
+            market.key().as_ref(),
+            bridge_account.key().as_ref(),
+            &position_index.to_le_bytes()
+        ],
+        bump
+    )]
+    pub position: Option<Account<'info, Position>>,
+
+    /// Required when the deposit is bet.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = UserStats::LEN,
+        seeds = [USER_STATS_SEED, bridge_account.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Option<Account<'info, UserStats>>,
+
+    /// Required when the deposit is held for refund.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = BridgeRefund::LEN,
+        seeds = [BRIDGE_REFUND_SEED, market.key().as_ref(), bridge_account.key().as_ref()],
+        bump
+    )]
+    pub bridge_refund: Option<Account<'info, BridgeRefund>>,
+
+    /// Required with `house` on markets with a house limit.
+    #[account(address = market.bet_mint)]
+    pub bet_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Required on markets with a house limit.
+    #[account(mut, seeds = [HOUSE_SEED, market.bet_mint.as_ref()], bump = house.bump)]
+    pub house: Option<Account<'info, House>>,
+
+    /// Must be `house.vault`; checked in `record_bet`.
+    #[account(mut)]
+    pub house_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct BridgeOut<'info> {
+    /// Pays the Wormhole message fee and receives closed accounts' rent.
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    #[account(mut, has_one = vault)]
+    pub market: Account<'info, Market>,
+
+    pub bridge_account: Account<'info, BridgeAccount>,
+
+    /// CHECK: PDA signer for the vault, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, constraint = position.owner == bridge_account.key() @ ErrorCode::Unauthorized)]
+    pub position: Option<Account<'info, Position>>,
+
+    #[account(
+        mut,
+        has_one = market,
+        has_one = bridge_account,
+        seeds = [BRIDGE_REFUND_SEED, market.key().as_ref(), bridge_account.key().as_ref()],
+        bump = bridge_refund.bump
+    )]
+    pub bridge_refund: Option<Account<'info, BridgeRefund>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_key: Pubkey)]
+pub struct CreateSession<'info> {
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(
+        init,
+        payer = wallet,
+        space = Session::LEN,
+        seeds = [SESSION_SEED, wallet.key().as_ref(), session_key.as_ref()],
+        bump
+    )]
+    pub session: Account<'info, Session>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = bet_mint,
+        token::authority = wallet,
+        token::token_program = token_program
+    )]
+    pub wallet_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSession<'info> {
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(mut, has_one = wallet, close = wallet)]
+    pub session: Account<'info, Session>,
+
+    #[account(
+        mut,
+        token::mint = session.bet_mint,
+        token::authority = wallet,
+        token::token_program = token_program
+    )]
+    pub wallet_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(outcome: Outcome, amount: u64, position_index: u16)]
+pub struct PlaceBetWithSession<'info> {
+    /// Ephemeral key; also pays rent for a new position or user stats.
+    #[account(mut)]
+    pub session_key: Signer<'info>,
+
+    /// CHECK: the wallet that authorized the session; pinned by `session`.
+    pub wallet: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = wallet,
+        has_one = session_key,
+        has_one = bet_mint,
+        seeds = [SESSION_SEED, wallet.key().as_ref(), session_key.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Account<'info, Session>,
+
+    #[account(mut, has_one = bet_mint, has_one = vault)]
+    pub market: Account<'info, Market>,
+
+    /// Singleton: `initialize_program` is the only way to create one.
+    #[account(mut)]
+    pub program_stats: Account<'info, ProgramStats>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = bet_mint,
+        token::authority = wallet,
+        token::token_program = token_program
+    )]
+    pub wallet_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = session_key,
+        space = Position::LEN,
+        seeds = [
+            POSITION_SEED,
+            market.key().as_ref(),
+            wallet.key().as_ref(),
+            &position_index.to_le_bytes()
+        ],
+        bump
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        init_if_needed,
+        payer = session_key,
+        space = UserStats::LEN,
+        seeds = [USER_STATS_SEED, wallet.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Account<'info, UserStats>,
+
+    /// Optional portfolio index; kept in step with the positions opened and closed here when passed.
+    #[account(mut, seeds = [PORTFOLIO_SEED, wallet.key().as_ref()], bump = portfolio.bump)]
+    pub portfolio: Option<Account<'info, Portfolio>>,
+
+    /// Required when the market caps bets in USD.
+    pub price_feed: Option<Account<'info, OracleFeed>>,
+
+    /// Required on markets with a house limit.
+    #[account(mut, seeds = [HOUSE_SEED, bet_mint.key().as_ref()], bump = house.bump)]
+    pub house: Option<Account<'info, House>>,
+
+    /// Must be `house.vault`; checked in `record_bet`.
+    #[account(mut)]
+    pub house_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitHouse<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = House::LEN,
+        seeds = [HOUSE_SEED, bet_mint.key().as_ref()],
+        bump
+    )]
+    pub house: Account<'info, House>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = bet_mint,
+        associated_token::authority = house,
+        associated_token::token_program = token_program
+    )]
+    pub house_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct SetHouseMaxExposure<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub house: Account<'info, House>,
+}
+
+#[derive(Accounts)]
+pub struct SetHouseLimit<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct SettleHouse<'info> {
+    #[account(mut, has_one = bet_mint, has_one = vault)]
+    pub market: Account<'info, Market>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, has_one = bet_mint, seeds = [HOUSE_SEED, bet_mint.key().as_ref()], bump = house.bump)]
+    pub house: Account<'info, House>,
+
+    #[account(mut, address = house.vault)]
+    pub house_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: PDA signer for the vault, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawHouse<'info> {
+    pub owner: Signer<'info>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(has_one = bet_mint, seeds = [HOUSE_SEED, bet_mint.key().as_ref()], bump = house.bump)]
+    pub house: Account<'info, House>,
+
+    #[account(mut, address = house.vault)]
+    pub house_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = bet_mint,
+        token::authority = owner,
+        token::token_program = token_program
+    )]
+    pub owner_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveMint<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = ApprovedMint::LEN,
+        seeds = [APPROVED_MINT_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub approved_mint: Account<'info, ApprovedMint>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveMint<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut, close = owner)]
+    pub approved_mint: Account<'info, ApprovedMint>,
+}
+
+#[derive(Accounts)]
+pub struct SkimSurplus<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(has_one = bet_mint, has_one = vault)]
+    pub market: Account<'info, Market>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA signer for the vault, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: only used as the ATA authority; must match the market's fee receiver.
+    #[account(address = market.fee_receiver @ ErrorCode::InvalidFeeReceiver)]
+    pub fee_receiver: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = bet_mint,
+        associated_token::authority = fee_receiver,
+        associated_token::token_program = token_program
+    )]
+    pub fee_receiver_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+#[instruction(outcome: Outcome, amount: u64, min_odds_bps: u32, keeper_tip: u64, position_index: u16)]
+pub struct PlaceLimitBet<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(has_one = bet_mint)]
+    pub market: Account<'info, Market>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = bet_mint,
+        token::authority = owner,
+        token::token_program = token_program
+    )]
+    pub owner_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = LimitBet::LEN,
+        seeds = [LIMIT_BET_SEED, market.key().as_ref(), owner.key().as_ref(), &position_index.to_le_bytes()],
+        bump
+    )]
+    pub limit_bet: Account<'info, LimitBet>,
+
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = bet_mint,
+        associated_token::authority = limit_bet,
+        associated_token::token_program = token_program
+    )]
+    pub escrow: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteLimitBet<'info> {
+    /// Anyone; pays rent for a new position or user stats and earns the tip.
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    /// CHECK: the limit bet's owner, pinned by `limit_bet`; receives its rent.
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        has_one = market,
+        seeds = [LIMIT_BET_SEED, market.key().as_ref(), owner.key().as_ref(), &limit_bet.position_index.to_le_bytes()],
+        bump = limit_bet.bump
+    )]
+    pub limit_bet: Account<'info, LimitBet>,
+
+    #[account(mut, has_one = bet_mint, has_one = vault)]
+    pub market: Account<'info, Market>,
+
+    /// Singleton: `initialize_program` is the only way to create one.
+    #[account(mut)]
+    pub program_stats: Account<'info, ProgramStats>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = bet_mint,
+        associated_token::authority = limit_bet,
+        associated_token::token_program = token_program
+    )]
+    pub escrow: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, token::mint = bet_mint, token::token_program = token_program)]
+    pub keeper_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        space = Position::LEN,
+        seeds = [
+            POSITION_SEED,
+            market.key().as_ref(),
+            owner.key().as_ref(),
+            &limit_bet.position_index.to_le_bytes()
+        ],
+        bump
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        space = UserStats::LEN,
+        seeds = [USER_STATS_SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Account<'info, UserStats>,
+
+    /// Optional portfolio index; kept in step with the positions opened and closed here when passed.
+    #[account(mut, seeds = [PORTFOLIO_SEED, owner.key().as_ref()], bump = portfolio.bump)]
+    pub portfolio: Option<Account<'info, Portfolio>>,
+
+    /// Required when the market caps bets in USD.
+    pub price_feed: Option<Account<'info, OracleFeed>>,
+
+    /// Required on markets with a house limit.
+    #[account(mut, seeds = [HOUSE_SEED, bet_mint.key().as_ref()], bump = house.bump)]
+    pub house: Option<Account<'info, House>>,
+
+    /// Must be `house.vault`; checked in `record_bet`.
+    #[account(mut)]
+    pub house_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CancelLimitBet<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        seeds = [LIMIT_BET_SEED, limit_bet.market.as_ref(), owner.key().as_ref(), &limit_bet.position_index.to_le_bytes()],
+        bump = limit_bet.bump
+    )]
+    pub limit_bet: Account<'info, LimitBet>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = bet_mint,
+        associated_token::authority = limit_bet,
+        associated_token::token_program = token_program
+    )]
+    pub escrow: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = bet_mint,
+        token::authority = owner,
+        token::token_program = token_program
+    )]
+    pub owner_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitOrderBook<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(has_one = bet_mint)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = OrderBook::LEN,
+        seeds = [ORDER_BOOK_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    /// The market's YES outcome mint.
+    #[account(address = market.yes_mint @ ErrorCode::WrongMint, mint::token_program = token_program)]
+    pub base_mint: InterfaceAccount<'info, Mint>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = base_mint,
+        associated_token::authority = order_book,
+        associated_token::token_program = token_program
+    )]
+    pub base_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = bet_mint,
+        associated_token::authority = order_book,
+        associated_token::token_program = token_program
+    )]
+    pub quote_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct PlaceOrder<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = base_mint,
+        has_one = quote_mint,
+        seeds = [ORDER_BOOK_SEED, order_book.market.as_ref()],
+        bump = order_book.bump
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    pub base_mint: InterfaceAccount<'info, Mint>,
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = base_mint,
+        associated_token::authority = order_book,
+        associated_token::token_program = token_program
+    )]
+    pub base_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = order_book,
+        associated_token::token_program = token_program
+    )]
+    pub quote_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Bet-mint account for a bid, share account for an ask.
+    #[account(mut, token::authority = owner, token::token_program = token_program)]
+    pub owner_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOrder<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = base_mint,
+        has_one = quote_mint,
+        seeds = [ORDER_BOOK_SEED, order_book.market.as_ref()],
+        bump = order_book.bump
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    pub base_mint: InterfaceAccount<'info, Mint>,
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = base_mint,
+        associated_token::authority = order_book,
+        associated_token::token_program = token_program
+    )]
+    pub base_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = order_book,
+        associated_token::token_program = token_program
+    )]
+    pub quote_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, token::token_program = token_program)]
+    pub owner_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct MatchOrders<'info> {
+    pub cranker: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = base_mint,
+        has_one = quote_mint,
+        seeds = [ORDER_BOOK_SEED, order_book.market.as_ref()],
+        bump = order_book.bump
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+
+    pub base_mint: InterfaceAccount<'info, Mint>,
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = base_mint,
+        associated_token::authority = order_book,
+        associated_token::token_program = token_program
+    )]
+    pub base_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = order_book,
+        associated_token::token_program = token_program
+    )]
+    pub quote_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, token::mint = base_mint, token::token_program = token_program)]
+    pub buyer_base_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, token::mint = quote_mint, token::token_program = token_program)]
+    pub buyer_quote_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, token::mint = quote_mint, token::token_program = token_program)]
+    pub seller_quote_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitOutcomeMints<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut, has_one = bet_mint)]
+    pub market: Account<'info, Market>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA used as mint authority, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        seeds = [OUTCOME_MINT_SEED, market.key().as_ref(), &[Outcome::Yes as u8]],
+        bump,
+        mint::decimals = bet_mint.decimals,
+        mint::authority = vault_authority,
+        mint::token_program = token_program
+    )]
+    pub yes_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = owner,
+        seeds = [OUTCOME_MINT_SEED, market.key().as_ref(), &[Outcome::No as u8]],
+        bump,
+        mint::decimals = bet_mint.decimals,
+        mint::authority = vault_authority,
+        mint::token_program = token_program
+    )]
+    pub no_mint: InterfaceAccount<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct OutcomeTokens<'info> {
+    pub user: Signer<'info>,
+
+    #[account(mut, has_one = bet_mint, has_one = vault, has_one = yes_mint, has_one = no_mint)]
+    pub market: Account<'info, Market>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA signer for the vault and mints, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub yes_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub no_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = bet_mint,
+        token::authority = user,
+        token::token_program = token_program
+    )]
+    pub user_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = yes_mint,
+        token::authority = user,
+        token::token_program = token_program
+    )]
+    pub user_yes: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = no_mint,
+        token::authority = user,
+        token::token_program = token_program
+    )]
+    pub user_no: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitPortfolio<'info> {
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(
+        init,
+        payer = wallet,
+        space = Portfolio::LEN,
+        seeds = [PORTFOLIO_SEED, wallet.key().as_ref()],
+        bump
+    )]
+    pub portfolio: Account<'info, Portfolio>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PrunePortfolio<'info> {
+    pub wallet: Signer<'info>,
+
+    #[account(mut, seeds = [PORTFOLIO_SEED, wallet.key().as_ref()], bump = portfolio.bump)]
+    pub portfolio: Account<'info, Portfolio>,
+}
+
+#[derive(Accounts)]
+pub struct SetAttestationIssuer<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct IssueAttestation<'info> {
+    #[account(mut)]
+    pub issuer: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = issuer,
+        space = Attestation::LEN,
+        seeds = [ATTESTATION_SEED, issuer.key().as_ref(), wallet.as_ref()],
+        bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeAttestation<'info> {
+    #[account(mut)]
+    pub issuer: Signer<'info>,
+
+    #[account(
+        mut,
+        close = issuer,
+        has_one = issuer,
+        seeds = [ATTESTATION_SEED, issuer.key().as_ref(), attestation.wallet.as_ref()],
+        bump = attestation.bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+}
+
+#[derive(Accounts)]
+pub struct ImportMarket<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    /// Required when an operator's admin imports into its market.
+    pub operator: Option<Account<'info, Operator>>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey, outcome: Outcome, amount: u64, position_index: u16)]
+pub struct ImportPosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut, has_one = vault)]
+    pub market: Account<'info, Market>,
+
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Position::LEN,
+        seeds = [POSITION_SEED, market.key().as_ref(), wallet.as_ref(), &position_index.to_le_bytes()],
+        bump
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = UserStats::LEN,
+        seeds = [USER_STATS_SEED, wallet.as_ref()],
+        bump
+    )]
+    pub user_stats: Account<'info, UserStats>,
+
+    /// Optional portfolio index of `wallet`; the imported position is added to it when passed.
+    #[account(mut, seeds = [PORTFOLIO_SEED, wallet.as_ref()], bump = portfolio.bump)]
+    pub portfolio: Option<Account<'info, Portfolio>>,
+
+    /// Required when an operator's admin imports into its market.
+    pub operator: Option<Account<'info, Operator>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetVrfThreshold<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct RequestVrf<'info> {
+    pub caller: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    /// CHECK: Switchboard randomness account, owner-checked and parsed in `parse_randomness`.
+    #[account(owner = SWITCHBOARD_ON_DEMAND_ID @ ErrorCode::InvalidRandomness)]
+    pub randomness: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConsumeVrf<'info> {
+    pub caller: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+    /// CHECK: must be the randomness account committed by `request_vrf`.
+    #[account(owner = SWITCHBOARD_ON_DEMAND_ID @ ErrorCode::InvalidRandomness)]
+    pub randomness: UncheckedAccount<'info>,
+    /// CHECK: instructions sysvar, pinned by address.
+    #[account(address = ix_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRewardSchedule<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, market.key().as_ref(), owner.key().as_ref(), &position.index.to_le_bytes()],
+        bump,
+        constraint = position.owner == owner.key() @ ErrorCode::Unauthorized
+    )]
+    pub position: Option<Account<'info, Position>>,
+
+    #[account(
+        mut,
+        seeds = [SUBSIDY_SEED, market.key().as_ref(), owner.key().as_ref()],
+        bump = subsidy.bump
+    )]
+    pub subsidy: Option<Account<'info, Subsidy>>,
+
+    #[account(seeds = [REWARD_CONFIG_SEED], bump = reward_config.bump)]
+    pub reward_config: Account<'info, RewardConfig>,
+
+    #[account(mut, address = reward_config.reward_mint @ ErrorCode::WrongMint)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: program-held mint authority, validated by seeds.
+    #[account(seeds = [REWARD_MINT_AUTH_SEED], bump)]
+    pub reward_mint_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = owner,
+        token::token_program = token_program
+    )]
+    pub owner_reward_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(bracket_id: u64, rounds: u8)]
+pub struct CreateBracket<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Bracket::space(rounds.min(MAX_BRACKET_ROUNDS)),
+        seeds = [BRACKET_SEED, &bracket_id.to_le_bytes()],
+        bump
+    )]
+    pub bracket: Account<'info, Bracket>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetBracketMatch<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [BRACKET_SEED, &bracket.bracket_id.to_le_bytes()], bump = bracket.bump)]
+    pub bracket: Account<'info, Bracket>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct AdvanceBracket<'info> {
+    #[account(mut, seeds = [BRACKET_SEED, &bracket.bracket_id.to_le_bytes()], bump = bracket.bump)]
+    pub bracket: Account<'info, Bracket>,
+    pub market: Account<'info, Market>,
+    /// The next-round market; required once both of its sides are decided.
+    #[account(mut)]
+    pub next_market: Option<Account<'info, Market>>,
+}
+
+#[derive(Accounts)]
+pub struct SetBracketWinner<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [BRACKET_SEED, &bracket.bracket_id.to_le_bytes()], bump = bracket.bump)]
+    pub bracket: Account<'info, Bracket>,
+    pub market: Account<'info, Market>,
+    /// The next-round market; required once both of its sides are decided.
+    #[account(mut)]
+    pub next_market: Option<Account<'info, Market>>,
+}
+
+#[derive(Accounts)]
+pub struct SetSeasonPassConfig<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = SeasonPassConfig::LEN,
+        seeds = [SEASON_PASS_CONFIG_SEED],
+        bump
+    )]
+    pub season_pass_config: Account<'info, SeasonPassConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BuySeasonPass<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(mut, seeds = [SEASON_PASS_CONFIG_SEED], bump = season_pass_config.bump)]
+    pub season_pass_config: Account<'info, SeasonPassConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = SeasonPass::LEN,
+        seeds = [SEASON_PASS_SEED, buyer.key().as_ref()],
+        bump
+    )]
+    pub season_pass: Account<'info, SeasonPass>,
+
+    #[account(address = season_pass_config.mint @ ErrorCode::WrongMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, token::mint = mint, token::authority = buyer, token::token_program = token_program)]
+    pub buyer_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint,
+        associated_token::authority = season_pass_config,
+        associated_token::token_program = token_program
+    )]
+    pub pass_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSeasonPassRevenue<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(seeds = [SEASON_PASS_CONFIG_SEED], bump = season_pass_config.bump)]
+    pub season_pass_config: Account<'info, SeasonPassConfig>,
+
+    #[account(address = season_pass_config.mint @ ErrorCode::WrongMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = season_pass_config,
+        associated_token::token_program = token_program
+    )]
+    pub pass_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub recipient_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SetOpenTs<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+}
+
+fn owner_pubkey() -> Pubkey {
+    Pubkey::from_str(OWNER).unwrap()
+}
+
+fn fee_wallet_pubkey() -> Pubkey {
+    Pubkey::from_str(FEE_WALLET).unwrap()
+}
+
+/// The market's bet fee: its operator's rate, or `FEE_BPS` when unset.
+fn market_fee_bps(market: &Market) -> u64 {
+    if market.fee_bps == 0 {
+        FEE_BPS
+    } else {
+        market.fee_bps as u64
+    }
+}
+
+fn calculate_fee(market: &Market, amount: u64) -> Result<u64> {
+    sillymarket_math::fee(amount, market_fee_bps(market)).ok_or(error!(ErrorCode::Overflow))
+}
+
+fn validate_schedule(
+    now: i64,
+    cutoff_ts: i64,
+    event_ts: Option<i64>,
+    claim_deadline_ts: Option<i64>,
+    min_duration: i64,
+) -> Result<(i64, i64)> {
+    require!(cutoff_ts > now, ErrorCode::CutoffInPast);
+    require!(
+        cutoff_ts >= now.checked_add(min_duration).ok_or(ErrorCode::Overflow)?,
+        ErrorCode::MarketDurationTooShort
+    );
+    require!(cutoff_ts <= now + MAX_MARKET_DURATION, ErrorCode::MarketDurationTooLong);
+    let event_ts = event_ts.unwrap_or(cutoff_ts);
+    require!(event_ts >= cutoff_ts, ErrorCode::InvalidEventTs);
+    let claim_deadline_ts = match claim_deadline_ts {
+        Some(ts) => ts,
+        None => event_ts.checked_add(DEFAULT_CLAIM_WINDOW).ok_or(ErrorCode::Overflow)?,
+    };
+    require!(claim_deadline_ts > event_ts, ErrorCode::InvalidClaimDeadline);
+    let horizon = now.checked_add(MAX_SCHEDULE_HORIZON).ok_or(ErrorCode::Overflow)?;
+    require!(event_ts <= horizon && claim_deadline_ts <= horizon, ErrorCode::ScheduleTooFar);
+    Ok((event_ts, claim_deadline_ts))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn init_market(
+    m: &mut Market,
+    creator: Pubkey,
+    bet_mint: Pubkey,
+    vault: Pubkey,
+    vault_authority: Pubkey,
+    cutoff_ts: i64,
+    event_ts: i64,
+    claim_deadline_ts: i64,
+    now: i64,
+) {
+    m.creator = creator;
+    m.bet_mint = bet_mint;
+    m.vault = vault;
+    m.vault_authority = vault_authority;
+    m.cutoff_ts = cutoff_ts;
+    m.resolved = false;
+    m.winning_outcome = Outcome::Unset as u8;
+    m.total_yes = 0;
+    m.total_no = 0;
+    m.fees_accrued = 0;
+    m.emergency_paused = false;
+    m.fee_receiver = fee_wallet_pubkey(); // Uses hardcoded fee wallet
+    m.created_at = now;
+    m.resolution_reason = ResolutionReason::Unset as u8;
+    m.multi_mint = false;
+    m.mint_count = 0;
+    m.price_feed = Pubkey::default();
+    m.usd_bet_cap = 0;
+    m.winner_rake_bps = 0;
+    m.winner_bonus = 0;
+    m.insurance_contributed = 0;
+    m.subsidy_yes = 0;
+    m.subsidy_no = 0;
+    m.emergency_withdraw_queued_at = 0;
+    m.claim_deadline_ts = claim_deadline_ts;
+    m.max_payout = 0;
+    m.max_multiplier_bps = 0;
+    m.clipped_excess = 0;
+    m.snipe_window_secs = 0;
+    m.snipe_threshold_bps = 0;
+    m.snipe_extension_secs = 0;
+    m.snipe_max_extension_secs = 0;
+    m.snipe_extended = 0;
+    m.event_ts = event_ts;
+    m.evidence_hash = [0u8; 32];
+    m.evidence_uri = String::new();
+    m.round_count = 0;
+    m.round_results = [Outcome::Unset as u8; MAX_ROUNDS];
+    m.parent_market = Pubkey::default();
+    m.parent_condition = Outcome::Unset as u8;
+    m.oracle_feed = Pubkey::default();
+    m.oracle_threshold = 0;
+    m.twap_cumulative = 0;
+    m.twap_last_ts = now;
+    m.twap_last_bps = (BPS_DENOM / 2) as u16;
+    m.resolved_ts = 0;
+    m.vest_threshold = 0;
+    m.vest_period_secs = 0;
+    m.total_claimed = 0;
+    m.subsidy_refunded = 0;
+    m.vote_end_ts = 0;
+    m.votes_yes = 0;
+    m.votes_no = 0;
+    m.votes_draw = 0;
+    m.resolver = Pubkey::default();
+    m.proposed_outcome = Outcome::Unset as u8;
+    m.dispute_deadline_ts = 0;
+    m.disputer = Pubkey::default();
+    m.dispute_stage = DisputeStage::None as u8;
+    m.vote_outcome = Outcome::Unset as u8;
+    m.appeal_deadline_ts = 0;
+    m.appellant = Pubkey::default();
+    m.appeal_ready_ts = 0;
+    m.fee_destination = Pubkey::default();
+    m.merkle_refund = false;
+    m.block_cpi = false;
+    m.market_id = 0;
+    m.bump = 0;
+    m.keeper = Pubkey::default();
+    m.keeper_tip = 0;
+    m.resolvable_early = false;
+    m.three_way = false;
+    m.total_draw = 0;
+    m.bet_cooldown_secs = 0;
+    m.resolve_authority = Pubkey::default();
+    m.over_under = false;
+    m.ou_line = 0;
+    m.ou_units = [0u8; 16];
+    m.max_bet_pool_bps = 0;
+    m.bet_cap_min_pool = 0;
+    m.yield_program = Pubkey::default();
+    m.yield_reserve = Pubkey::default();
+    m.yield_to_pool = false;
+    m.yield_deposited = 0;
+    m.yield_earned = 0;
+    m.lst_program = Pubkey::default();
+    m.lst_pool = Pubkey::default();
+    m.gross_volume = 0;
+    m.shard_count = 0;
+    m.shards_merged = 0;
+    m.report_signer = Pubkey::default();
+    m.vaa_emitter_chain = 0;
+    m.vaa_emitter = [0; 32];
+    m.bridge_refunds_due = 0;
+    m.house_limit = 0;
+    m.house_yes = 0;
+    m.house_no = 0;
+    m.house_settled = false;
+    m.split_yes_bps = 0;
+    m.premium_pool = 0;
+    m.insured_claimed = 0;
+    m.yes_mint = Pubkey::default();
+    m.no_mint = Pubkey::default();
+    m.set_collateral = 0;
+    m.attestation_issuer = Pubkey::default();
+    m.legacy_market = Pubkey::default();
+    m.vrf_threshold_bps = 0;
+    m.vrf_randomness = Pubkey::default();
+    m.vrf_commit_slot = 0;
+    m.vrf_value = [0; 32];
+    m.jackpot_pending = false;
+    m.reward_rate = 0;
+    m.reward_end_ts = 0;
+    m.reward_last_ts = 0;
+    m.reward_per_stake = 0;
+    m.bracket = Pubkey::default();
+    m.bracket_locked = false;
+    m.open_ts = 0;
+    m.operator = Pubkey::default();
+    m.fee_bps = 0;
+    m.yield_loss = 0;
+}
+
+fn validate_bet_limit(market: &Market, mint: &Mint, position: &Position, total_amount: u64) -> Result<()> {
+    let current = position_total_after(market, position, 0)?;
+    let within = sillymarket_math::within_cap(current, total_amount, MAX_BET_LIMIT, mint.decimals)
+        .ok_or(error!(ErrorCode::Overflow))?;
+
+    require!(within, ErrorCode::BetExceedsLimit);
+    Ok(())
+}
+
+fn validate_usd_bet_limit(
+    market: &Market,
+    mint: &Mint,
+    position: &Position,
+    total_amount: u64,
+    price: i64,
+    expo: i32,
+) -> Result<()> {
+    let new_total = position_total_after(market, position, total_amount)?;
+    let new_total = u64::try_from(new_total).map_err(|_| error!(ErrorCode::Overflow))?;
+    let usd_total = to_accounting_units(new_total, mint.decimals, price, expo)?;
+
+    require!(usd_total <= market.usd_bet_cap, ErrorCode::BetExceedsLimit);
+    Ok(())
+}
+
+/// Gross (fee-inclusive) stake of the position once `total_amount` is added.
+fn position_total_after(market: &Market, position: &Position, total_amount: u64) -> Result<u128> {
+    let current_net = position.amount as u128;
+    let fee_rate = market_fee_bps(market) as f64 / BPS_DENOM as f64;
+    let current_total_estimate = (current_net as f64 / (1.0 - fee_rate)) as u128;
+
+    Ok(current_total_estimate
+        .checked_add(total_amount as u128)
+        .ok_or(ErrorCode::Overflow)?)
+}
+
+fn validate_market_active(market: &Market) -> Result<()> {
+    require!(!market.resolved, ErrorCode::MarketResolved);
+    Ok(())
+}
+
+fn calculate_payout(market: &Market, position: &Position) -> Result<u64> {
+    let gross = gross_payout(market, position)?;
+    if market.yield_loss == 0 {
+        return Ok(gross);
+    }
+    // Share an uncovered yield shortfall pro rata across every payout.
+    let pool = gross_claimable_pool(market)? as u128;
+    require!(pool > 0, ErrorCode::NoPayout);
+    let kept = pool.saturating_sub(market.yield_loss as u128);
+    let net = (gross as u128)
+        .checked_mul(kept)
+        .ok_or(ErrorCode::Overflow)?
+        / pool;
+    Ok(net as u64)
+}
+
+fn gross_payout(market: &Market, position: &Position) -> Result<u64> {
+    if market.winning_outcome == Outcome::Void as u8 {
+        require!(position.amount > 0, ErrorCode::NoPayout);
+        Ok(sillymarket_math::void_refund(position.amount))
+    } else if market.winning_outcome == Outcome::Split as u8 {
+        split_payout(market, position)
+    } else {
+        require!(position.outcome == market.winning_outcome, ErrorCode::NoPayout);
+
+        let subsidy = losing_subsidy(market, market.winning_outcome) as u128;
+        let total_pool = (total_staked(market)? as u128)
+            .checked_add(market.winner_bonus as u128)
+            .and_then(|v| v.checked_add(subsidy))
+            .ok_or(ErrorCode::Overflow)?;
+        
+        let winning_pool = outcome_pool(market, market.winning_outcome) as u128;
+        
+        require!(winning_pool > 0, ErrorCode::NoPayout);
+
+        sillymarket_math::pro_rata_payout(total_pool, winning_pool, position.amount)
+            .ok_or(error!(ErrorCode::Overflow))
+    }
+}
+
+/// Split resolution: each side's pool shares `split_yes_bps` or the rest of the market.
+fn split_payout(market: &Market, position: &Position) -> Result<u64> {
+    let (side_pool, side_bps) = if position.outcome == Outcome::Yes as u8 {
+        (market.total_yes, market.split_yes_bps as u128)
+    } else if position.outcome == Outcome::No as u8 {
+        (market.total_no, BPS_DENOM as u128 - market.split_yes_bps as u128)
+    } else {
+        return err!(ErrorCode::NoPayout);
+    };
+    require!(side_pool > 0 && position.amount > 0, ErrorCode::NoPayout);
+
+    let total_pool = (total_staked(market)? as u128)
+        .checked_add(market.winner_bonus as u128)
+        .and_then(|v| v.checked_add(losing_subsidy(market, Outcome::Split as u8) as u128))
+        .ok_or(ErrorCode::Overflow)?;
+    let side_share = total_pool
+        .checked_mul(side_bps)
+        .ok_or(ErrorCode::Overflow)?
+        / BPS_DENOM as u128;
+
+    sillymarket_math::pro_rata_payout(side_share, side_pool as u128, position.amount)
+        .ok_or(error!(ErrorCode::Overflow))
+}
+
+fn read_oracle_price(feed: &OracleFeed, now: i64) -> Result<(i64, i32)> {
+    require!(feed.updated_ts > 0, ErrorCode::OracleStale);
+    require!(now.saturating_sub(feed.updated_ts) <= MAX_ORACLE_STALENESS, ErrorCode::OracleStale);
+    require!(feed.value > 0, ErrorCode::InvalidOraclePrice);
+    Ok((feed.value, feed.expo))
+}
+
+/// Converts a token amount into the multi-mint accounting unit using a `value * 10^expo` USD price.
+fn to_accounting_units(amount: u64, decimals: u8, price: i64, expo: i32) -> Result<u64> {
+    let mut num = (amount as u128)
+        .checked_mul(price as u128)
+        .ok_or(ErrorCode::Overflow)?
+        .checked_mul(10u128.pow(ACCOUNTING_DECIMALS))
+        .ok_or(ErrorCode::Overflow)?;
+    let mut den = 10u128.checked_pow(decimals as u32).ok_or(ErrorCode::Overflow)?;
+    if expo >= 0 {
+        num = num
+            .checked_mul(10u128.checked_pow(expo as u32).ok_or(ErrorCode::Overflow)?)
+            .ok_or(ErrorCode::Overflow)?;
+    } else {
+        den = den
+            .checked_mul(10u128.checked_pow(expo.unsigned_abs()).ok_or(ErrorCode::Overflow)?)
+            .ok_or(ErrorCode::Overflow)?;
+    }
+    let units = num.checked_div(den).ok_or(ErrorCode::Overflow)?;
+    u64::try_from(units).map_err(|_| error!(ErrorCode::Overflow))
+}
+
+fn validate_mint_bet_limit(mint: &Mint, deposited: u64, amount: u64) -> Result<()> {
+    let within = sillymarket_math::within_cap(deposited as u128, amount, MAX_BET_LIMIT, mint.decimals)
+        .ok_or(error!(ErrorCode::Overflow))?;
+    require!(within, ErrorCode::BetExceedsLimit);
+    Ok(())
+}
+
+fn full_mint_mask(mint_count: u8) -> u8 {
+    if mint_count >= 8 {
+        u8::MAX
+    } else {
+        (1u8 << mint_count) - 1
+    }
+}
+
+/// Accounting units a mint's vault holds, and what its own winners are owed out of the whole pool.
+fn mint_units_due(m: &Market, mm: &MarketMint) -> Result<(u64, u64)> {
+    let held = mm.units_yes.checked_add(mm.units_no).ok_or(ErrorCode::Overflow)?;
+    let (winning_units, mint_winning_units) = if m.winning_outcome == Outcome::Yes as u8 {
+        (m.total_yes, mm.units_yes)
+    } else {
+        (m.total_no, mm.units_no)
+    };
+    let total_units = m.total_yes.checked_add(m.total_no).ok_or(ErrorCode::Overflow)?;
+    let due = sillymarket_math::pro_rata_payout(total_units as u128, winning_units as u128, mint_winning_units)
+        .ok_or(error!(ErrorCode::Overflow))?;
+    Ok((held, due))
+}
+
+/// Splits `owed` units between the deposit mint's vault and other vaults' surplus.
+fn split_mint_claim(m: &Market, deposit_mm: &MarketMint, owed: u64) -> Result<(u64, u64)> {
+    let (held, due) = mint_units_due(m, deposit_mm)?;
+    if due <= held {
+        return Ok((owed, 0));
+    }
+    let own = sillymarket_math::pro_rata_payout(held as u128, due as u128, owed)
+        .ok_or(error!(ErrorCode::Overflow))?;
+    Ok((own, owed - own))
+}
+
+/// Units a vault holds beyond what its own winners are owed.
+fn mint_surplus(m: &Market, mm: &MarketMint) -> Result<u64> {
+    let (held, due) = mint_units_due(m, mm)?;
+    Ok(held.saturating_sub(due))
+}
+
+/// Converts accounting units back into `mm`'s mint at its bet-time rate.
+fn units_to_mint_amount(mm: &MarketMint, units: u64) -> Result<u64> {
+    let held = mm.units_yes.checked_add(mm.units_no).ok_or(ErrorCode::Overflow)?;
+    if held == 0 {
+        return Ok(0);
+    }
+    let pool = mm.total_yes.checked_add(mm.total_no).ok_or(ErrorCode::Overflow)?;
+    sillymarket_math::pro_rata_payout(pool as u128, held as u128, units).ok_or(error!(ErrorCode::Overflow))
+}
+
+/// Every `MarketMint` of `m`, each exactly once.
+fn load_market_mints<'info>(
+    m: &Account<Market>,
+    infos: &'info [AccountInfo<'info>],
+) -> Result<Vec<Account<'info, MarketMint>>> {
+    require!(infos.len() == m.mint_count as usize, ErrorCode::InvalidBatch);
+    let mut seen = 0u8;
+    let mut mints = Vec::with_capacity(infos.len());
+    for info in infos {
+        let mm = Account::<MarketMint>::try_from(info)?;
+        require_keys_eq!(mm.market, m.key(), ErrorCode::WrongMarket);
+        let bit = 1u8 << mm.index;
+        require!(seen & bit == 0, ErrorCode::InvalidBatch);
+        seen |= bit;
+        mints.push(mm);
+    }
+    Ok(mints)
 }
 
-#[account]
-pub struct ProgramStats {
-    pub authority: Pubkey,
-    pub total_markets: u64,
-    pub total_volume: u64,
-    pub total_fees: u64,
-    pub total_bettors: u64,
-    pub is_initialized: bool,
+fn calculate_reward(wagered: u64, emission_per_thousand: u64) -> Result<u64> {
+    let reward = (wagered as u128)
+        .checked_mul(emission_per_thousand as u128)
+        .ok_or(ErrorCode::Overflow)?
+        / 1_000;
+    u64::try_from(reward).map_err(|_| error!(ErrorCode::Overflow))
 }
-impl ProgramStats {
-    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 1;
+
+fn calculate_winner_bonus(fees_accrued: u64, winner_rake_bps: u16) -> Result<u64> {
+    (fees_accrued as u128)
+        .checked_mul(winner_rake_bps as u128)
+        .ok_or(ErrorCode::Overflow)?
+        .checked_div(BPS_DENOM as u128)
+        .ok_or(ErrorCode::Overflow)?
+        .try_into()
+        .map_err(|_| error!(ErrorCode::Overflow))
 }
 
-#[repr(u8)]
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
-pub enum Outcome {
-    Unset = 0,
-    Yes   = 1,
-    No    = 2,
-    Void  = 3,
+/// All stakes in the market, including the Draw pool of 1X2 markets.
+fn total_staked(market: &Market) -> Result<u64> {
+    market.total_yes
+        .checked_add(market.total_no)
+        .and_then(|v| v.checked_add(market.total_draw))
+        .ok_or(error!(ErrorCode::Overflow))
 }
 
-#[repr(u8)]
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
-pub enum ResolutionReason {
-    Unset = 0,
-    Manual = 1,
-    Oracle = 2,
-    AutoVoid = 3,
-    Consensus = 4,
+fn outcome_pool(market: &Market, outcome: u8) -> u64 {
+    if outcome == Outcome::Yes as u8 {
+        market.total_yes
+    } else if outcome == Outcome::No as u8 {
+        market.total_no
+    } else if outcome == Outcome::Draw as u8 {
+        market.total_draw
+    } else {
+        0
+    }
 }
 
-#[event]
-pub struct ProgramInitialized {
-    pub authority: Pubkey,
-    pub timestamp: i64,
+/// Largest total the winners could be owed across both outcomes.
+fn potential_liability(market: &Market) -> Result<u64> {
+    let bonus = calculate_winner_bonus(market.fees_accrued, market.winner_rake_bps)?;
+    // A Draw pays out both sides' subsidies.
+    let subsidies = if market.three_way {
+        market.subsidy_yes.checked_add(market.subsidy_no).ok_or(ErrorCode::Overflow)?
+    } else {
+        market.subsidy_yes.max(market.subsidy_no)
+    };
+    total_staked(market)?
+        .checked_add(subsidies)
+        .and_then(|v| v.checked_add(bonus))
+        .ok_or(error!(ErrorCode::Overflow))
 }
 
-#[event]
-pub struct MarketCreated {
-    pub market: Pubkey,
-    pub creator: Pubkey,
-    pub cutoff_ts: i64,
-    pub bet_mint: Pubkey,
-    pub question: String,
-    pub category: String,
+/// Returns `(capped_payout, clipped_excess)`.
+fn apply_odds_cap(payout: u64, stake: u64, max_multiplier_bps: u32) -> Result<(u64, u64)> {
+    sillymarket_math::apply_odds_cap(payout, stake, max_multiplier_bps).ok_or(error!(ErrorCode::Overflow))
 }
 
-#[event]
-pub struct CutoffUpdated {
-    pub market: Pubkey,
-    pub new_cutoff_ts: i64,
+/// Seconds a late bet of `net` pushes the cutoff out by, within the extension budget.
+fn anti_snipe_extension(market: &Market, now: i64, net: u64, pool_before: u64) -> Result<i64> {
+    if market.snipe_window_secs == 0 || market.snipe_extension_secs == 0 {
+        return Ok(0);
+    }
+    if now < market.cutoff_ts.saturating_sub(market.snipe_window_secs) {
+        return Ok(0);
+    }
+    let threshold = (pool_before as u128)
+        .checked_mul(market.snipe_threshold_bps as u128)
+        .ok_or(ErrorCode::Overflow)?
+        / BPS_DENOM as u128;
+    if (net as u128) <= threshold {
+        return Ok(0);
+    }
+
+    let budget = market.snipe_max_extension_secs.saturating_sub(market.snipe_extended);
+    let deadline_room = market.event_ts.saturating_sub(market.cutoff_ts);
+    Ok(market.snipe_extension_secs.min(budget).min(deadline_room).max(0))
 }
 
-#[event]
-pub struct BetPlaced {
-    pub market: Pubkey,
-    pub bettor: Pubkey,
-    pub outcome: Outcome,
-    pub amount: u64,
-    pub fee: u64,
-    pub net_amount: u64,
+/// Marks the market resolved (or auto-void) and returns its `MarketResolved` event.
+fn settle_market(
+    m: &mut Account<Market>,
+    stats: &mut ProgramStats,
+    winning_outcome: Outcome,
+    resolution_reason: ResolutionReason,
+    instructions: &AccountInfo,
+) -> Result<MarketResolved> {
+    require_isolated_resolution(instructions, &m.key())?;
+    require_shards_merged(m)?;
+    // A random market may only be settled by its committed randomness.
+    require!(
+        m.vrf_threshold_bps == 0 || resolution_reason == ResolutionReason::Vrf,
+        ErrorCode::VrfMarket
+    );
+    // Void when nobody backed the winner or nobody bet against it.
+    let winning_pool = outcome_pool(m, winning_outcome as u8);
+    let auto_void = if m.three_way {
+        winning_pool == 0 || winning_pool == total_staked(m)?
+    } else {
+        m.total_yes == 0 || m.total_no == 0
+    };
+
+    stats.markets_resolved = stats.markets_resolved.checked_add(1).ok_or(ErrorCode::Overflow)?;
+    m.resolved = true;
+    m.resolved_ts = Clock::get()?.unix_timestamp;
+    m.winning_outcome = if auto_void {
+        Outcome::Void as u8
+    } else {
+        match winning_outcome {
+            Outcome::Yes | Outcome::No => winning_outcome as u8,
+            Outcome::Draw if m.three_way => winning_outcome as u8,
+            Outcome::Split if m.split_yes_bps > 0 => winning_outcome as u8,
+            _ => return err!(ErrorCode::InvalidOutcomeArg),
+        }
+    };
+    m.resolution_reason = if auto_void {
+        ResolutionReason::AutoVoid as u8
+    } else {
+        resolution_reason as u8
+    };
+
+    if !auto_void {
+        let bonus = calculate_winner_bonus(m.fees_accrued, m.winner_rake_bps)?;
+        m.fees_accrued = m.fees_accrued.checked_sub(bonus).ok_or(ErrorCode::Overflow)?;
+        m.winner_bonus = bonus;
+    }
+
+    Ok(MarketResolved {
+        market: m.key(),
+        winning_outcome: m.winning_outcome,
+        auto_void,
+        total_yes: m.total_yes,
+        total_no: m.total_no,
+        fees_accrued: m.fees_accrued,
+        resolution_reason: m.resolution_reason,
+        winner_bonus: m.winner_bonus,
+        evidence_hash: m.evidence_hash,
+        evidence_uri: m.evidence_uri.clone(),
+    })
 }
 
-#[event]
-pub struct MarketResolved {
-    pub market: Pubkey,
-    pub winning_outcome: u8,
-    pub auto_void: bool,
-    pub total_yes: u64,
-    pub total_no: u64,
-    pub fees_accrued: u64,
-    pub resolution_reason: u8,
+fn void_market_state(
+    m: &mut Account<Market>,
+    stats: &mut ProgramStats,
+    reason: ResolutionReason,
+    now: i64,
+) -> Result<()> {
+    require_shards_merged(m)?;
+    stats.markets_resolved = stats.markets_resolved.checked_add(1).ok_or(ErrorCode::Overflow)?;
+    m.resolved = true;
+    m.resolved_ts = now;
+    m.winning_outcome = Outcome::Void as u8;
+    m.resolution_reason = reason as u8;
+
+    emit!(MarketVoided {
+        market: m.key(),
+        before_cutoff: now < m.cutoff_ts,
+        total_yes: m.total_yes,
+        total_no: m.total_no,
+        timestamp: now,
+        resolution_reason: m.resolution_reason,
+    });
+
+    Ok(())
+}
+
+/// For conditional markets, checks the supplied parent.
+fn parent_condition_failed(market: &Market, parent: Option<&Account<Market>>) -> Result<bool> {
+    if market.parent_market == Pubkey::default() {
+        return Ok(false);
+    }
+    let parent = parent.ok_or(ErrorCode::WrongParentMarket)?;
+    require_keys_eq!(parent.key(), market.parent_market, ErrorCode::WrongParentMarket);
+    require!(parent.resolved, ErrorCode::ParentNotResolved);
+    Ok(parent.winning_outcome != market.parent_condition)
+}
+
+/// Allocates a program-owned PDA.
+fn create_pda_account<'info>(
+    payer: &AccountInfo<'info>,
+    target: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    owner: &Pubkey,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let lamports = Rent::get()?.minimum_balance(space);
+    let current = target.lamports();
+    if current == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                CreateAccount {
+                    from: payer.clone(),
+                    to: target.clone(),
+                },
+                &[seeds],
+            ),
+            lamports,
+            space as u64,
+            owner,
+        );
+    }
+
+    // Someone sent lamports to the address first; `create_account` would
+    // refuse it, so top up, allocate and assign instead.
+    let top_up = lamports.saturating_sub(current);
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: target.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Allocate { account_to_allocate: target.clone() },
+            &[seeds],
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Assign { account_to_assign: target.clone() },
+            &[seeds],
+        ),
+        owner,
+    )
+}
+
+/// Creates the duplicate guard for `guard_hash` when its seeds are only known in the handler.
+fn claim_market_guard<'info>(
+    payer: &AccountInfo<'info>,
+    guard: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    program_id: &Pubkey,
+    market: Pubkey,
+    guard_hash: [u8; 32],
+) -> Result<()> {
+    let (key, bump) = Pubkey::find_program_address(&[MARKET_GUARD_SEED, &guard_hash], program_id);
+    require_keys_eq!(guard.key(), key, ErrorCode::InvalidMarketGuard);
+    require!(guard.owner != program_id, ErrorCode::DuplicateMarket);
+    create_pda_account(
+        payer,
+        guard,
+        system_program,
+        MarketGuard::LEN,
+        program_id,
+        &[MARKET_GUARD_SEED, &guard_hash, &[bump]],
+    )?;
+    MarketGuard { market }.try_serialize(&mut &mut guard.try_borrow_mut_data()?[..])
+}
+
+/// Implied Yes probability in bps from the pool ratio; 50% while empty.
+fn implied_yes_bps(market: &Market) -> Result<u16> {
+    let pool = total_staked(market)? as u128;
+    if pool == 0 {
+        return Ok((BPS_DENOM / 2) as u16);
+    }
+    let bps = (market.total_yes as u128)
+        .checked_mul(BPS_DENOM as u128)
+        .ok_or(ErrorCode::Overflow)?
+        / pool;
+    Ok(bps as u16)
+}
+
+/// Rolls the TWAP accumulator forward to `now` at the last recorded price.
+fn accumulate_twap(m: &mut Market, now: i64) -> Result<()> {
+    let elapsed = now.saturating_sub(m.twap_last_ts).max(0) as u128;
+    m.twap_cumulative = m.twap_cumulative
+        .checked_add(elapsed.checked_mul(m.twap_last_bps as u128).ok_or(ErrorCode::Overflow)?)
+        .ok_or(ErrorCode::Overflow)?;
+    m.twap_last_ts = now;
+    Ok(())
+}
+
+/// Portion of `entitled` unlocked at `now` under the market's vesting terms.
+fn vested_amount(market: &Market, entitled: u64, now: i64) -> Result<u64> {
+    if market.vest_period_secs == 0 || entitled <= market.vest_threshold {
+        return Ok(entitled);
+    }
+    let elapsed = now
+        .saturating_sub(market.resolved_ts)
+        .clamp(0, market.vest_period_secs);
+    let excess = (entitled - market.vest_threshold) as u128;
+    let unlocked = excess
+        .checked_mul(elapsed as u128)
+        .ok_or(ErrorCode::Overflow)?
+        / market.vest_period_secs as u128;
+    Ok(market.vest_threshold + unlocked as u64)
+}
+
+/// Bet-size cap check against either the USD cap (when a feed is bound) or the token-denominated limit.
+fn validate_position_cap(
+    market: &Market,
+    mint: &Mint,
+    position: &Position,
+    amount: u64,
+    price_feed: Option<&Account<OracleFeed>>,
+    now: i64,
+) -> Result<()> {
+    if market.price_feed != Pubkey::default() {
+        let feed = price_feed.ok_or(ErrorCode::WrongOracle)?;
+        require_keys_eq!(feed.key(), market.price_feed, ErrorCode::WrongOracle);
+        let (price, expo) = read_oracle_price(feed, now)?;
+        validate_usd_bet_limit(market, mint, position, amount, price, expo)
+    } else {
+        validate_bet_limit(market, mint, position, amount)
+    }
+}
+
+/// Accounts a bet path passes so `record_bet` can counter-bet from the house.
+struct HouseLeg<'a, 'info> {
+    house: &'a mut Account<'info, House>,
+    house_vault: &'a InterfaceAccount<'info, TokenAccount>,
+    vault: &'a InterfaceAccount<'info, TokenAccount>,
+    bet_mint: &'a InterfaceAccount<'info, Mint>,
+    token_program: &'a Interface<'info, TokenInterface>,
+}
+
+/// Pairs the optional house accounts with the market's vault; `None` if either house account was left out.
+fn house_leg<'a, 'info>(
+    house: Option<&'a mut Account<'info, House>>,
+    house_vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    vault: &'a InterfaceAccount<'info, TokenAccount>,
+    bet_mint: &'a InterfaceAccount<'info, Mint>,
+    token_program: &'a Interface<'info, TokenInterface>,
+) -> Option<HouseLeg<'a, 'info>> {
+    let (house, house_vault) = house.zip(house_vault)?;
+    Some(HouseLeg { house, house_vault, vault, bet_mint, token_program })
+}
+
+/// Books `received` tokens already in the vault as a bet and returns `(fee, net)`.
+#[allow(clippy::too_many_arguments)]
+fn record_bet(
+    m: &mut Account<Market>,
+    p: &mut Position,
+    stats: &mut ProgramStats,
+    us: &mut UserStats,
+    portfolio: Option<&mut Portfolio>,
+    bettor: Pubkey,
+    user_stats_bump: u8,
+    outcome: Outcome,
+    received: u64,
+    fee_discount_bps: u16,
+    position_index: u16,
+    now: i64,
+    house: Option<HouseLeg>,
+) -> Result<(u64, u64)> {
+    apply_bet_cooldown(m, p, position_index, now)?;
+    validate_pool_share(m, outcome, received)?;
+
+    let fee = discounted_fee(calculate_fee(m, received)?, fee_discount_bps)?;
+    let net = received.checked_sub(fee).ok_or(ErrorCode::Overflow)?;
+    m.fees_accrued = m.fees_accrued.checked_add(fee).ok_or(ErrorCode::Overflow)?;
+    m.gross_volume = m.gross_volume.checked_add(received).ok_or(ErrorCode::Overflow)?;
+
+    stats.total_volume = stats.total_volume.checked_add(received).ok_or(ErrorCode::Overflow)?;
+    stats.total_fees = stats.total_fees.checked_add(fee).ok_or(ErrorCode::Overflow)?;
+    if p.amount == 0 {
+        stats.total_bettors = stats.total_bettors.checked_add(1).ok_or(ErrorCode::Overflow)?;
+    }
+
+    accrue_rewards(m, now)?;
+    (p.reward_debt, p.rewards_owed) = settle_rewards(m.reward_per_stake, p.amount, p.reward_debt, p.rewards_owed)?;
+    credit_bettor(p, us, portfolio, m.key(), bettor, user_stats_bump, outcome, received, fee, net, position_index)?;
+    p.reward_debt = reward_debt(m.reward_per_stake, p.amount)?;
+
+    let pool_before = total_staked(m)?;
+    accumulate_twap(m, now)?;
+    match outcome {
+        Outcome::Yes => m.total_yes = m.total_yes.checked_add(net).ok_or(ErrorCode::Overflow)?,
+        Outcome::No => m.total_no = m.total_no.checked_add(net).ok_or(ErrorCode::Overflow)?,
+        Outcome::Draw if m.three_way => {
+            m.total_draw = m.total_draw.checked_add(net).ok_or(ErrorCode::Overflow)?
+        }
+        _ => return err!(ErrorCode::InvalidOutcomeArg),
+    }
+    m.twap_last_bps = implied_yes_bps(m)?;
+
+    let extension = anti_snipe_extension(m, now, net, pool_before)?;
+    if extension > 0 {
+        m.cutoff_ts = m.cutoff_ts.checked_add(extension).ok_or(ErrorCode::Overflow)?;
+        m.snipe_extended = m.snipe_extended.checked_add(extension).ok_or(ErrorCode::Overflow)?;
+
+        emit!(CutoffUpdated {
+            market: m.key(),
+            new_cutoff_ts: m.cutoff_ts,
+        });
+    }
+
+    if m.house_limit > 0 {
+        let leg = house.ok_or(ErrorCode::HouseAccountsMissing)?;
+        require_keys_eq!(leg.house_vault.key(), leg.house.vault, ErrorCode::HouseAccountsMissing);
+        let (side, matched) = house_match(m, leg.house, leg.house_vault.amount)?;
+        if matched > 0 {
+            let mint_key = leg.bet_mint.key();
+            let house_seeds: &[&[u8]] = &[HOUSE_SEED, mint_key.as_ref(), &[leg.house.bump]];
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    leg.token_program.to_account_info(),
+                    TransferChecked {
+                        from: leg.house_vault.to_account_info(),
+                        mint: leg.bet_mint.to_account_info(),
+                        to: leg.vault.to_account_info(),
+                        authority: leg.house.to_account_info(),
+                    },
+                    &[house_seeds],
+                ),
+                matched,
+                leg.bet_mint.decimals,
+            )?;
+            emit!(HouseMatched {
+                market: m.key(),
+                outcome: side,
+                amount: matched,
+                exposure: m.house_yes + m.house_no,
+            });
+        }
+    }
+
+    // Checked against the final pools, including any house counter-stake.
+    if m.max_payout > 0 {
+        require!(potential_liability(m)? <= m.max_payout, ErrorCode::MaxPayoutExceeded);
+    }
+
+    Ok((fee, net))
+}
+
+/// Records what `p` can claim at `now`, after the odds cap and vesting.
+fn take_claimable(
+    m: &mut Account<Market>,
+    p: &mut Position,
+    vault_balance: u64,
+    max_amount: Option<u64>,
+    now: i64,
+) -> Result<(u64, bool, bool)> {
+    require!(m.resolved, ErrorCode::NotResolved);
+    require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+    require!(!m.merkle_refund, ErrorCode::MerkleRefundActive);
+    require!(m.yield_deposited == 0, ErrorCode::YieldDeployed);
+    require!(!p.frozen, ErrorCode::PositionFrozen);
+    require!(!p.claimed, ErrorCode::AlreadyClaimed);
+    require!(now <= m.claim_deadline_ts, ErrorCode::ClaimExpired);
+    require_keys_eq!(p.market, m.key(), ErrorCode::WrongMarket);
+
+    let voided = m.winning_outcome == Outcome::Void as u8;
+    let first_claim = p.amount_claimed == 0;
+    let entitled = calculate_payout(m, p)?;
+    let (entitled, clipped) = if voided {
+        (entitled, 0)
+    } else {
+        apply_odds_cap(entitled, p.amount, m.max_multiplier_bps)?
+    };
+    if clipped > 0 && first_claim {
+        // Excess over the odds cap is left in the vault for the treasury.
+        m.fees_accrued = m.fees_accrued.checked_add(clipped).ok_or(ErrorCode::Overflow)?;
+        m.clipped_excess = m.clipped_excess.checked_add(clipped).ok_or(ErrorCode::Overflow)?;
+    }
+
+    let unlocked = if voided { entitled } else { vested_amount(m, entitled, now)? };
+    let available = unlocked.checked_sub(p.amount_claimed).ok_or(ErrorCode::Overflow)?;
+    require!(available > 0, ErrorCode::NothingVested);
+    let payout = match max_amount {
+        Some(amount) => {
+            require!(amount > 0, ErrorCode::InvalidAmount);
+            amount.min(available)
+        }
+        None => available,
+    };
+
+    let total_claimed = m.total_claimed.checked_add(payout).ok_or(ErrorCode::Overflow)?;
+    require!(total_claimed <= claimable_pool(m)?, ErrorCode::Insolvent);
+    require!(
+        payout
+            <= vault_balance
+                .saturating_sub(m.fees_accrued)
+                .saturating_sub(m.keeper_tip)
+                .saturating_sub(m.bridge_refunds_due)
+                .saturating_sub(m.set_collateral),
+        ErrorCode::Insolvent
+    );
+    m.total_claimed = total_claimed;
+
+    p.amount_claimed = p.amount_claimed.checked_add(payout).ok_or(ErrorCode::Overflow)?;
+    p.claimed = p.amount_claimed == entitled;
+
+    Ok((payout, voided, first_claim))
+}
+
+fn record_win(us: &mut UserStats, payout: u64, first_claim: bool) -> Result<()> {
+    us.total_won = us.total_won.checked_add(payout).ok_or(ErrorCode::Overflow)?;
+    if first_claim {
+        us.win_count = us.win_count.checked_add(1).ok_or(ErrorCode::Overflow)?;
+    }
+    Ok(())
+}
+
+/// Everything owed to position holders once resolved, less any uncovered yield shortfall.
+fn claimable_pool(market: &Market) -> Result<u64> {
+    Ok(gross_claimable_pool(market)?.saturating_sub(market.yield_loss))
+}
+
+/// The whole stake pool, plus the winner bonus and losing-side subsidy when not voided.
+fn gross_claimable_pool(market: &Market) -> Result<u64> {
+    let stakes = total_staked(market)?;
+    if market.winning_outcome == Outcome::Void as u8 {
+        return Ok(stakes);
+    }
+    stakes
+        .checked_add(market.winner_bonus)
+        .and_then(|v| v.checked_add(losing_subsidy(market, market.winning_outcome)))
+        .ok_or(error!(ErrorCode::Overflow))
+}
+
+/// Tokens the vault must still hold for this market: unpaid claims, unpaid subsidy refunds and unswept fees.
+fn outstanding_liabilities(market: &Market) -> Result<u64> {
+    let (owed, subsidies) = if !market.resolved {
+        let stakes = total_staked(market)?.saturating_sub(market.yield_loss);
+        (stakes, market.subsidy_yes.checked_add(market.subsidy_no).ok_or(ErrorCode::Overflow)?)
+    } else {
+        // Odds-cap excess was moved into fees_accrued, so it is not owed twice.
+        let owed = claimable_pool(market)?
+            .checked_sub(market.total_claimed)
+            .and_then(|v| v.checked_sub(market.clipped_excess))
+            .ok_or(ErrorCode::Overflow)?;
+        let refundable = if market.winning_outcome == Outcome::Void as u8 {
+            market.subsidy_yes.checked_add(market.subsidy_no).ok_or(ErrorCode::Overflow)?
+        } else if market.winning_outcome == Outcome::Yes as u8 {
+            market.subsidy_yes
+        } else if market.winning_outcome == Outcome::No as u8 {
+            market.subsidy_no
+        } else {
+            0
+        };
+        (owed, refundable.checked_sub(market.subsidy_refunded).ok_or(ErrorCode::Overflow)?)
+    };
+    owed.checked_add(subsidies)
+        .and_then(|v| v.checked_add(market.fees_accrued))
+        .and_then(|v| v.checked_add(market.keeper_tip))
+        .and_then(|v| v.checked_add(market.bridge_refunds_due))
+        .and_then(|v| v.checked_add(market.set_collateral))
+        .ok_or(error!(ErrorCode::Overflow))
+}
+
+fn validate_config_change(change: &ConfigChange) -> Result<()> {
+    match change {
+        ConfigChange::FeeReceiver { receiver, .. } => {
+            require!(*receiver != Pubkey::default(), ErrorCode::InvalidFeeReceiver);
+        }
+        ConfigChange::Cutoff { .. } => {}
+        ConfigChange::MinDuration { min_duration } => {
+            require!(
+                (0..=MAX_MARKET_DURATION).contains(min_duration),
+                ErrorCode::MarketDurationTooShort
+            );
+        }
+        ConfigChange::Timelock { timelock_secs } => {
+            require!((0..=MAX_TIMELOCK).contains(timelock_secs), ErrorCode::InvalidTimelock);
+        }
+        ConfigChange::WinnerRake { winner_rake_bps: bps, .. }
+        | ConfigChange::InsuranceShare { fee_share_bps: bps }
+        | ConfigChange::JackpotShare { fee_share_bps: bps, .. } => {
+            require!(*bps as u64 <= BPS_DENOM, ErrorCode::InvalidBps);
+        }
+        ConfigChange::MaxMultiplier { max_multiplier_bps, .. } => {
+            require!(
+                *max_multiplier_bps == 0 || *max_multiplier_bps as u64 >= BPS_DENOM,
+                ErrorCode::InvalidBps
+            );
+        }
+        ConfigChange::FeeDiscount { discount_bps, .. } => {
+            require!(*discount_bps as u64 <= BPS_DENOM, ErrorCode::InvalidBps);
+        }
+        ConfigChange::EventTs { .. }
+        | ConfigChange::ReportSigner { .. }
+        | ConfigChange::VaaEmitter { .. }
+        | ConfigChange::Governance { .. } => {}
+    }
+    Ok(())
+}
+
+/// The market a queued change targets, which must be the one passed in.
+fn change_target<'a, 'info>(
+    market: &'a mut Option<Account<'info, Market>>,
+    key: Pubkey,
+) -> Result<&'a mut Account<'info, Market>> {
+    let m = market.as_mut().ok_or(ErrorCode::WrongMarket)?;
+    require_keys_eq!(m.key(), key, ErrorCode::WrongMarket);
+    Ok(m)
+}
+
+fn apply_cutoff(m: &mut Account<Market>, new_cutoff_ts: i64, min_duration: i64, now: i64) -> Result<()> {
+    validate_market_active(m)?;
+    require!(new_cutoff_ts > now, ErrorCode::CutoffInPast);
+    require!(new_cutoff_ts <= now + MAX_MARKET_DURATION, ErrorCode::MarketDurationTooLong);
+    require!(new_cutoff_ts < m.claim_deadline_ts, ErrorCode::InvalidClaimDeadline);
+    require!(
+        new_cutoff_ts >= m.created_at.checked_add(min_duration).ok_or(ErrorCode::Overflow)?,
+        ErrorCode::MarketDurationTooShort
+    );
+
+    m.cutoff_ts = new_cutoff_ts;
+    // The event can never happen before betting closes.
+    if m.event_ts < new_cutoff_ts {
+        m.event_ts = new_cutoff_ts;
+    }
+
+    emit!(CutoffUpdated {
+        market: m.key(),
+        new_cutoff_ts,
+    });
+
+    Ok(())
+}
+
+fn apply_fee_receiver(m: &mut Account<Market>, new_receiver: Pubkey) -> Result<()> {
+    require!(new_receiver != Pubkey::default(), ErrorCode::InvalidFeeReceiver);
+    m.fee_receiver = new_receiver;
+
+    emit!(FeeReceiverUpdated {
+        market: m.key(),
+        new_receiver,
+    });
+
+    Ok(())
+}
+
+fn apply_winner_rake(m: &mut Account<Market>, winner_rake_bps: u16) -> Result<()> {
+    require!(winner_rake_bps as u64 <= BPS_DENOM, ErrorCode::InvalidBps);
+    validate_market_active(m)?;
+    require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+    m.winner_rake_bps = winner_rake_bps;
+
+    emit!(WinnerRakeUpdated {
+        market: m.key(),
+        winner_rake_bps,
+    });
+
+    Ok(())
+}
+
+fn apply_max_multiplier(m: &mut Account<Market>, max_multiplier_bps: u32) -> Result<()> {
+    require!(
+        max_multiplier_bps == 0 || max_multiplier_bps as u64 >= BPS_DENOM,
+        ErrorCode::InvalidBps
+    );
+    validate_market_active(m)?;
+    require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+    m.max_multiplier_bps = max_multiplier_bps;
+
+    emit!(MaxMultiplierUpdated {
+        market: m.key(),
+        max_multiplier_bps,
+    });
+
+    Ok(())
+}
+
+fn apply_event_ts(m: &mut Account<Market>, new_event_ts: i64) -> Result<()> {
+    validate_market_active(m)?;
+    require!(new_event_ts >= m.cutoff_ts, ErrorCode::InvalidEventTs);
+    require!(new_event_ts < m.claim_deadline_ts, ErrorCode::InvalidClaimDeadline);
+    m.event_ts = new_event_ts;
+
+    emit!(EventTsUpdated {
+        market: m.key(),
+        new_event_ts,
+    });
+
+    Ok(())
+}
+
+fn apply_report_signer(m: &mut Account<Market>, report_signer: Pubkey) -> Result<()> {
+    validate_market_active(m)?;
+    m.report_signer = report_signer;
+
+    emit!(ReportSignerUpdated {
+        market: m.key(),
+        report_signer,
+    });
+
+    Ok(())
+}
+
+fn apply_vaa_emitter(m: &mut Account<Market>, emitter_chain: u16, emitter: [u8; 32]) -> Result<()> {
+    validate_market_active(m)?;
+    m.vaa_emitter_chain = emitter_chain;
+    m.vaa_emitter = emitter;
+
+    emit!(VaaEmitterUpdated {
+        market: m.key(),
+        emitter_chain,
+        emitter,
+    });
+
+    Ok(())
+}
+
+fn apply_governance(config: &mut ProgramConfig, governance_mint: Pubkey, vote_quorum: u64) -> Result<()> {
+    config.governance_mint = governance_mint;
+    config.vote_quorum = vote_quorum;
+
+    emit!(GovernanceUpdated {
+        governance_mint,
+        vote_quorum,
+    });
+
+    Ok(())
+}
+
+fn apply_fee_discount(
+    d: &mut FeeDiscount,
+    key: Pubkey,
+    discount_bps: u16,
+    is_collection: bool,
+    enabled: bool,
+) -> Result<()> {
+    require!(discount_bps as u64 <= BPS_DENOM, ErrorCode::InvalidBps);
+    d.key = key;
+    d.discount_bps = discount_bps;
+    d.is_collection = is_collection;
+    d.enabled = enabled;
+
+    emit!(FeeDiscountUpdated {
+        key,
+        discount_bps,
+        is_collection,
+        enabled,
+    });
+
+    Ok(())
+}
+
+fn apply_min_duration(config: &mut ProgramConfig, min_duration: i64) -> Result<()> {
+    require!(
+        (0..=MAX_MARKET_DURATION).contains(&min_duration),
+        ErrorCode::MarketDurationTooShort
+    );
+    config.min_duration = min_duration;
+
+    emit!(MinDurationUpdated { min_duration });
+
+    Ok(())
+}
+
+fn apply_timelock(config: &mut ProgramConfig, timelock_secs: i64) -> Result<()> {
+    require!((0..=MAX_TIMELOCK).contains(&timelock_secs), ErrorCode::InvalidTimelock);
+    config.timelock_secs = timelock_secs;
+
+    emit!(TimelockUpdated { timelock_secs });
+
+    Ok(())
+}
+
+/// Updates the resolver's record and releases the dispute bond to the winning side.
+fn settle_dispute(
+    m: &mut Account<Market>,
+    entry: Option<&mut Account<ResolverEntry>>,
+    disputer: Option<&UncheckedAccount>,
+    upheld: bool,
+) -> Result<()> {
+    let entry = entry.ok_or(ErrorCode::WrongResolver)?;
+    require_keys_eq!(entry.authority, m.resolver, ErrorCode::WrongResolver);
+    entry.markets_resolved = entry.markets_resolved.checked_add(1).ok_or(ErrorCode::Overflow)?;
+
+    m.sub_lamports(DISPUTE_BOND)?;
+    if upheld {
+        entry.stake = entry.stake.checked_add(DISPUTE_BOND).ok_or(ErrorCode::Overflow)?;
+        entry.add_lamports(DISPUTE_BOND)?;
+    } else {
+        entry.disputes_lost = entry.disputes_lost.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        let disputer = disputer.ok_or(ErrorCode::WrongDisputer)?;
+        require_keys_eq!(disputer.key(), m.disputer, ErrorCode::WrongDisputer);
+        disputer.add_lamports(DISPUTE_BOND)?;
+    }
+
+    emit!(DisputeSettled {
+        market: m.key(),
+        resolver: m.resolver,
+        disputer: m.disputer,
+        upheld,
+    });
+
+    Ok(())
+}
+
+fn outcome_from_u8(m: &Market, value: u8) -> Result<Outcome> {
+    match value {
+        v if v == Outcome::Yes as u8 => Ok(Outcome::Yes),
+        v if v == Outcome::No as u8 => Ok(Outcome::No),
+        v if v == Outcome::Draw as u8 && m.three_way => Ok(Outcome::Draw),
+        v if v == Outcome::Void as u8 => Ok(Outcome::Void),
+        _ => err!(ErrorCode::InvalidOutcomeArg),
+    }
+}
+
+/// Final step of the dispute path: settles any resolver dispute bond and resolves (or voids) the market.
+#[allow(clippy::too_many_arguments)]
+fn conclude_dispute(
+    m: &mut Account<Market>,
+    stats: &mut ProgramStats,
+    instructions: &AccountInfo,
+    resolver_entry: Option<&mut Account<ResolverEntry>>,
+    disputer: Option<&UncheckedAccount>,
+    outcome: Outcome,
+    reason: ResolutionReason,
+    now: i64,
+) -> Result<()> {
+    if m.disputer != Pubkey::default() {
+        let upheld = outcome as u8 == m.proposed_outcome;
+        settle_dispute(m, resolver_entry, disputer, upheld)?;
+    }
+    m.dispute_stage = DisputeStage::Closed as u8;
+
+    if outcome == Outcome::Void {
+        return void_market_state(m, stats, reason, now);
+    }
+    emit!(settle_market(m, stats, outcome, reason, instructions)?);
+    Ok(())
 }
 
-#[event]
-pub struct WinningsClaimed {
-    pub market: Pubkey,
-    pub bettor: Pubkey,
-    pub payout: u64,
-    pub position_amount: u64,
-    pub outcome: u8,
-    pub winning_outcome: u8,
+fn pnl_realized(market: Pubkey, bettor: Pubkey, p: &Position, payout: u64) -> Result<PnlRealized> {
+    let cost = (p.amount as i128)
+        .checked_add(p.fees_paid as i128)
+        .ok_or(ErrorCode::Overflow)?;
+    let pnl = i64::try_from((p.amount_claimed as i128) - cost).map_err(|_| error!(ErrorCode::Overflow))?;
+    Ok(PnlRealized {
+        market,
+        bettor,
+        position_index: p.index,
+        stake: p.amount,
+        fee_paid: p.fees_paid,
+        payout,
+        total_payout: p.amount_claimed,
+        pnl,
+        closed: p.claimed,
+    })
 }
 
-#[event]
-pub struct FeesCollected {
-    pub market: Pubkey,
-    pub amount: u64,
-    pub recipient: Pubkey,
+/// Opt-in `place_bet` features, passed as `remaining_accounts` groups.
+#[derive(Default)]
+struct BetExtras<'info> {
+    holder_discount: Option<&'info [AccountInfo<'info>]>,
+    season_pass: Option<&'info [AccountInfo<'info>]>,
+    rewards: Option<&'info [AccountInfo<'info>]>,
+    receipt: Option<&'info [AccountInfo<'info>]>,
 }
 
-#[event]
-pub struct MarketPaused {
-    pub market: Pubkey,
-    pub paused: bool,
+fn bet_extras<'info>(accounts: &'info [AccountInfo<'info>]) -> Result<BetExtras<'info>> {
+    let mut extras = BetExtras::default();
+    let mut rest = accounts;
+    while let Some(lead) = rest.first() {
+        require_keys_eq!(*lead.owner, crate::ID, ErrorCode::InvalidBetExtras);
+        let data = lead.try_borrow_data()?;
+        let (slot, len) = if data.starts_with(FeeDiscount::DISCRIMINATOR) {
+            (&mut extras.holder_discount, 3)
+        } else if data.starts_with(SeasonPassConfig::DISCRIMINATOR) {
+            (&mut extras.season_pass, 2)
+        } else if data.starts_with(RewardConfig::DISCRIMINATOR) {
+            (&mut extras.rewards, 5)
+        } else if data.starts_with(ReceiptConfig::DISCRIMINATOR) {
+            (&mut extras.receipt, 7)
+        } else {
+            return err!(ErrorCode::InvalidBetExtras);
+        };
+        require!(slot.is_none() && rest.len() >= len, ErrorCode::InvalidBetExtras);
+        *slot = Some(&rest[..len]);
+        rest = &rest[len..];
+    }
+    Ok(extras)
 }
 
-#[event]
-pub struct FeeReceiverUpdated {
-    pub market: Pubkey,
-    pub new_receiver: Pubkey,
+/// Mints a compressed NFT to the bettor describing the bet.
+fn mint_bet_receipt<'info>(
+    a: &PlaceBet<'info>,
+    group: &'info [AccountInfo<'info>],
+    market: Pubkey,
+    outcome: Outcome,
+    stake: u64,
+) -> Result<()> {
+    let [rc, tree, tree_config, authority, bubblegum, log_wrapper, compression] = group else {
+        return err!(ErrorCode::InvalidBetExtras);
+    };
+    let rc: Account<ReceiptConfig> = Account::try_from(rc)?;
+    require_keys_eq!(tree.key(), rc.merkle_tree, ErrorCode::WrongReceiptTree);
+    require_keys_eq!(bubblegum.key(), mpl_bubblegum::ID, ErrorCode::InvalidBetExtras);
+    require_keys_eq!(log_wrapper.key(), SPL_NOOP_ID, ErrorCode::InvalidBetExtras);
+    require_keys_eq!(compression.key(), SPL_ACCOUNT_COMPRESSION_ID, ErrorCode::InvalidBetExtras);
+    let receipt_authority = Pubkey::create_program_address(&[RECEIPT_AUTH_SEED, &[rc.authority_bump]], &crate::ID)
+        .map_err(|_| error!(ErrorCode::InvalidBetExtras))?;
+    require_keys_eq!(authority.key(), receipt_authority, ErrorCode::InvalidBetExtras);
+    if !rc.enabled {
+        return Ok(());
+    }
+
+    let side = match outcome {
+        Outcome::Yes => "yes",
+        Outcome::No => "no",
+        Outcome::Draw => "draw",
+        _ => return err!(ErrorCode::InvalidOutcomeArg),
+    };
+    let metadata = MetadataArgs {
+        name: format!("Bet {} {}", side.to_uppercase(), stake),
+        symbol: "SILLY".to_string(),
+        uri: format!("{}/{}/{}/{}", rc.uri_base, market, side, stake),
+        seller_fee_basis_points: 0,
+        primary_sale_happened: true,
+        is_mutable: false,
+        edition_nonce: None,
+        token_standard: Some(TokenStandard::NonFungible),
+        collection: None,
+        uses: None,
+        token_program_version: TokenProgramVersion::Original,
+        creators: vec![],
+    };
+
+    let seeds: &[&[u8]] = &[RECEIPT_AUTH_SEED, &[rc.authority_bump]];
+    MintV1CpiBuilder::new(bubblegum)
+        .tree_config(tree_config)
+        .leaf_owner(&a.bettor.to_account_info())
+        .leaf_delegate(&a.bettor.to_account_info())
+        .merkle_tree(tree)
+        .payer(&a.payer.to_account_info())
+        .tree_creator_or_delegate(authority)
+        .log_wrapper(log_wrapper)
+        .compression_program(compression)
+        .system_program(&a.system_program.to_account_info())
+        .metadata(metadata)
+        .invoke_signed(&[seeds])?;
+
+    Ok(())
 }
 
-#[derive(Accounts)]
-pub struct InitializeProgram<'info> {
-    #[account(mut)]
-    pub owner: Signer<'info>,
+fn validate_bet_caller(market: &Market) -> Result<()> {
+    if market.block_cpi {
+        require!(get_stack_height() == TRANSACTION_LEVEL_STACK_HEIGHT, ErrorCode::CpiNotAllowed);
+    }
+    Ok(())
+}
 
-    #[account(
-        init,
-        payer = owner,
-        space = ProgramStats::LEN,
-        seeds = [b"program-stats"],
-        bump
-    )]
-    pub program_stats: Account<'info, ProgramStats>,
+/// Sets aside `KEEPER_TIP_BPS` of accrued fees for the caller of a settlement crank.
+fn reserve_keeper_tip(m: &mut Account<Market>, keeper: Pubkey) -> Result<()> {
+    if m.multi_mint {
+        return Ok(());
+    }
+    let tip = m.fees_accrued
+        .checked_mul(KEEPER_TIP_BPS)
+        .ok_or(ErrorCode::Overflow)?
+        / BPS_DENOM;
+    if tip == 0 {
+        return Ok(());
+    }
+    m.fees_accrued -= tip;
+    m.keeper_tip = m.keeper_tip.checked_add(tip).ok_or(ErrorCode::Overflow)?;
+    m.keeper = keeper;
 
-    pub system_program: Program<'info, System>,
+    emit!(KeeperTipReserved {
+        market: m.key(),
+        keeper,
+        amount: tip,
+    });
+    Ok(())
 }
 
-#[derive(Accounts)]
-pub struct CreateMarket<'info> {
-    #[account(mut)]
-    pub owner: Signer<'info>,
+fn market_guard_hash(question: &str, cutoff_ts: i64, bet_mint: &Pubkey) -> [u8; 32] {
+    keccak::hashv(&[question.as_bytes(), &cutoff_ts.to_le_bytes(), bet_mint.as_ref()]).0
+}
 
-    #[account(init, payer = owner, space = Market::LEN)]
-    pub market: Account<'info, Market>,
+fn validate_pool_share(market: &Market, outcome: Outcome, amount: u64) -> Result<()> {
+    if market.max_bet_pool_bps == 0 {
+        return Ok(());
+    }
+    let opposing = total_staked(market)?
+        .checked_sub(outcome_pool(market, outcome as u8))
+        .ok_or(ErrorCode::Overflow)?;
+    if opposing < market.bet_cap_min_pool {
+        return Ok(());
+    }
+    let cap = (opposing as u128)
+        .checked_mul(market.max_bet_pool_bps as u128)
+        .ok_or(ErrorCode::Overflow)?
+        / BPS_DENOM as u128;
+    require!(amount as u128 <= cap, ErrorCode::BetTooLargeForPool);
+    Ok(())
+}
 
-    #[account(
-        init,
-        payer = owner,
-        space = MarketMetadata::LEN,
-        seeds = [b"market-metadata", market.key().as_ref()],
-        bump
-    )]
-    pub market_metadata: Account<'info, MarketMetadata>,
+fn set_position_frozen(ctx: Context<SetPositionFrozen>, frozen: bool, reason_uri: String) -> Result<()> {
+    require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+    require!(reason_uri.len() <= MAX_EVIDENCE_URI_LEN, ErrorCode::MetadataTooLong);
 
-    #[account(mut, seeds = [b"program-stats"], bump)]
-    pub program_stats: Account<'info, ProgramStats>,
+    let p = &mut ctx.accounts.position;
+    p.frozen = frozen;
 
-    pub bet_mint: Account<'info, Mint>,
+    emit!(PositionFreezeChanged {
+        market: ctx.accounts.market.key(),
+        position: p.key(),
+        owner: p.owner,
+        frozen,
+        reason_uri,
+        ts: Clock::get()?.unix_timestamp,
+    });
 
-    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
-    pub vault_authority: UncheckedAccount<'info>,
+    Ok(())
+}
 
-    #[account(
-        init,
-        payer = owner,
-        associated_token::mint = bet_mint,
-        associated_token::authority = vault_authority
-    )]
-    pub vault: Account<'info, TokenAccount>,
+/// Enforces the market's per-wallet bet cooldown and stamps the position.
+fn apply_bet_cooldown(m: &Market, p: &mut Position, position_index: u16, now: i64) -> Result<()> {
+    if m.bet_cooldown_secs > 0 {
+        // One position per wallet, or extra indexes would sidestep the cooldown.
+        require!(position_index == 0, ErrorCode::InvalidPosition);
+        let ready_ts = p.last_bet_ts.checked_add(m.bet_cooldown_secs).ok_or(ErrorCode::Overflow)?;
+        require!(p.amount == 0 || now >= ready_ts, ErrorCode::BetCooldown);
+    }
+    p.last_bet_ts = now;
+    Ok(())
+}
 
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
+/// Books a bet on the bettor's position and lifetime stats.
+#[allow(clippy::too_many_arguments)]
+fn credit_bettor(
+    p: &mut Position,
+    us: &mut UserStats,
+    portfolio: Option<&mut Portfolio>,
+    market: Pubkey,
+    bettor: Pubkey,
+    user_stats_bump: u8,
+    outcome: Outcome,
+    received: u64,
+    fee: u64,
+    net: u64,
+    position_index: u16,
+) -> Result<()> {
+    if us.owner == Pubkey::default() {
+        us.owner = bettor;
+        us.bump = user_stats_bump;
+    }
+    us.total_wagered = us.total_wagered.checked_add(received).ok_or(ErrorCode::Overflow)?;
+    if p.amount == 0 {
+        us.markets_participated = us.markets_participated.checked_add(1).ok_or(ErrorCode::Overflow)?;
+    }
+
+    require!(position_index < MAX_POSITIONS_PER_MARKET, ErrorCode::PositionIndexTooHigh);
+    if p.amount == 0 {
+        if let Some(pf) = portfolio {
+            pf.track(market)?;
+        }
+        p.owner = bettor;
+        p.market = market;
+        p.outcome = outcome as u8;
+        p.claimed = false;
+        p.amount = net;
+        p.index = position_index;
+        p.amount_claimed = 0;
+        p.fees_paid = fee;
+        p.frozen = false;
+    } else {
+        require!(p.outcome == outcome as u8, ErrorCode::CannotSwitchSide);
+        p.amount = p.amount.checked_add(net).ok_or(ErrorCode::Overflow)?;
+        p.fees_paid = p.fees_paid.checked_add(fee).ok_or(ErrorCode::Overflow)?;
+    }
+    Ok(())
 }
 
-#[derive(Accounts)]
-pub struct UpdateCutoff<'info> {
-    pub owner: Signer<'info>,
-    #[account(mut)]
-    pub market: Account<'info, Market>,
+/// Settlement needs every pool shard folded into the market totals.
+fn require_shards_merged(m: &Market) -> Result<()> {
+    require!(
+        m.shards_merged.count_ones() == m.shard_count as u32,
+        ErrorCode::ShardsUnmerged
+    );
+    Ok(())
 }
 
-#[derive(Accounts)]
-pub struct PlaceBet<'info> {
-    #[account(mut)]
-    pub bettor: Signer<'info>,
+/// Discount the bettor earns by holding the NFT in `token`.
+fn holder_discount_bps(
+    discount: &FeeDiscount,
+    token: &TokenAccount,
+    metadata: &AccountInfo,
+    bettor: Pubkey,
+) -> Result<u16> {
+    if !discount.enabled {
+        return Ok(0);
+    }
+    require_keys_eq!(token.owner, bettor, ErrorCode::NotNftHolder);
+    require!(token.amount >= 1, ErrorCode::NotNftHolder);
 
-    #[account(mut, has_one = bet_mint)]
-    pub market: Account<'info, Market>,
+    if !discount.is_collection {
+        require_keys_eq!(token.mint, discount.key, ErrorCode::NotNftHolder);
+        return Ok(discount.discount_bps);
+    }
 
-    #[account(mut, seeds = [b"program-stats"], bump)]
-    pub program_stats: Account<'info, ProgramStats>,
+    require_keys_eq!(*metadata.owner, mpl_token_metadata::ID, ErrorCode::NotNftHolder);
+    require_keys_eq!(metadata.key(), Metadata::find_pda(&token.mint).0, ErrorCode::NotNftHolder);
+    let data = metadata.try_borrow_data()?;
+    let md = Metadata::safe_deserialize(&data).map_err(|_| error!(ErrorCode::NotNftHolder))?;
+    let verified = md
+        .collection
+        .is_some_and(|c| c.verified && c.key == discount.key);
+    require!(verified, ErrorCode::NotNftHolder);
 
-    pub bet_mint: Account<'info, Mint>,
+    Ok(discount.discount_bps)
+}
 
-    #[account(
-        mut,
-        associated_token::mint = bet_mint,
-        associated_token::authority = bettor
-    )]
-    pub bettor_ata: Account<'info, TokenAccount>,
+fn effective_fee_bps(market: &Market, discount_bps: u16) -> u64 {
+    let fee_bps = market_fee_bps(market);
+    fee_bps - fee_bps * discount_bps as u64 / BPS_DENOM
+}
 
-    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
-    pub vault_authority: UncheckedAccount<'info>,
+fn discounted_fee(fee: u64, discount_bps: u16) -> Result<u64> {
+    let off = (fee as u128)
+        .checked_mul(discount_bps as u128)
+        .ok_or(ErrorCode::Overflow)?
+        / BPS_DENOM as u128;
+    fee.checked_sub(off as u64).ok_or(error!(ErrorCode::Overflow))
+}
 
-    #[account(
-        mut,
-        associated_token::mint = bet_mint,
-        associated_token::authority = vault_authority
-    )]
-    pub vault: Account<'info, TokenAccount>,
+/// Rejects a resolution sharing its transaction with a value move or a wrapper on the market.
+fn require_isolated_resolution(instructions: &AccountInfo, market: &Pubkey) -> Result<()> {
+    let blocked: &[&[u8]] = &[
+        instruction::PlaceBet::DISCRIMINATOR,
+        instruction::PlaceBetSharded::DISCRIMINATOR,
+        instruction::PlaceBetMulti::DISCRIMINATOR,
+        instruction::PlaceBetWithSession::DISCRIMINATOR,
+        instruction::PlaceLimitBet::DISCRIMINATOR,
+        instruction::ExecuteLimitBet::DISCRIMINATOR,
+        instruction::CancelLimitBet::DISCRIMINATOR,
+        instruction::PlaceOrder::DISCRIMINATOR,
+        instruction::MatchOrders::DISCRIMINATOR,
+        instruction::BridgeDeposit::DISCRIMINATOR,
+        instruction::BridgeOut::DISCRIMINATOR,
+        instruction::SubsidizeMarket::DISCRIMINATOR,
+        instruction::SplitSet::DISCRIMINATOR,
+        instruction::MergeSet::DISCRIMINATOR,
+        instruction::RedeemOutcome::DISCRIMINATOR,
+        instruction::ClaimWinnings::DISCRIMINATOR,
+        instruction::ClaimAndBet::DISCRIMINATOR,
+        instruction::ClaimMultiMint::DISCRIMINATOR,
+        instruction::DistributePayouts::DISCRIMINATOR,
+        instruction::ClaimInsurance::DISCRIMINATOR,
+        instruction::PayFromInsurance::DISCRIMINATOR,
+        instruction::ClaimMerkleRefunds::DISCRIMINATOR,
+        instruction::ClaimSubsidyRefund::DISCRIMINATOR,
+        instruction::ClaimRewards::DISCRIMINATOR,
+        instruction::ClaimKeeperTip::DISCRIMINATOR,
+        instruction::DistributeRebate::DISCRIMINATOR,
+        instruction::SettleHouse::DISCRIMINATOR,
+        instruction::WithdrawHouse::DISCRIMINATOR,
+        instruction::SettleJackpotDraw::DISCRIMINATOR,
+        instruction::SweepFees::DISCRIMINATOR,
+        instruction::SweepMintFees::DISCRIMINATOR,
+        instruction::SkimSurplus::DISCRIMINATOR,
+        instruction::EmergencyWithdraw::DISCRIMINATOR,
+        instruction::CloseVault::DISCRIMINATOR,
+        instruction::MergePoolShards::DISCRIMINATOR,
+        instruction::DepositIdle::DISCRIMINATOR,
+        instruction::WithdrawIdle::DISCRIMINATOR,
+        instruction::StakeIdle::DISCRIMINATOR,
+        instruction::UnstakeIdle::DISCRIMINATOR,
+    ];
+    let sandwiched = (0..)
+        .map_while(|i| load_instruction_at_checked(i, instructions).ok())
+        .any(|ix| {
+            if ix.program_id == crate::ID {
+                blocked.iter().any(|d| ix.data.starts_with(d))
+            } else {
+                ix.accounts.iter().any(|a| a.pubkey == *market)
+            }
+        });
+    require!(!sandwiched, ErrorCode::ResolutionSandwich);
+    Ok(())
+}
 
-    #[account(
-        init_if_needed,
-        payer = bettor,
-        associated_token::mint = bet_mint,
-        associated_token::authority = fee_wallet
-    )]
-    pub fee_receiver_ata: Account<'info, TokenAccount>,
+/// Payout multiple, in bps, a winning `outcome` stake would get at the current pools.
+fn implied_odds_bps(market: &Market, outcome: Outcome) -> Result<u64> {
+    let side = outcome_pool(market, outcome as u8) as u128;
+    if side == 0 {
+        return Ok(u64::MAX);
+    }
+    let odds = (total_staked(market)? as u128)
+        .checked_mul(BPS_DENOM as u128)
+        .ok_or(ErrorCode::Overflow)?
+        / side;
+    Ok(u64::try_from(odds).unwrap_or(u64::MAX))
+}
 
-    #[account(address = fee_wallet_pubkey())]
-    pub fee_wallet: UncheckedAccount<'info>,
+/// Checks the previous instruction is an Ed25519 check of `signer`'s signature over `message`.
+fn verify_signed_report(instructions: &AccountInfo, signer: &Pubkey, message: &[u8]) -> Result<()> {
+    let current = load_current_index_checked(instructions)? as usize;
+    require!(current > 0, ErrorCode::InvalidReport);
+    let ix = load_instruction_at_checked(current - 1, instructions)?;
+    require_keys_eq!(ix.program_id, ed25519_program::ID, ErrorCode::InvalidReport);
 
-    #[account(
-        init_if_needed,
-        payer = bettor,
-        space = Position::LEN,
-        seeds = [POSITION_SEED, market.key().as_ref(), bettor.key().as_ref()],
-        bump
-    )]
-    pub position: Account<'info, Position>,
+    // Header: count (u8), padding (u8), then seven u16 offsets for the one
+    // signature; `u16::MAX` instruction indices mean "this instruction".
+    let data = &ix.data;
+    require!(data.len() >= 16 && data[0] == 1, ErrorCode::InvalidReport);
+    let read = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]) as usize;
+    let inline = u16::MAX as usize;
+    require!(
+        read(4) == inline && read(8) == inline && read(14) == inline,
+        ErrorCode::InvalidReport
+    );
+    let (pubkey_at, message_at, message_len) = (read(6), read(10), read(12));
+    require!(
+        data.get(pubkey_at..pubkey_at + 32) == Some(signer.as_ref())
+            && data.get(message_at..message_at + message_len) == Some(message),
+        ErrorCode::InvalidReport
+    );
+    Ok(())
+}
 
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
+/// Reads the emitter, timestamp and payload of a Borsh `PostedVAAV1`.
+fn parse_posted_vaa(data: &[u8]) -> Result<PostedVaa<'_>> {
+    const PAYLOAD_AT: usize = 95;
+    require!(data.len() >= PAYLOAD_AT && data.starts_with(b"vaa"), ErrorCode::InvalidVaa);
+    let len = u32::from_le_bytes(data[91..95].try_into().map_err(|_| ErrorCode::InvalidVaa)?) as usize;
+    let payload = data.get(PAYLOAD_AT..PAYLOAD_AT + len).ok_or(ErrorCode::InvalidVaa)?;
+    Ok(PostedVaa {
+        timestamp: u32::from_le_bytes(data[5..9].try_into().map_err(|_| ErrorCode::InvalidVaa)?),
+        emitter_chain: u16::from_le_bytes(data[57..59].try_into().map_err(|_| ErrorCode::InvalidVaa)?),
+        emitter: data[59..91].try_into().map_err(|_| ErrorCode::InvalidVaa)?,
+        payload,
+    })
 }
 
-#[derive(Accounts)]
-pub struct ResolveMarket<'info> {
-    pub owner: Signer<'info>,
-    #[account(mut)]
-    pub market: Account<'info, Market>,
+/// Returns the app payload of a Token Bridge transfer-with-payload sent to `redeemer`.
+fn parse_bridge_payload<'a>(vaa_payload: &'a [u8], redeemer: &Pubkey) -> Result<&'a [u8]> {
+    const TO_AT: usize = 1 + 32 + 32 + 2;
+    const PAYLOAD_AT: usize = TO_AT + 32 + 2 + 32;
+    require!(vaa_payload.len() >= PAYLOAD_AT && vaa_payload[0] == 3, ErrorCode::InvalidVaa);
+    require!(vaa_payload[TO_AT..TO_AT + 32] == redeemer.to_bytes(), ErrorCode::InvalidVaa);
+    Ok(&vaa_payload[PAYLOAD_AT..])
 }
 
-#[derive(Accounts)]
-pub struct ClaimWinnings<'info> {
-    #[account(mut)]
-    pub bettor: Signer<'info>,
+/// Forwards `accounts` to the Token Bridge as-is, adding the vault authority's signature.
+fn bridge_instruction(accounts: &[AccountInfo], vault_authority: &Pubkey, data: Vec<u8>) -> Instruction {
+    Instruction {
+        program_id: TOKEN_BRIDGE_ID,
+        accounts: accounts
+            .iter()
+            .map(|a| AccountMeta {
+                pubkey: a.key(),
+                is_signer: a.is_signer || a.key == vault_authority,
+                is_writable: a.is_writable,
+            })
+            .collect(),
+        data,
+    }
+}
 
-    #[account(mut, has_one = bet_mint)]
-    pub market: Account<'info, Market>,
+/// Tops up the thinner side from the house within its limits and balance.
+fn house_match(m: &mut Market, house: &mut House, available: u64) -> Result<(Outcome, u64)> {
+    let (side, gap) = if m.total_yes < m.total_no {
+        (Outcome::Yes, m.total_no - m.total_yes)
+    } else {
+        (Outcome::No, m.total_yes - m.total_no)
+    };
+    let staked = m.house_yes.checked_add(m.house_no).ok_or(ErrorCode::Overflow)?;
+    let amount = gap
+        .min(m.house_limit.saturating_sub(staked))
+        .min(house.max_exposure.saturating_sub(house.exposure))
+        .min(available);
+    if amount == 0 {
+        return Ok((side, 0));
+    }
 
-    pub bet_mint: Account<'info, Mint>,
+    if side == Outcome::Yes {
+        m.total_yes = m.total_yes.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        m.house_yes = m.house_yes.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+    } else {
+        m.total_no = m.total_no.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        m.house_no = m.house_no.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+    }
+    house.exposure = house.exposure.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+    Ok((side, amount))
+}
 
-    #[account(
-        mut,
-        associated_token::mint = bet_mint,
-        associated_token::authority = bettor
-    )]
-    pub bettor_ata: Account<'info, TokenAccount>,
+/// What the house's implicit position is owed once the market is settled.
+fn house_payout(m: &Market) -> Result<u64> {
+    if m.winning_outcome == Outcome::Void as u8 {
+        return m.house_yes.checked_add(m.house_no).ok_or(error!(ErrorCode::Overflow));
+    }
+    let mut payout: u64 = 0;
+    for (outcome, stake) in [(Outcome::Yes, m.house_yes), (Outcome::No, m.house_no)] {
+        let wins = m.winning_outcome == outcome as u8 || m.winning_outcome == Outcome::Split as u8;
+        if stake == 0 || !wins {
+            continue;
+        }
+        let house = Position {
+            owner: Pubkey::default(),
+            market: Pubkey::default(),
+            outcome: outcome as u8,
+            claimed: false,
+            amount: stake,
+            index: 0,
+            amount_claimed: 0,
+            fees_paid: 0,
+            last_bet_ts: 0,
+            frozen: false,
+            insured: 0,
+            reward_debt: 0,
+            rewards_owed: 0,
+        };
+        payout = payout.checked_add(calculate_payout(m, &house)?).ok_or(ErrorCode::Overflow)?;
+    }
+    Ok(payout)
+}
 
-    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
-    pub vault_authority: UncheckedAccount<'info>,
+/// The seeds constraint ties `approved_mint` to the bet mint, so its presence is the approval.
+fn require_mint_allowed(config: &ProgramConfig, approved_mint: Option<&Account<ApprovedMint>>) -> Result<()> {
+    require!(!config.mint_allowlist || approved_mint.is_some(), ErrorCode::MintNotApproved);
+    Ok(())
+}
 
-    #[account(
-        mut,
-        associated_token::mint = bet_mint,
-        associated_token::authority = vault_authority
-    )]
-    pub vault: Account<'info, TokenAccount>,
+/// Bet-mint cost of `qty` shares at `price_bps`.
+fn order_cost(qty: u64, price_bps: u16, round_up: bool) -> Result<u64> {
+    let num = (qty as u128).checked_mul(price_bps as u128).ok_or(ErrorCode::Overflow)?;
+    let cost = if round_up {
+        num.div_ceil(BPS_DENOM as u128)
+    } else {
+        num / BPS_DENOM as u128
+    };
+    u64::try_from(cost).map_err(|_| error!(ErrorCode::Overflow))
+}
 
-    #[account(
-        mut,
-        close = bettor,
-        seeds = [POSITION_SEED, market.key().as_ref(), bettor.key().as_ref()],
-        bump,
-        constraint = position.owner == bettor.key() @ ErrorCode::Unauthorized
-    )]
-    pub position: Account<'info, Position>,
+/// Sends `amount` out of one of the order book's vaults, signed by the book.
+fn transfer_from_book<'info>(
+    book: &Account<'info, OrderBook>,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let seeds: &[&[u8]] = &[ORDER_BOOK_SEED, book.market.as_ref(), &[book.bump]];
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: from.to_account_info(),
+                mint: mint.to_account_info(),
+                to: to.to_account_info(),
+                authority: book.to_account_info(),
+            },
+            &[seeds],
+        ),
+        amount,
+        mint.decimals,
+    )
+}
 
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
+/// Collateral one outcome token redeems for after resolution, in bps.
+fn outcome_token_share_bps(m: &Market, outcome: Outcome) -> u64 {
+    if m.winning_outcome == Outcome::Void as u8 {
+        BPS_DENOM / 2
+    } else if m.winning_outcome == Outcome::Split as u8 {
+        match outcome {
+            Outcome::Yes => m.split_yes_bps as u64,
+            _ => BPS_DENOM - m.split_yes_bps as u64,
+        }
+    } else if m.winning_outcome == outcome as u8 {
+        BPS_DENOM
+    } else {
+        0
+    }
 }
 
-#[derive(Accounts)]
-pub struct EmergencyPause<'info> {
-    pub owner: Signer<'info>,
-    #[account(mut)]
-    pub market: Account<'info, Market>,
+/// Pays complete-set collateral from the vault to the user.
+fn pay_set_collateral(ctx: &Context<OutcomeTokens>, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let market_key = ctx.accounts.market.key();
+    let bump: u8 = ctx.bumps.vault_authority;
+    let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, market_key.as_ref(), &[bump]];
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault.to_account_info(),
+                mint: ctx.accounts.bet_mint.to_account_info(),
+                to: ctx.accounts.user_ata.to_account_info(),
+                authority: ctx.accounts.vault_authority.to_account_info(),
+            },
+            &[seeds],
+        ),
+        amount,
+        ctx.accounts.bet_mint.decimals,
+    )
 }
 
-#[derive(Accounts)]
-pub struct UpdateFeeReceiver<'info> {
-    pub owner: Signer<'info>,
-    #[account(mut)]
-    pub market: Account<'info, Market>,
+/// Checks the bettor's credential when the market is gated.
+fn require_attestation(m: &Market, attestation: Option<&Account<Attestation>>, bettor: Pubkey, now: i64) -> Result<()> {
+    if m.attestation_issuer == Pubkey::default() {
+        return Ok(());
+    }
+    let a = attestation.ok_or(ErrorCode::AttestationRequired)?;
+    require_keys_eq!(a.issuer, m.attestation_issuer, ErrorCode::AttestationRequired);
+    require_keys_eq!(a.wallet, bettor, ErrorCode::AttestationRequired);
+    require!(a.expires_at == 0 || now < a.expires_at, ErrorCode::AttestationExpired);
+    Ok(())
 }
 
-#[derive(Accounts)]
-pub struct GetPotentialPayout<'info> {
-    pub market: Account<'info, Market>,
-    pub position: Account<'info, Position>,
+/// Reads the seed slot, reveal slot and value of a Switchboard `RandomnessAccountData`.
+fn parse_randomness(data: &[u8]) -> Result<SbRandomness> {
+    require!(data.len() >= 184, ErrorCode::InvalidRandomness);
+    let discriminator = solana_sha256_hasher::hash(b"account:RandomnessAccountData");
+    require!(data[..8] == discriminator.to_bytes()[..8], ErrorCode::InvalidRandomness);
+    let u64_at = |at: usize| -> Result<u64> {
+        Ok(u64::from_le_bytes(data[at..at + 8].try_into().map_err(|_| ErrorCode::InvalidRandomness)?))
+    };
+    Ok(SbRandomness {
+        seed_slot: u64_at(104)?,
+        reveal_slot: u64_at(144)?,
+        value: data[152..184].try_into().map_err(|_| ErrorCode::InvalidRandomness)?,
+    })
 }
 
-fn owner_pubkey() -> Pubkey {
-    Pubkey::from_str(OWNER).unwrap()
+/// Whether a paid-out position may be closed without losing rewards or a pending draw.
+fn position_closable(m: &Market, p: &Position, now: i64) -> Result<bool> {
+    if m.jackpot_pending || p.rewards_owed > 0 {
+        return Ok(false);
+    }
+    Ok(pending_rewards(accrued_reward_per_stake(m, now)?, p.amount, p.reward_debt)? == 0)
 }
 
-fn fee_wallet_pubkey() -> Pubkey {
-    Pubkey::from_str(FEE_WALLET).unwrap()
+/// Stake earning liquidity-mining rewards: bettors' positions plus subsidies.
+fn reward_stake(m: &Market) -> Result<u64> {
+    total_staked(m)?
+        .checked_sub(m.house_yes)
+        .and_then(|v| v.checked_sub(m.house_no))
+        .and_then(|v| v.checked_add(m.subsidy_yes))
+        .and_then(|v| v.checked_add(m.subsidy_no))
+        .ok_or(error!(ErrorCode::Overflow))
 }
 
-fn calculate_fee(amount: u64) -> Result<u64> {
-    amount
-        .checked_mul(FEE_BPS)
-        .ok_or(ErrorCode::Overflow)?
-        .checked_div(BPS_DENOM)
-        .ok_or(ErrorCode::Overflow)
+/// Brings `reward_per_stake` up to `now` (capped at the schedule end and the cutoff).
+fn accrue_rewards(m: &mut Market, now: i64) -> Result<()> {
+    let end = now.min(m.reward_end_ts).min(m.cutoff_ts);
+    if m.reward_rate == 0 || end <= m.reward_last_ts {
+        return Ok(());
+    }
+    m.reward_per_stake = accrued_reward_per_stake(m, now)?;
+    m.reward_last_ts = end;
+    Ok(())
 }
 
-fn validate_bet_limit(mint: &Mint, position: &Position, total_amount: u64) -> Result<()> {
-    let decimals = mint.decimals as u32;
-    let max_total: u128 = (MAX_BET_LIMIT as u128)
-        .checked_mul(10u128.pow(decimals))
-        .ok_or(ErrorCode::Overflow)?;
-    
-    let current_net = position.amount as u128;
-    let fee_rate = FEE_BPS as f64 / BPS_DENOM as f64;
-    let current_total_estimate = (current_net as f64 / (1.0 - fee_rate)) as u128;
-    
-    let new_total = current_total_estimate
-        .checked_add(total_amount as u128)
+/// `reward_per_stake` as `accrue_rewards` would leave it at `now`.
+fn accrued_reward_per_stake(m: &Market, now: i64) -> Result<u128> {
+    let end = now.min(m.reward_end_ts).min(m.cutoff_ts);
+    if m.reward_rate == 0 || end <= m.reward_last_ts {
+        return Ok(m.reward_per_stake);
+    }
+    let delta = sillymarket_math::reward_index_delta((end - m.reward_last_ts) as u64, m.reward_rate, reward_stake(m)?)
         .ok_or(ErrorCode::Overflow)?;
-    
-    require!(new_total <= max_total, ErrorCode::BetExceedsLimit);
-    Ok(())
+    m.reward_per_stake.checked_add(delta).ok_or(error!(ErrorCode::Overflow))
 }
 
-fn validate_market_active(market: &Market) -> Result<()> {
-    require!(!market.resolved, ErrorCode::MarketResolved);
-    Ok(())
+fn reward_debt(reward_per_stake: u128, amount: u64) -> Result<u128> {
+    sillymarket_math::reward_debt(reward_per_stake, amount).ok_or(error!(ErrorCode::Overflow))
 }
 
-fn calculate_payout(market: &Market, position: &Position) -> Result<u64> {
-    if market.winning_outcome == Outcome::Void as u8 {
-        require!(position.amount > 0, ErrorCode::NoPayout);
-        Ok(position.amount)
-    } else {
-        require!(position.outcome == market.winning_outcome, ErrorCode::NoPayout);
+fn pending_rewards(reward_per_stake: u128, amount: u64, debt: u128) -> Result<u64> {
+    sillymarket_math::rewards_earned(reward_per_stake, amount, debt).ok_or(error!(ErrorCode::Overflow))
+}
 
-        let total_yes = market.total_yes as u128;
-        let total_no = market.total_no as u128;
-        let total_pool = total_yes.checked_add(total_no).ok_or(ErrorCode::Overflow)?;
-        
-        let winning_pool = if market.winning_outcome == Outcome::Yes as u8 {
-            total_yes
-        } else {
-            total_no
-        };
-        
-        require!(winning_pool > 0, ErrorCode::NoPayout);
+/// Moves what `amount` has earned since `debt` into `owed` and resets `debt`; returns the new `(debt, owed)`.
+fn settle_rewards(reward_per_stake: u128, amount: u64, debt: u128, owed: u64) -> Result<(u128, u64)> {
+    let owed = owed
+        .checked_add(pending_rewards(reward_per_stake, amount, debt)?)
+        .ok_or(ErrorCode::Overflow)?;
+    Ok((reward_debt(reward_per_stake, amount)?, owed))
+}
 
-        let user_amt = position.amount as u128;
-        let payout_u128 = total_pool
-            .checked_mul(user_amt)
-            .ok_or(ErrorCode::Overflow)?
-            .checked_div(winning_pool)
-            .ok_or(ErrorCode::Overflow)?;
-            
-        u64::try_from(payout_u128).map_err(|_| ErrorCode::Overflow)
+/// Fee discount from `wallet`'s season pass, or 0 when it is missing, lapsed or passes are switched off.
+fn season_pass_discount_bps(config: &SeasonPassConfig, pass: &SeasonPass, wallet: Pubkey, now: i64) -> Result<u16> {
+    require_keys_eq!(pass.wallet, wallet, ErrorCode::Unauthorized);
+    if !config.enabled || now >= pass.expires_at {
+        return Ok(0);
     }
+    Ok(config.discount_bps)
+}
+
+/// Records `winner` for match `slot` and seats it in the next round's match.
+fn record_bracket_winner(
+    b: &mut Bracket,
+    slot: usize,
+    winner: u8,
+    next_market: Option<&mut Account<Market>>,
+) -> Result<Pubkey> {
+    b.matches[slot].winner = winner;
+    if slot == 0 {
+        b.champion = winner;
+        return Ok(Pubkey::default());
+    }
+
+    let next = (slot - 1) / 2;
+    if slot % 2 == 1 {
+        b.matches[next].yes_entrant = winner;
+    } else {
+        b.matches[next].no_entrant = winner;
+    }
+    let nm = b.matches[next];
+    if nm.yes_entrant == BRACKET_TBD || nm.no_entrant == BRACKET_TBD {
+        return Ok(Pubkey::default());
+    }
+    let next_market = next_market.ok_or(ErrorCode::WrongBracket)?;
+    require_keys_eq!(next_market.key(), nm.market, ErrorCode::WrongBracket);
+    next_market.bracket_locked = false;
+    Ok(next_market.key())
 }
 
 #[error_code]
@@ -796,4 +12182,234 @@ pub enum ErrorCode {
     InvalidFeeReceiver,
     #[msg("Market metadata too long")]
     MetadataTooLong,
-}
\ No newline at end of file
+    #[msg("Invalid oracle reporter")]
+    InvalidOracleReporter,
+    #[msg("Oracle price is stale")]
+    OracleStale,
+    #[msg("Oracle price is invalid")]
+    InvalidOraclePrice,
+    #[msg("Wrong oracle account for this market")]
+    WrongOracle,
+    #[msg("Market already has bets")]
+    MarketHasBets,
+    #[msg("Too many mints for this market")]
+    TooManyMints,
+    #[msg("Market does not accept multiple mints")]
+    NotMultiMintMarket,
+    #[msg("Multi-mint markets must use the multi-mint instructions")]
+    MultiMintMarket,
+    #[msg("Reward mint authority must be the program PDA")]
+    InvalidRewardMint,
+    #[msg("Basis points out of range")]
+    InvalidBps,
+    #[msg("Emergency withdrawal has not been queued")]
+    NotQueued,
+    #[msg("Claim deadline must be after the cutoff")]
+    InvalidClaimDeadline,
+    #[msg("Claim deadline has passed")]
+    ClaimExpired,
+    #[msg("Position can still be claimed")]
+    PositionStillClaimable,
+    #[msg("Bet would push potential payouts past the market cap")]
+    MaxPayoutExceeded,
+    #[msg("Invalid anti-snipe parameters")]
+    InvalidAntiSnipe,
+    #[msg("Event time must not be before the betting cutoff")]
+    InvalidEventTs,
+    #[msg("Staged markets resolve from their rounds")]
+    StagedMarket,
+    #[msg("Market has no rounds configured")]
+    NotStagedMarket,
+    #[msg("Round count must be odd and within the supported maximum")]
+    InvalidRoundCount,
+    #[msg("Round index out of range")]
+    InvalidRoundIndex,
+    #[msg("Round results already recorded")]
+    RoundsAlreadyRecorded,
+    #[msg("No side has won a majority of rounds yet")]
+    RoundsUndecided,
+    #[msg("Wrong or missing parent market")]
+    WrongParentMarket,
+    #[msg("Parent market is not resolved yet")]
+    ParentNotResolved,
+    #[msg("Parent market condition did not fail")]
+    ConditionNotFailed,
+    #[msg("Invalid batch size or accounts")]
+    InvalidBatch,
+    #[msg("Snapshot taken too recently")]
+    SnapshotTooSoon,
+    #[msg("Market duration is below the configured minimum")]
+    MarketDurationTooShort,
+    #[msg("Cutoff must be in the future")]
+    CutoffInPast,
+    #[msg("Timestamp is too far in the future")]
+    ScheduleTooFar,
+    #[msg("Invalid position")]
+    InvalidPosition,
+    #[msg("Invalid vesting parameters")]
+    InvalidVesting,
+    #[msg("Nothing has vested yet")]
+    NothingVested,
+    #[msg("Fees must be swept before closing the vault")]
+    FeesNotSwept,
+    #[msg("Vault still holds claimable funds")]
+    VaultNotEmpty,
+    #[msg("Claim would exceed the funds deposited for this market")]
+    Insolvent,
+    #[msg("Vault balance diverges from market liabilities")]
+    InvariantViolated,
+    #[msg("This change must go through the timelock")]
+    TimelockRequired,
+    #[msg("Invalid timelock")]
+    InvalidTimelock,
+    #[msg("Queued change has expired")]
+    ChangeExpired,
+    #[msg("A governance vote is in progress for this market")]
+    VoteInProgress,
+    #[msg("No vote is open for this market")]
+    VoteNotOpen,
+    #[msg("Governance voting is not configured")]
+    GovernanceDisabled,
+    #[msg("Resolver is not registered, approved or assigned to this market")]
+    WrongResolver,
+    #[msg("A resolution proposal is pending")]
+    ProposalPending,
+    #[msg("No resolution has been proposed")]
+    NoProposal,
+    #[msg("Dispute window has closed")]
+    DisputeWindowClosed,
+    #[msg("Wrong disputer account")]
+    WrongDisputer,
+    #[msg("Action not allowed at this dispute stage")]
+    WrongDisputeStage,
+    #[msg("Receipt accounts are incomplete or the tree does not match")]
+    WrongReceiptTree,
+    #[msg("Market was not voided")]
+    NotVoided,
+    #[msg("Claims changed since the refund snapshot")]
+    RefundSnapshotStale,
+    #[msg("Invalid Merkle proof")]
+    InvalidProof,
+    #[msg("Refunds for this market are paid from the Merkle root")]
+    MerkleRefundActive,
+    #[msg("This market only accepts bets as top-level instructions")]
+    CpiNotAllowed,
+    #[msg("Oracle condition is not met")]
+    ConditionNotMet,
+    #[msg("Bet cooldown has not elapsed")]
+    BetCooldown,
+    #[msg("An identical market already exists")]
+    DuplicateMarket,
+    #[msg("Bet exceeds the allowed share of the opposing pool")]
+    BetTooLargeForPool,
+    #[msg("Yield is not enabled for this market")]
+    YieldNotEnabled,
+    #[msg("Vault funds are still lent out; withdraw them first")]
+    YieldDeployed,
+    #[msg("Position is frozen pending review")]
+    PositionFrozen,
+    #[msg("Not supported on sharded markets")]
+    ShardingUnsupported,
+    #[msg("Pool shards must be merged first")]
+    ShardsUnmerged,
+    #[msg("Fee discount NFT not held or not in the collection")]
+    NotNftHolder,
+    #[msg("Resolution cannot share a transaction with bets or claims")]
+    ResolutionSandwich,
+    #[msg("Pool odds moved past the bettor's tolerance")]
+    OddsSlippage,
+    #[msg("Fee exceeds the bettor's max_fee_bps")]
+    FeeTooHigh,
+    #[msg("Signed report is missing, malformed or from the wrong signer")]
+    InvalidReport,
+    #[msg("VAA is missing, malformed or from the wrong emitter")]
+    InvalidVaa,
+    #[msg("Token Bridge accounts don't match this market")]
+    InvalidBridgeAccounts,
+    #[msg("Bridged deposits are still owed back")]
+    BridgeRefundPending,
+    #[msg("Bridged positions are paid out through the bridge")]
+    BridgedPosition,
+    #[msg("Session expiry must be in the future and within the maximum length")]
+    InvalidSession,
+    #[msg("Session has expired")]
+    SessionExpired,
+    #[msg("Bet exceeds the session's limits")]
+    SessionLimitExceeded,
+    #[msg("House accounts are required for this market")]
+    HouseAccountsMissing,
+    #[msg("Bet mint is not on the allowlist")]
+    MintNotApproved,
+    #[msg("Vault holds nothing beyond the market's liabilities")]
+    NoSurplus,
+    #[msg("Insurance fund accounts are required for an insured bet")]
+    InsuranceAccountsMissing,
+    #[msg("Order book side is full")]
+    OrderBookFull,
+    #[msg("Order not found on the book")]
+    OrderNotFound,
+    #[msg("Bid price is below the ask price")]
+    OrdersDoNotCross,
+    #[msg("Portfolio is tracking the maximum number of markets")]
+    PortfolioFull,
+    #[msg("Market requires a valid attestation from its issuer")]
+    AttestationRequired,
+    #[msg("Attestation has expired")]
+    AttestationExpired,
+    #[msg("Market has already been linked to a legacy market")]
+    AlreadyImported,
+    #[msg("Market is not linked to a legacy market")]
+    NotImported,
+    #[msg("Random markets can only be settled by their randomness")]
+    VrfMarket,
+    #[msg("Market is not a random market")]
+    NotVrfMarket,
+    #[msg("Randomness was already requested for this market")]
+    VrfAlreadyRequested,
+    #[msg("Invalid or stale randomness account")]
+    InvalidRandomness,
+    #[msg("Randomness has not been revealed yet")]
+    RandomnessNotRevealed,
+    #[msg("Jackpot accounts are required")]
+    JackpotAccountsMissing,
+    #[msg("A jackpot draw is pending")]
+    JackpotDrawPending,
+    #[msg("Jackpot has not reached its trigger amount")]
+    JackpotNotTriggered,
+    #[msg("Reward emissions are disabled")]
+    RewardsDisabled,
+    #[msg("Reward schedule must end in the future")]
+    InvalidRewardSchedule,
+    #[msg("Market does not belong to this bracket slot")]
+    WrongBracket,
+    #[msg("Bracket match is waiting on an earlier round")]
+    BracketMatchLocked,
+    #[msg("Bracket match must resolve Yes or No to advance; use set_bracket_winner")]
+    BracketMatchUndecided,
+    #[msg("Bracket match already advanced")]
+    AlreadyAdvanced,
+    #[msg("Season passes are disabled")]
+    SeasonPassDisabled,
+    #[msg("Market is not open for bets yet")]
+    BettingNotOpen,
+    #[msg("Operator is disabled")]
+    OperatorDisabled,
+    #[msg("Operator account missing or does not match the market")]
+    WrongOperator,
+    #[msg("Only the whitelisted yield program may hold vault funds")]
+    UnsupportedYieldProgram,
+    #[msg("Claim the position's liquidity-mining rewards first")]
+    RewardsUnclaimed,
+    #[msg("Position index is above MAX_POSITIONS_PER_MARKET")]
+    PositionIndexTooHigh,
+    #[msg("Malformed place_bet extra accounts")]
+    InvalidBetExtras,
+    #[msg("Bracket match already has a winner; use advance_bracket")]
+    BracketMatchDecided,
+    #[msg("Market guard does not match the question, cutoff and mint")]
+    InvalidMarketGuard,
+    #[msg("Fee discount account missing or registered for another key")]
+    WrongFeeDiscount,
+    #[msg("Refunds for this market are not paid from a Merkle root")]
+    MerkleRefundInactive,
+}
@@ -0,0 +1,272 @@
+use crate::*;
+
+pub const REFUND_LEDGER_SEED: &[u8] = b"refund-ledger";
+pub const MAX_REFUND_LEAVES: u32 = 80_000; // Bitmap must fit the 10KB init limit
+
+pub(crate) fn publish_refund_root(
+    ctx: Context<PublishRefundRoot>,
+    root: [u8; 32],
+    leaf_count: u32,
+    total: u64,
+    expected_claimed: u64,
+) -> Result<()> {
+    require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+    require!(leaf_count > 0 && leaf_count <= MAX_REFUND_LEAVES, ErrorCode::InvalidBatch);
+
+    let m = &mut ctx.accounts.market;
+    require!(m.resolved, ErrorCode::NotResolved);
+    require!(m.winning_outcome == Outcome::Void as u8, ErrorCode::NotVoided);
+    require!(m.yield_deposited == 0, ErrorCode::YieldDeployed);
+    require!(m.total_claimed == expected_claimed, ErrorCode::RefundSnapshotStale);
+    let remaining = claimable_pool(m)?.checked_sub(m.total_claimed).ok_or(ErrorCode::Overflow)?;
+    require!(total <= remaining, ErrorCode::Insolvent);
+    m.merkle_refund = true;
+
+    let ledger = &mut ctx.accounts.refund_ledger;
+    ledger.market = m.key();
+    ledger.root = root;
+    ledger.leaf_count = leaf_count;
+    ledger.total = total;
+    ledger.claimed_amount = 0;
+    ledger.bump = ctx.bumps.refund_ledger;
+    ledger.claimed = vec![0u8; RefundLedger::bitmap_len(leaf_count)];
+
+    emit!(RefundRootPublished {
+        market: m.key(),
+        root,
+        leaf_count,
+        total,
+    });
+
+    Ok(())
+}
+
+pub(crate) fn claim_merkle_refunds<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ClaimMerkleRefunds<'info>>,
+    claims: Vec<RefundClaim>,
+) -> Result<()> {
+    require!(
+        !claims.is_empty() && claims.len() == ctx.remaining_accounts.len(),
+        ErrorCode::InvalidBatch
+    );
+    let m = &ctx.accounts.market;
+    require!(m.merkle_refund, ErrorCode::MerkleRefundInactive);
+    require!(m.resolved && m.winning_outcome == Outcome::Void as u8, ErrorCode::NotVoided);
+
+    let market_key = m.key();
+    let bump: u8 = ctx.bumps.vault_authority;
+    let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, market_key.as_ref(), &[bump]];
+    let signer: &[&[&[u8]]] = &[seeds];
+
+    for (claim, info) in claims.iter().zip(ctx.remaining_accounts.iter()) {
+        let ledger = &mut ctx.accounts.refund_ledger;
+        require!(claim.index < ledger.leaf_count, ErrorCode::InvalidProof);
+        require!(!ledger.is_claimed(claim.index), ErrorCode::AlreadyClaimed);
+        let leaf = refund_leaf(claim.index, &claim.wallet, claim.amount);
+        require!(verify_merkle_proof(&claim.proof, ledger.root, leaf), ErrorCode::InvalidProof);
+
+        let dest: InterfaceAccount<'info, TokenAccount> = InterfaceAccount::try_from(info)?;
+        require_keys_eq!(dest.mint, ctx.accounts.bet_mint.key(), ErrorCode::WrongMint);
+        require_keys_eq!(dest.owner, claim.wallet, ErrorCode::Unauthorized);
+
+        ledger.set_claimed(claim.index);
+        ledger.claimed_amount = ledger.claimed_amount.checked_add(claim.amount).ok_or(ErrorCode::Overflow)?;
+        require!(ledger.claimed_amount <= ledger.total, ErrorCode::Insolvent);
+        let m = &mut ctx.accounts.market;
+        m.total_claimed = m.total_claimed.checked_add(claim.amount).ok_or(ErrorCode::Overflow)?;
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault.to_account_info(),
+                    mint: ctx.accounts.bet_mint.to_account_info(),
+                    to: info.clone(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                signer,
+            ),
+            claim.amount,
+            ctx.accounts.bet_mint.decimals,
+        )?;
+
+        emit!(MerkleRefundClaimed {
+            market: market_key,
+            wallet: claim.wallet,
+            index: claim.index,
+            amount: claim.amount,
+        });
+    }
+
+    Ok(())
+}
+
+pub(crate) fn close_refunded_positions<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CloseRefundedPositions<'info>>,
+) -> Result<()> {
+    let m = &ctx.accounts.market;
+    require!(m.merkle_refund, ErrorCode::NotVoided);
+    let now = Clock::get()?.unix_timestamp;
+    let pairs = ctx.remaining_accounts;
+    require!(!pairs.is_empty() && pairs.len() % 2 == 0, ErrorCode::InvalidBatch);
+
+    let mut closed: u32 = 0;
+    for pair in pairs.chunks(2) {
+        let (position_info, owner_info) = (&pair[0], &pair[1]);
+        let p: Account<'info, Position> = Account::try_from(position_info)?;
+        require_keys_eq!(p.market, m.key(), ErrorCode::WrongMarket);
+        require_keys_eq!(p.owner, owner_info.key(), ErrorCode::Unauthorized);
+        // Left open until its liquidity-mining rewards are claimed.
+        if !position_closable(m, &p, now)? {
+            continue;
+        }
+
+        let tip = position_info.lamports()
+            .checked_mul(RENT_CRANK_BPS)
+            .ok_or(ErrorCode::Overflow)?
+            / BPS_DENOM;
+        position_info.sub_lamports(tip)?;
+        ctx.accounts.cranker.add_lamports(tip)?;
+        p.close(owner_info.clone())?;
+        closed += 1;
+    }
+
+    emit!(RefundedPositionsClosed {
+        market: m.key(),
+        cranker: ctx.accounts.cranker.key(),
+        count: closed,
+    });
+
+    Ok(())
+}
+
+#[account]
+pub struct RefundLedger {
+    pub market: Pubkey,
+    pub root: [u8; 32],
+    pub leaf_count: u32,
+    pub total: u64,
+    pub claimed_amount: u64,
+    pub bump: u8,
+    pub claimed: Vec<u8>, // one bit per leaf
+}
+
+impl RefundLedger {
+    pub const BASE_LEN: usize = 8 + 32 + 32 + 4 + 8 + 8 + 1 + 4;
+
+    pub fn bitmap_len(leaf_count: u32) -> usize {
+        (leaf_count as usize).div_ceil(8)
+    }
+
+    pub fn space(leaf_count: u32) -> usize {
+        Self::BASE_LEN + Self::bitmap_len(leaf_count)
+    }
+
+    pub fn is_claimed(&self, index: u32) -> bool {
+        self.claimed[index as usize / 8] & (1 << (index % 8)) != 0
+    }
+
+    pub fn set_claimed(&mut self, index: u32) {
+        self.claimed[index as usize / 8] |= 1 << (index % 8);
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RefundClaim {
+    pub index: u32,
+    pub wallet: Pubkey,
+    pub amount: u64,
+    pub proof: Vec<[u8; 32]>,
+}
+
+#[event]
+pub struct RefundRootPublished {
+    pub market: Pubkey,
+    pub root: [u8; 32],
+    pub leaf_count: u32,
+    pub total: u64,
+}
+
+#[event]
+pub struct MerkleRefundClaimed {
+    pub market: Pubkey,
+    pub wallet: Pubkey,
+    pub index: u32,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RefundedPositionsClosed {
+    pub market: Pubkey,
+    pub cranker: Pubkey,
+    pub count: u32,
+}
+
+#[derive(Accounts)]
+#[instruction(root: [u8; 32], leaf_count: u32)]
+pub struct PublishRefundRoot<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(
+        init,
+        payer = owner,
+        space = RefundLedger::space(leaf_count),
+        seeds = [REFUND_LEDGER_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub refund_ledger: Account<'info, RefundLedger>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimMerkleRefunds<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(mut, has_one = bet_mint, has_one = vault)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [REFUND_LEDGER_SEED, market.key().as_ref()],
+        bump = refund_ledger.bump
+    )]
+    pub refund_ledger: Account<'info, RefundLedger>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA signer for the vault, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CloseRefundedPositions<'info> {
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    pub market: Account<'info, Market>,
+}
+
+/// Leaves are domain-separated from inner nodes so a node can't be replayed as a leaf.
+pub(crate) fn refund_leaf(index: u32, wallet: &Pubkey, amount: u64) -> [u8; 32] {
+    keccak::hashv(&[&[0u8], &index.to_le_bytes(), wallet.as_ref(), &amount.to_le_bytes()]).0
+}
+
+/// Sorted-pair Merkle proof check, so proofs carry no left/right flags.
+pub(crate) fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let mut node = leaf;
+    for sibling in proof {
+        node = if node <= *sibling {
+            keccak::hashv(&[&[1u8], &node, sibling]).0
+        } else {
+            keccak::hashv(&[&[1u8], sibling, &node]).0
+        };
+    }
+    node == root
+}
@@ -0,0 +1,190 @@
+use crate::*;
+
+pub const OPERATOR_SEED: &[u8] = b"operator";
+pub const MAX_OPERATOR_FEE_BPS: u16 = 1_000; // 10%
+
+pub(crate) fn register_operator(
+    ctx: Context<RegisterOperator>,
+    operator_id: u64,
+    admin: Pubkey,
+    fee_receiver: Pubkey,
+    platform_cut_bps: u16,
+) -> Result<()> {
+    require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+    require!(platform_cut_bps as u64 <= BPS_DENOM, ErrorCode::InvalidBps);
+    require!(fee_receiver != Pubkey::default(), ErrorCode::InvalidFeeReceiver);
+
+    let op = &mut ctx.accounts.operator;
+    op.operator_id = operator_id;
+    op.admin = admin;
+    op.fee_receiver = fee_receiver;
+    op.fee_bps = 0;
+    op.platform_cut_bps = platform_cut_bps;
+    op.enabled = true;
+    op.market_count = 0;
+    op.bump = ctx.bumps.operator;
+
+    emit!(OperatorRegistered {
+        operator: op.key(),
+        operator_id,
+        admin,
+        platform_cut_bps,
+    });
+
+    Ok(())
+}
+
+pub(crate) fn set_operator_platform(ctx: Context<SetOperatorPlatform>, platform_cut_bps: u16, enabled: bool) -> Result<()> {
+    require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+    require!(platform_cut_bps as u64 <= BPS_DENOM, ErrorCode::InvalidBps);
+
+    let op = &mut ctx.accounts.operator;
+    op.platform_cut_bps = platform_cut_bps;
+    op.enabled = enabled;
+
+    emit!(OperatorPlatformSet {
+        operator: op.key(),
+        platform_cut_bps,
+        enabled,
+    });
+
+    Ok(())
+}
+
+pub(crate) fn update_operator(
+    ctx: Context<UpdateOperator>,
+    new_admin: Pubkey,
+    fee_receiver: Pubkey,
+    fee_bps: u16,
+) -> Result<()> {
+    let op = &mut ctx.accounts.operator;
+    require_keys_eq!(ctx.accounts.admin.key(), op.admin, ErrorCode::Unauthorized);
+    require!(fee_bps <= MAX_OPERATOR_FEE_BPS, ErrorCode::InvalidBps);
+    require!(fee_receiver != Pubkey::default(), ErrorCode::InvalidFeeReceiver);
+
+    op.admin = new_admin;
+    op.fee_receiver = fee_receiver;
+    op.fee_bps = fee_bps;
+
+    emit!(OperatorUpdated {
+        operator: op.key(),
+        admin: new_admin,
+        fee_receiver,
+        fee_bps,
+    });
+
+    Ok(())
+}
+
+/// A whitelabel frontend running its own markets on this deployment.
+#[account]
+pub struct Operator {
+    pub operator_id: u64, // Namespace; also the PDA seed
+    pub admin: Pubkey,
+    pub fee_receiver: Pubkey,
+    pub fee_bps: u16,
+    pub platform_cut_bps: u16,
+    pub enabled: bool,
+    pub market_count: u64,
+    pub bump: u8,
+}
+
+impl Operator {
+    pub const LEN: usize = 8 + 8 + 32 + 32 + 2 + 2 + 1 + 8 + 1;
+}
+
+#[event]
+pub struct OperatorRegistered {
+    pub operator: Pubkey,
+    pub operator_id: u64,
+    pub admin: Pubkey,
+    pub platform_cut_bps: u16,
+}
+
+#[event]
+pub struct OperatorPlatformSet {
+    pub operator: Pubkey,
+    pub platform_cut_bps: u16,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct OperatorUpdated {
+    pub operator: Pubkey,
+    pub admin: Pubkey,
+    pub fee_receiver: Pubkey,
+    pub fee_bps: u16,
+}
+
+#[event]
+pub struct PlatformFeeTaken {
+    pub market: Pubkey,
+    pub operator: Pubkey,
+    pub amount: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(operator_id: u64)]
+pub struct RegisterOperator<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Operator::LEN,
+        seeds = [OPERATOR_SEED, &operator_id.to_le_bytes()],
+        bump
+    )]
+    pub operator: Account<'info, Operator>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetOperatorPlatform<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [OPERATOR_SEED, &operator.operator_id.to_le_bytes()], bump = operator.bump)]
+    pub operator: Account<'info, Operator>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateOperator<'info> {
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [OPERATOR_SEED, &operator.operator_id.to_le_bytes()], bump = operator.bump)]
+    pub operator: Account<'info, Operator>,
+}
+
+pub(crate) fn is_operator_admin(market: &Market, operator: Option<&Account<Operator>>, signer: Pubkey) -> bool {
+    market.operator != Pubkey::default()
+        && operator.is_some_and(|op| op.key() == market.operator && op.admin == signer)
+}
+
+/// Markets are created by the owner, or by an enabled operator's admin in that operator's namespace.
+pub(crate) fn require_market_creator(signer: Pubkey, operator: Option<&Account<Operator>>) -> Result<()> {
+    match operator {
+        Some(op) => {
+            require_keys_eq!(signer, op.admin, ErrorCode::Unauthorized);
+            require!(op.enabled, ErrorCode::OperatorDisabled);
+        }
+        None => require_keys_eq!(signer, owner_pubkey(), ErrorCode::Unauthorized),
+    }
+    Ok(())
+}
+
+/// Seed after `MARKET_SEED`: the global counter, or the operator id and its own counter.
+pub(crate) fn market_seed(stats: &ProgramStats, operator: Option<&Account<Operator>>) -> Vec<u8> {
+    match operator {
+        Some(op) => [op.operator_id.to_le_bytes(), op.market_count.to_le_bytes()].concat(),
+        None => stats.total_markets.to_le_bytes().to_vec(),
+    }
+}
+
+/// Hands a new market to `op` under its fee settings and bumps its market counter.
+pub(crate) fn assign_operator(m: &mut Market, op: &mut Account<Operator>) -> Result<()> {
+    m.operator = op.key();
+    m.fee_receiver = op.fee_receiver;
+    m.fee_bps = op.fee_bps;
+    op.market_count = op.market_count.checked_add(1).ok_or(ErrorCode::Overflow)?;
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use crate::*;
+
+pub const MAX_ROUNDS: usize = 7; // Longest supported series (best-of-7)
+
+pub(crate) fn configure_rounds(ctx: Context<ConfigureRounds>, round_count: u8) -> Result<()> {
+    require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+    require!(
+        round_count as usize <= MAX_ROUNDS && (round_count == 0 || round_count % 2 == 1),
+        ErrorCode::InvalidRoundCount
+    );
+
+    let m = &mut ctx.accounts.market;
+    validate_market_active(m)?;
+    require!(
+        m.round_results.iter().all(|r| *r == Outcome::Unset as u8),
+        ErrorCode::RoundsAlreadyRecorded
+    );
+
+    m.round_count = round_count;
+
+    emit!(RoundsConfigured {
+        market: m.key(),
+        round_count,
+    });
+
+    Ok(())
+}
+
+pub(crate) fn record_round(ctx: Context<RecordRound>, round_index: u8, result: Outcome) -> Result<()> {
+    require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+    require!(matches!(result, Outcome::Yes | Outcome::No), ErrorCode::InvalidOutcomeArg);
+
+    let m = &mut ctx.accounts.market;
+    validate_market_active(m)?;
+    require!(m.round_count > 0, ErrorCode::NotStagedMarket);
+    require!(round_index < m.round_count, ErrorCode::InvalidRoundIndex);
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(now >= m.cutoff_ts, ErrorCode::TooEarly);
+
+    let slot = &mut m.round_results[round_index as usize];
+    require!(*slot == Outcome::Unset as u8, ErrorCode::RoundsAlreadyRecorded);
+    *slot = result as u8;
+
+    emit!(RoundRecorded {
+        market: m.key(),
+        round_index,
+        result: result as u8,
+    });
+
+    Ok(())
+}
+
+pub(crate) fn finalize_from_rounds(ctx: Context<FinalizeFromRounds>) -> Result<()> {
+    let m = &mut ctx.accounts.market;
+    require!(!m.resolved, ErrorCode::AlreadyResolved);
+    require!(m.round_count > 0, ErrorCode::NotStagedMarket);
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(now >= m.cutoff_ts && now >= m.event_ts, ErrorCode::TooEarly);
+    reserve_keeper_tip(m, ctx.accounts.caller.key())?;
+
+    if parent_condition_failed(m, ctx.accounts.parent_market.as_ref())? {
+        return void_market_state(m, &mut ctx.accounts.program_stats, ResolutionReason::ConditionFailed, now);
+    }
+
+    let outcome = outcome_from_rounds(m).ok_or(ErrorCode::RoundsUndecided)?;
+    emit!(settle_market(m, &mut ctx.accounts.program_stats, outcome, ResolutionReason::Rounds, &ctx.accounts.instructions)?);
+
+    Ok(())
+}
+
+#[event]
+pub struct RoundsConfigured {
+    pub market: Pubkey,
+    pub round_count: u8,
+}
+
+#[event]
+pub struct RoundRecorded {
+    pub market: Pubkey,
+    pub round_index: u8,
+    pub result: u8,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureRounds<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct RecordRound<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeFromRounds<'info> {
+    pub caller: Signer<'info>,
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+    /// Required for conditional markets.
+    pub parent_market: Option<Account<'info, Market>>,
+    /// CHECK: instructions sysvar, pinned by address.
+    #[account(address = ix_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+pub(crate) fn outcome_from_rounds(market: &Market) -> Option<Outcome> {
+    let rounds = &market.round_results[..market.round_count as usize];
+    let yes = rounds.iter().filter(|r| **r == Outcome::Yes as u8).count();
+    let no = rounds.iter().filter(|r| **r == Outcome::No as u8).count();
+    let majority = market.round_count as usize / 2 + 1;
+    if yes >= majority {
+        Some(Outcome::Yes)
+    } else if no >= majority {
+        Some(Outcome::No)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,268 @@
+use crate::*;
+
+pub const SUBSIDY_SEED: &[u8] = b"subsidy";
+
+pub(crate) fn subsidize_market(ctx: Context<SubsidizeMarket>, amount: u64, side: SubsidySide) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+
+    let now = Clock::get()?.unix_timestamp;
+    let m = &mut ctx.accounts.market;
+    validate_market_active(m)?;
+    require!(!m.multi_mint, ErrorCode::MultiMintMarket);
+    require!(!m.emergency_paused, ErrorCode::EmergencyPaused);
+    require!(!m.bracket_locked, ErrorCode::BracketMatchLocked);
+    require!(now < m.cutoff_ts, ErrorCode::BettingClosed);
+
+    let vault_before = ctx.accounts.vault.amount;
+    token_interface::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.donor_ata.to_account_info(),
+                mint: ctx.accounts.bet_mint.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.donor.to_account_info(),
+            },
+        ),
+        amount,
+        ctx.accounts.bet_mint.decimals,
+    )?;
+    ctx.accounts.vault.reload()?;
+    let received = ctx.accounts.vault.amount
+        .checked_sub(vault_before)
+        .ok_or(ErrorCode::Overflow)?;
+    require!(received > 0, ErrorCode::InvalidAmount);
+
+    let (to_yes, to_no) = match side {
+        SubsidySide::Yes => (received, 0),
+        SubsidySide::No => (0, received),
+        SubsidySide::Both => {
+            let half = received / 2;
+            (half, received - half)
+        }
+    };
+
+    accrue_rewards(m, now)?;
+    m.subsidy_yes = m.subsidy_yes.checked_add(to_yes).ok_or(ErrorCode::Overflow)?;
+    m.subsidy_no = m.subsidy_no.checked_add(to_no).ok_or(ErrorCode::Overflow)?;
+
+    let s = &mut ctx.accounts.subsidy;
+    if s.donor == Pubkey::default() {
+        s.donor = ctx.accounts.donor.key();
+        s.market = m.key();
+        s.bump = ctx.bumps.subsidy;
+    }
+    let staked = s.amount_yes.checked_add(s.amount_no).ok_or(ErrorCode::Overflow)?;
+    (s.reward_debt, s.rewards_owed) = settle_rewards(m.reward_per_stake, staked, s.reward_debt, s.rewards_owed)?;
+    s.amount_yes = s.amount_yes.checked_add(to_yes).ok_or(ErrorCode::Overflow)?;
+    s.amount_no = s.amount_no.checked_add(to_no).ok_or(ErrorCode::Overflow)?;
+    s.reward_debt = reward_debt(m.reward_per_stake, staked.checked_add(received).ok_or(ErrorCode::Overflow)?)?;
+
+    emit!(MarketSubsidized {
+        market: m.key(),
+        donor: s.donor,
+        side,
+        amount_yes: to_yes,
+        amount_no: to_no,
+    });
+
+    Ok(())
+}
+
+pub(crate) fn claim_subsidy_refund(ctx: Context<ClaimSubsidyRefund>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let m = &mut ctx.accounts.market;
+    let s = &mut ctx.accounts.subsidy;
+    require!(m.resolved, ErrorCode::NotResolved);
+    require!(m.yield_deposited == 0, ErrorCode::YieldDeployed);
+    require!(now <= m.claim_deadline_ts, ErrorCode::ClaimExpired);
+    let staked = s.amount_yes.checked_add(s.amount_no).ok_or(ErrorCode::Overflow)?;
+    require!(staked > 0, ErrorCode::AlreadyClaimed);
+
+    let refund = if m.winning_outcome == Outcome::Void as u8 {
+        s.amount_yes.checked_add(s.amount_no).ok_or(ErrorCode::Overflow)?
+    } else if m.winning_outcome == Outcome::Yes as u8 {
+        s.amount_yes
+    } else if m.winning_outcome == Outcome::No as u8 {
+        s.amount_no
+    } else {
+        0 // a Draw or split pays both sides' subsidies to the winners
+    };
+    m.subsidy_refunded = m.subsidy_refunded.checked_add(refund).ok_or(ErrorCode::Overflow)?;
+
+    // Rewards stop accruing at cutoff, so settling now captures them all.
+    accrue_rewards(m, now)?;
+    (s.reward_debt, s.rewards_owed) = settle_rewards(m.reward_per_stake, staked, s.reward_debt, s.rewards_owed)?;
+    s.amount_yes = 0;
+    s.amount_no = 0;
+    s.reward_debt = 0;
+
+    let market_key = m.key();
+    if refund > 0 {
+        let bump: u8 = ctx.bumps.vault_authority;
+        let seeds: &[&[u8]] = &[VAULT_AUTH_SEED, market_key.as_ref(), &[bump]];
+        let signer: &[&[&[u8]]] = &[seeds];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault.to_account_info(),
+                    mint: ctx.accounts.bet_mint.to_account_info(),
+                    to: ctx.accounts.donor_ata.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                signer,
+            ),
+            refund,
+            ctx.accounts.bet_mint.decimals,
+        )?;
+    }
+
+    emit!(SubsidyRefunded {
+        market: market_key,
+        donor: ctx.accounts.subsidy.donor,
+        amount: refund,
+    });
+
+    if ctx.accounts.subsidy.rewards_owed == 0 {
+        ctx.accounts.subsidy.close(ctx.accounts.donor.to_account_info())?;
+    }
+
+    Ok(())
+}
+
+#[account]
+pub struct Subsidy {
+    pub donor: Pubkey,
+    pub market: Pubkey,
+    pub amount_yes: u64,
+    pub amount_no: u64,
+    pub bump: u8,
+    pub reward_debt: u128,
+    pub rewards_owed: u64,
+}
+
+impl Subsidy {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 1 + 16 + 8;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SubsidySide {
+    Yes,
+    No,
+    Both,
+}
+
+#[event]
+pub struct MarketSubsidized {
+    pub market: Pubkey,
+    pub donor: Pubkey,
+    pub side: SubsidySide,
+    pub amount_yes: u64,
+    pub amount_no: u64,
+}
+
+#[event]
+pub struct SubsidyRefunded {
+    pub market: Pubkey,
+    pub donor: Pubkey,
+    pub amount: u64,
+}
+
+#[derive(Accounts)]
+pub struct SubsidizeMarket<'info> {
+    #[account(mut)]
+    pub donor: Signer<'info>,
+
+    #[account(mut, has_one = bet_mint)]
+    pub market: Account<'info, Market>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = bet_mint,
+        associated_token::authority = donor,
+        associated_token::token_program = token_program
+    )]
+    pub donor_ata: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: PDA signer for the vault, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = bet_mint,
+        associated_token::authority = vault_authority,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = donor,
+        space = Subsidy::LEN,
+        seeds = [SUBSIDY_SEED, market.key().as_ref(), donor.key().as_ref()],
+        bump
+    )]
+    pub subsidy: Account<'info, Subsidy>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimSubsidyRefund<'info> {
+    #[account(mut)]
+    pub donor: Signer<'info>,
+
+    #[account(mut, has_one = bet_mint)]
+    pub market: Account<'info, Market>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = bet_mint,
+        associated_token::authority = donor,
+        associated_token::token_program = token_program
+    )]
+    pub donor_ata: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: PDA signer for the vault, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = bet_mint,
+        associated_token::authority = vault_authority,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [SUBSIDY_SEED, market.key().as_ref(), donor.key().as_ref()],
+        bump = subsidy.bump,
+        constraint = subsidy.donor == donor.key() @ ErrorCode::Unauthorized
+    )]
+    pub subsidy: Account<'info, Subsidy>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Subsidy sitting on the side that did not win; it is paid out to winners.
+pub(crate) fn losing_subsidy(market: &Market, winning_outcome: u8) -> u64 {
+    if winning_outcome == Outcome::Yes as u8 {
+        market.subsidy_no
+    } else if winning_outcome == Outcome::No as u8 {
+        market.subsidy_yes
+    } else if winning_outcome == Outcome::Draw as u8 || winning_outcome == Outcome::Split as u8 {
+        market.subsidy_yes.saturating_add(market.subsidy_no)
+    } else {
+        0
+    }
+}
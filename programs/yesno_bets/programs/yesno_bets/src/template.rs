@@ -0,0 +1,288 @@
+use crate::*;
+
+pub const TEMPLATE_SEED: &[u8] = b"market-template";
+
+pub(crate) fn create_template(ctx: Context<CreateTemplate>, template_id: u64, params: TemplateParams) -> Result<()> {
+    require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+    validate_template_params(&params)?;
+
+    let t = &mut ctx.accounts.template;
+    t.template_id = template_id;
+    t.bet_mint = ctx.accounts.bet_mint.key();
+    t.bump = ctx.bumps.template;
+    t.apply(params);
+
+    emit!(TemplateUpdated {
+        template: t.key(),
+        template_id,
+    });
+
+    Ok(())
+}
+
+pub(crate) fn update_template(ctx: Context<UpdateTemplate>, params: TemplateParams) -> Result<()> {
+    require_keys_eq!(ctx.accounts.owner.key(), owner_pubkey(), ErrorCode::Unauthorized);
+    validate_template_params(&params)?;
+
+    let t = &mut ctx.accounts.template;
+    t.apply(params);
+
+    emit!(TemplateUpdated {
+        template: t.key(),
+        template_id: t.template_id,
+    });
+
+    Ok(())
+}
+
+pub(crate) fn create_from_template(ctx: Context<CreateFromTemplate>, question: String) -> Result<()> {
+    require_market_creator(ctx.accounts.owner.key(), ctx.accounts.operator.as_ref())?;
+    require!(question.len() <= 280, ErrorCode::MetadataTooLong);
+    require_mint_allowed(&ctx.accounts.config, ctx.accounts.approved_mint.as_ref())?;
+
+    let t = &ctx.accounts.template;
+    let now = Clock::get()?.unix_timestamp;
+    let cutoff_ts = now.checked_add(t.duration_secs).ok_or(ErrorCode::Overflow)?;
+    let event_ts = cutoff_ts.checked_add(t.event_offset_secs).ok_or(ErrorCode::Overflow)?;
+    let claim_deadline_ts = event_ts.checked_add(t.claim_window_secs).ok_or(ErrorCode::Overflow)?;
+    let (event_ts, claim_deadline_ts) =
+        validate_schedule(
+            now,
+            cutoff_ts,
+            Some(event_ts),
+            Some(claim_deadline_ts),
+            ctx.accounts.config.min_duration,
+        )?;
+
+    let stats = &mut ctx.accounts.program_stats;
+    let market_id = stats.total_markets;
+    stats.total_markets = stats.total_markets.checked_add(1).ok_or(ErrorCode::Overflow)?;
+
+    let m = &mut ctx.accounts.market;
+    init_market(
+        m,
+        ctx.accounts.owner.key(),
+        ctx.accounts.bet_mint.key(),
+        ctx.accounts.vault.key(),
+        ctx.accounts.vault_authority.key(),
+        cutoff_ts,
+        event_ts,
+        claim_deadline_ts,
+        now,
+    );
+    m.market_id = market_id;
+    m.bump = ctx.bumps.market;
+    m.fee_receiver = t.fee_receiver;
+    m.price_feed = t.price_feed;
+    m.usd_bet_cap = t.usd_bet_cap;
+    m.winner_rake_bps = t.winner_rake_bps;
+    m.max_payout = t.max_payout;
+    m.max_multiplier_bps = t.max_multiplier_bps;
+    if let Some(op) = ctx.accounts.operator.as_mut() {
+        assign_operator(m, op)?;
+    }
+    claim_market_guard(
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.market_guard.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        ctx.program_id,
+        m.key(),
+        market_guard_hash(&question, cutoff_ts, &m.bet_mint),
+    )?;
+
+    let metadata = &mut ctx.accounts.market_metadata;
+    metadata.market = m.key();
+    metadata.question = question;
+    metadata.category = t.category.clone();
+    metadata.created_at = now;
+
+    emit!(MarketCreated {
+        market: m.key(),
+        creator: m.creator,
+        cutoff_ts: m.cutoff_ts,
+        bet_mint: m.bet_mint,
+        question: metadata.question.clone(),
+        category: metadata.category.clone(),
+        claim_deadline_ts: m.claim_deadline_ts,
+        event_ts: m.event_ts,
+    });
+
+    Ok(())
+}
+
+/// Reusable market configuration for `create_from_template`.
+#[account]
+pub struct MarketTemplate {
+    pub template_id: u64,
+    pub bet_mint: Pubkey,
+    pub fee_receiver: Pubkey,
+    pub price_feed: Pubkey,
+    pub usd_bet_cap: u64,
+    pub max_payout: u64,
+    pub max_multiplier_bps: u32,
+    pub winner_rake_bps: u16,
+    pub duration_secs: i64,
+    pub event_offset_secs: i64,
+    pub claim_window_secs: i64,
+    pub category: String,
+    pub bump: u8,
+}
+
+impl MarketTemplate {
+    pub const LEN: usize = 8
+        + 8 + 32 + 32 + 32
+        + 8 + 8 + 4 + 2
+        + 8 + 8 + 8
+        + (4 + 50) + 1;
+
+    fn apply(&mut self, params: TemplateParams) {
+        self.fee_receiver = params.fee_receiver;
+        self.price_feed = params.price_feed;
+        self.usd_bet_cap = params.usd_bet_cap;
+        self.max_payout = params.max_payout;
+        self.max_multiplier_bps = params.max_multiplier_bps;
+        self.winner_rake_bps = params.winner_rake_bps;
+        self.duration_secs = params.duration_secs;
+        self.event_offset_secs = params.event_offset_secs;
+        self.claim_window_secs = params.claim_window_secs;
+        self.category = params.category;
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct TemplateParams {
+    pub fee_receiver: Pubkey,
+    pub price_feed: Pubkey,
+    pub usd_bet_cap: u64,
+    pub max_payout: u64,
+    pub max_multiplier_bps: u32,
+    pub winner_rake_bps: u16,
+    pub duration_secs: i64,
+    pub event_offset_secs: i64,
+    pub claim_window_secs: i64,
+    pub category: String,
+}
+
+#[event]
+pub struct TemplateUpdated {
+    pub template: Pubkey,
+    pub template_id: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(template_id: u64)]
+pub struct CreateTemplate<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = MarketTemplate::LEN,
+        seeds = [TEMPLATE_SEED, template_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub template: Account<'info, MarketTemplate>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTemplate<'info> {
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [TEMPLATE_SEED, template.template_id.to_le_bytes().as_ref()],
+        bump = template.bump
+    )]
+    pub template: Account<'info, MarketTemplate>,
+}
+
+#[derive(Accounts)]
+pub struct CreateFromTemplate<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        has_one = bet_mint,
+        seeds = [TEMPLATE_SEED, template.template_id.to_le_bytes().as_ref()],
+        bump = template.bump
+    )]
+    pub template: Account<'info, MarketTemplate>,
+
+    #[account(mut, seeds = [b"program-stats"], bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+
+    /// Operator creating the market in its namespace; `owner` must be its admin.
+    #[account(mut, seeds = [OPERATOR_SEED, &operator.operator_id.to_le_bytes()], bump = operator.bump)]
+    pub operator: Option<Account<'info, Operator>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Market::LEN,
+        seeds = [MARKET_SEED, &market_seed(&program_stats, operator.as_ref())],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = MarketMetadata::LEN,
+        seeds = [b"market-metadata", market.key().as_ref()],
+        bump
+    )]
+    pub market_metadata: Account<'info, MarketMetadata>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+
+    /// Required while the mint allowlist is on.
+    #[account(seeds = [APPROVED_MINT_SEED, bet_mint.key().as_ref()], bump = approved_mint.bump)]
+    pub approved_mint: Option<Account<'info, ApprovedMint>>,
+
+    /// CHECK: duplicate guard PDA; the cutoff comes from the clock, so the handler creates it.
+    #[account(mut)]
+    pub market_guard: UncheckedAccount<'info>,
+
+    /// CHECK: PDA signer for the vault, validated by seeds.
+    #[account(seeds = [VAULT_AUTH_SEED, market.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = bet_mint,
+        associated_token::authority = vault_authority,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+pub(crate) fn validate_template_params(params: &TemplateParams) -> Result<()> {
+    require!(params.category.len() <= 50, ErrorCode::MetadataTooLong);
+    require!(params.fee_receiver != Pubkey::default(), ErrorCode::InvalidFeeReceiver);
+    require!(params.winner_rake_bps as u64 <= BPS_DENOM, ErrorCode::InvalidBps);
+    require!(
+        params.max_multiplier_bps == 0 || params.max_multiplier_bps as u64 >= BPS_DENOM,
+        ErrorCode::InvalidBps
+    );
+    require!(
+        (params.usd_bet_cap == 0) == (params.price_feed == Pubkey::default()),
+        ErrorCode::WrongOracle
+    );
+    require!(
+        params.duration_secs > 0 && params.event_offset_secs >= 0 && params.claim_window_secs > 0,
+        ErrorCode::InvalidCutoff
+    );
+    Ok(())
+}